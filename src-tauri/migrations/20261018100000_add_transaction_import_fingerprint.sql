@@ -0,0 +1,7 @@
+-- Identity for rows imported from statement files, which have no Plaid id to
+-- deduplicate on. Unique per account so re-importing a statement is a no-op.
+ALTER TABLE "transaction" ADD COLUMN import_fingerprint TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transaction_import_fingerprint
+    ON "transaction"(account_id, import_fingerprint)
+    WHERE import_fingerprint IS NOT NULL;
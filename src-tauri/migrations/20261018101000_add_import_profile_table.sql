@@ -0,0 +1,18 @@
+-- Column layouts for banks without a built-in importer. Column indexes start at 0.
+-- Either amount_column is set, or both debit_column and credit_column are.
+CREATE TABLE IF NOT EXISTS import_profile (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL,
+    delimiter TEXT NOT NULL DEFAULT ',' CHECK(length(delimiter) = 1),
+    skip_rows INTEGER NOT NULL DEFAULT 0 CHECK(skip_rows >= 0),
+    date_column INTEGER NOT NULL CHECK(date_column >= 0),
+    date_format TEXT NOT NULL,
+    amount_column INTEGER CHECK(amount_column >= 0),
+    sign_convention TEXT NOT NULL DEFAULT 'DEBITS_NEGATIVE'
+        CHECK(sign_convention IN ('DEBITS_NEGATIVE', 'DEBITS_POSITIVE')),
+    debit_column INTEGER CHECK(debit_column >= 0),
+    credit_column INTEGER CHECK(credit_column >= 0),
+    -- JSON array of column indexes joined to build the transaction name
+    description_columns TEXT NOT NULL,
+    CHECK(amount_column IS NOT NULL OR (debit_column IS NOT NULL AND credit_column IS NOT NULL))
+);
@@ -0,0 +1,38 @@
+-- Parsed statements waiting for the user to review them before they are written to
+-- the ledger. Sessions nobody commits are deleted once they expire.
+CREATE TABLE IF NOT EXISTS import_session (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    importer_id TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    statement_balance_cents INTEGER,
+    statement_balance_as_of TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT NOT NULL,
+    FOREIGN KEY (account_id) REFERENCES account(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_import_session_expires_at ON import_session(expires_at);
+
+-- One row per statement line. Lines the parser rejected are kept as INVALID rows
+-- with the raw line as their name so the preview can show them.
+CREATE TABLE IF NOT EXISTS import_staged_row (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    row_index INTEGER NOT NULL,
+    date TEXT,
+    name TEXT NOT NULL,
+    amount_cents INTEGER,
+    external_id TEXT,
+    category_hint TEXT,
+    -- JSON array of the splits the source file divided the row into
+    splits TEXT NOT NULL DEFAULT '[]',
+    import_fingerprint TEXT,
+    -- Cleared when the category is deleted, the row then falls back to the default category
+    proposed_category_id INTEGER,
+    duplicate INTEGER NOT NULL DEFAULT 0 CHECK (duplicate IN (0, 1)),
+    status TEXT NOT NULL CHECK(status IN ('VALID', 'INVALID')),
+    reason TEXT,
+    FOREIGN KEY (session_id) REFERENCES import_session(id) ON DELETE CASCADE,
+    FOREIGN KEY (proposed_category_id) REFERENCES category(id) ON DELETE SET NULL,
+    UNIQUE (session_id, row_index)
+);
@@ -0,0 +1,20 @@
+-- One row per statement written to the ledger, so an import can be reviewed and undone
+CREATE TABLE IF NOT EXISTS import_batch (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    importer_id TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    -- SHA-256 of the file contents, hex encoded
+    file_hash TEXT NOT NULL,
+    row_count INTEGER NOT NULL DEFAULT 0,
+    imported_at TEXT NOT NULL DEFAULT (datetime('now')),
+    rolled_back_at TEXT,
+    FOREIGN KEY (account_id) REFERENCES account(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_import_batch_account_id ON import_batch(account_id);
+
+ALTER TABLE "transaction" ADD COLUMN import_batch_id INTEGER REFERENCES import_batch(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_transaction_import_batch_id ON "transaction"(import_batch_id);
+
+-- Hash of the file as it was when the session was previewed
+ALTER TABLE import_session ADD COLUMN file_hash TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,19 @@
+-- User-defined rules that pick a category for new transactions. Conditions left NULL
+-- match anything; amount bounds apply to the absolute amount in cents.
+CREATE TABLE IF NOT EXISTS categorization_rule (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    enabled INTEGER NOT NULL DEFAULT 1 CHECK (enabled IN (0, 1)),
+    description_pattern TEXT,
+    description_match TEXT NOT NULL DEFAULT 'CONTAINS'
+        CHECK(description_match IN ('CONTAINS', 'REGEX')),
+    merchant_entity_id TEXT,
+    account_id INTEGER,
+    min_amount_cents INTEGER CHECK(min_amount_cents >= 0),
+    max_amount_cents INTEGER CHECK(max_amount_cents >= 0),
+    amount_sign TEXT CHECK(amount_sign IN ('DEBIT', 'CREDIT')),
+    category_id INTEGER NOT NULL,
+    FOREIGN KEY (account_id) REFERENCES account(id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES category(id) ON DELETE CASCADE
+);
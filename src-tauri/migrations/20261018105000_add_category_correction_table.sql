@@ -0,0 +1,17 @@
+-- Categories the user picked by hand, kept as training examples for category suggestions.
+-- The description and merchant are copied so examples outlive the transaction they came from.
+CREATE TABLE IF NOT EXISTS category_correction (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transaction_id INTEGER,
+    description TEXT NOT NULL,
+    merchant_entity_id TEXT,
+    from_category_id INTEGER,
+    category_id INTEGER NOT NULL,
+    corrected_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (transaction_id) REFERENCES "transaction"(id) ON DELETE SET NULL,
+    FOREIGN KEY (from_category_id) REFERENCES category(id) ON DELETE SET NULL,
+    FOREIGN KEY (category_id) REFERENCES category(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_category_correction_transaction_id
+    ON category_correction(transaction_id);
@@ -0,0 +1,28 @@
+-- Plaid's personal finance category for synced transactions
+ALTER TABLE "transaction" ADD COLUMN plaid_category_primary TEXT;
+ALTER TABLE "transaction" ADD COLUMN plaid_category_detailed TEXT;
+
+-- Which category new Plaid transactions are filed under for a Plaid category code. Codes can
+-- be detailed (FOOD_AND_DRINK_GROCERIES) or primary (FOOD_AND_DRINK), detailed codes win
+CREATE TABLE IF NOT EXISTS plaid_category_mapping (
+    pfc_code TEXT PRIMARY KEY,
+    category_id INTEGER NOT NULL,
+    FOREIGN KEY (category_id) REFERENCES category(id) ON DELETE CASCADE
+);
+
+INSERT INTO plaid_category_mapping (pfc_code, category_id)
+SELECT m.pfc_code, c.id
+FROM (
+    SELECT 'INCOME' AS pfc_code, 'Income' AS category_name
+    UNION ALL SELECT 'RENT_AND_UTILITIES', 'Housing'
+    UNION ALL SELECT 'LOAN_PAYMENTS_MORTGAGE_PAYMENT', 'Housing'
+    UNION ALL SELECT 'FOOD_AND_DRINK', 'Restaurants'
+    UNION ALL SELECT 'FOOD_AND_DRINK_GROCERIES', 'Groceries'
+    UNION ALL SELECT 'TRANSPORTATION', 'Transportation'
+    UNION ALL SELECT 'MEDICAL', 'Healthcare'
+    UNION ALL SELECT 'ENTERTAINMENT', 'Entertainment'
+    UNION ALL SELECT 'GENERAL_MERCHANDISE', 'Shopping'
+    UNION ALL SELECT 'PERSONAL_CARE', 'Miscellaneous'
+    UNION ALL SELECT 'GENERAL_SERVICES_EDUCATION', 'Education'
+) m
+JOIN category c ON c.name = m.category_name;
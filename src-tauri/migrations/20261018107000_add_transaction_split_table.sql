@@ -0,0 +1,14 @@
+-- Parts of a transaction divided across categories. Split amounts add up to the parent's
+-- amount and replace its category when totalling spending
+CREATE TABLE IF NOT EXISTS transaction_split (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transaction_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    amount_cents INTEGER NOT NULL,
+    memo TEXT,
+    FOREIGN KEY (transaction_id) REFERENCES "transaction"(id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES category(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_split_transaction_id ON transaction_split(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_transaction_split_category_id ON transaction_split(category_id);
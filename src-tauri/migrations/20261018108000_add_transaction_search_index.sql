@@ -0,0 +1,23 @@
+-- Full-text index over transaction names for searching long histories. It reads names from
+-- the transaction table and is kept in sync by the triggers below
+CREATE VIRTUAL TABLE IF NOT EXISTS transaction_fts USING fts5(
+    name,
+    content='transaction',
+    content_rowid='id',
+    tokenize='unicode61 remove_diacritics 2'
+);
+
+INSERT INTO transaction_fts(transaction_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS transaction_fts_after_insert AFTER INSERT ON "transaction" BEGIN
+    INSERT INTO transaction_fts(rowid, name) VALUES (new.id, new.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS transaction_fts_after_delete AFTER DELETE ON "transaction" BEGIN
+    INSERT INTO transaction_fts(transaction_fts, rowid, name) VALUES ('delete', old.id, old.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS transaction_fts_after_update AFTER UPDATE OF name ON "transaction" BEGIN
+    INSERT INTO transaction_fts(transaction_fts, rowid, name) VALUES ('delete', old.id, old.name);
+    INSERT INTO transaction_fts(rowid, name) VALUES (new.id, new.name);
+END;
@@ -0,0 +1,5 @@
+-- User edits to synced transactions. Plaid keeps sending its own name, amount and date for a
+-- row, so edited fields are remembered here and win over what later syncs send
+ALTER TABLE "transaction" ADD COLUMN name_override TEXT;
+ALTER TABLE "transaction" ADD COLUMN amount_override_cents INTEGER;
+ALTER TABLE "transaction" ADD COLUMN date_override TEXT;
@@ -0,0 +1,21 @@
+-- Changes made to many transactions at once, kept so each one can be undone
+CREATE TABLE IF NOT EXISTS bulk_operation (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    action TEXT NOT NULL,
+    category_id INTEGER,
+    transaction_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    undone_at TEXT,
+    FOREIGN KEY (category_id) REFERENCES category(id) ON DELETE SET NULL
+);
+
+-- The transactions an operation changed and what they held before it
+CREATE TABLE IF NOT EXISTS bulk_operation_change (
+    operation_id INTEGER NOT NULL,
+    transaction_id INTEGER NOT NULL,
+    previous_category_id INTEGER NOT NULL,
+    previous_deleted_at TEXT,
+    PRIMARY KEY (operation_id, transaction_id),
+    FOREIGN KEY (operation_id) REFERENCES bulk_operation(id) ON DELETE CASCADE,
+    FOREIGN KEY (transaction_id) REFERENCES "transaction"(id) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+-- Free-form labels such as "reimbursable" that cut across categories. A transaction can have
+-- any number of them
+CREATE TABLE IF NOT EXISTS tag (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+CREATE TABLE IF NOT EXISTS transaction_tag (
+    transaction_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (transaction_id, tag_id),
+    FOREIGN KEY (transaction_id) REFERENCES "transaction"(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tag(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_tag_tag_id ON transaction_tag(tag_id);
+
+-- The tag a bulk operation added or removed
+ALTER TABLE bulk_operation ADD COLUMN tag_id INTEGER REFERENCES tag(id) ON DELETE SET NULL;
@@ -0,0 +1,30 @@
+-- Money moved between two of the user's own accounts, such as a card payment from checking.
+-- Both sides are left out of spending and income unless the pair was rejected
+CREATE TABLE IF NOT EXISTS transfer (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    outflow_transaction_id INTEGER NOT NULL,
+    inflow_transaction_id INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    -- Rejected pairs are kept so detection doesn't suggest them again
+    UNIQUE (outflow_transaction_id, inflow_transaction_id),
+    FOREIGN KEY (outflow_transaction_id) REFERENCES "transaction"(id) ON DELETE CASCADE,
+    FOREIGN KEY (inflow_transaction_id) REFERENCES "transaction"(id) ON DELETE CASCADE
+);
+
+-- A transaction belongs to at most one live transfer
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transfer_outflow ON transfer(outflow_transaction_id) WHERE status != 'REJECTED';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transfer_inflow ON transfer(inflow_transaction_id) WHERE status != 'REJECTED';
+
+CREATE VIEW IF NOT EXISTS transfer_transaction AS
+    SELECT outflow_transaction_id AS transaction_id FROM transfer WHERE status != 'REJECTED'
+    UNION ALL
+    SELECT inflow_transaction_id AS transaction_id FROM transfer WHERE status != 'REJECTED';
+
+-- How many days apart the two sides of a detected transfer may post
+CREATE TABLE IF NOT EXISTS transfer_setting (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    window_days INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO transfer_setting (id, window_days) VALUES (1, 3);
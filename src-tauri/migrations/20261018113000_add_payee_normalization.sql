@@ -0,0 +1,12 @@
+-- A readable merchant name cleaned up from the raw bank description. The app fills it in for
+-- rows where it is NULL, so existing transactions are normalized on the next write
+ALTER TABLE "transaction" ADD COLUMN payee TEXT;
+
+-- User rewrites tried before the built-in cleanup, such as "AMZN MKTP" to "Amazon"
+CREATE TABLE IF NOT EXISTS payee_rule (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    -- CONTAINS or REGEX, like categorization rules
+    description_match TEXT NOT NULL DEFAULT 'CONTAINS',
+    payee TEXT NOT NULL
+);
@@ -0,0 +1,25 @@
+-- Merchants that transactions are grouped under, with details the user can edit
+CREATE TABLE IF NOT EXISTS merchant (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    -- Files newly linked transactions that nothing else categorized
+    default_category_id INTEGER,
+    logo_url TEXT,
+    notes TEXT,
+    FOREIGN KEY (default_category_id) REFERENCES category(id) ON DELETE SET NULL
+);
+
+-- What identifies a merchant's transactions: Plaid's merchant_entity_id, or for rows without
+-- one the lowercased payee. Merged merchants keep the keys of both
+CREATE TABLE IF NOT EXISTS merchant_key (
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    merchant_id INTEGER NOT NULL,
+    PRIMARY KEY (kind, value),
+    FOREIGN KEY (merchant_id) REFERENCES merchant(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_merchant_key_merchant_id ON merchant_key(merchant_id);
+
+ALTER TABLE "transaction" ADD COLUMN merchant_id INTEGER REFERENCES merchant(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_transaction_merchant_id ON "transaction"(merchant_id);
@@ -0,0 +1,16 @@
+-- Charges that repeat at a regular cadence, such as subscriptions and rent, one series per payee
+CREATE TABLE IF NOT EXISTS recurring_series (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    payee TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    name TEXT NOT NULL,
+    -- WEEKLY, BIWEEKLY, MONTHLY or ANNUAL
+    cadence TEXT NOT NULL,
+    -- Negative like the charges themselves
+    expected_amount_cents INTEGER NOT NULL,
+    last_date TEXT NOT NULL,
+    next_expected_date TEXT NOT NULL,
+    -- ACTIVE, or LAPSED once a charge is overdue past the cadence's grace period
+    status TEXT NOT NULL DEFAULT 'ACTIVE',
+    -- SUGGESTED until the user confirms, edits or dismisses it
+    review TEXT NOT NULL DEFAULT 'SUGGESTED'
+);
@@ -0,0 +1,18 @@
+-- Price changes and missed payments noticed on monthly recurring series
+CREATE TABLE IF NOT EXISTS recurring_alert (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    series_id INTEGER NOT NULL,
+    -- PRICE_CHANGE or MISSED_PAYMENT
+    kind TEXT NOT NULL,
+    -- The charge that came in at a new amount, none for missed payments
+    transaction_id INTEGER,
+    expected_amount_cents INTEGER NOT NULL,
+    amount_cents INTEGER,
+    -- The due date of the charge the alert is about, so each one is raised once
+    expected_date TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    acknowledged_at TEXT,
+    UNIQUE (series_id, kind, expected_date),
+    FOREIGN KEY (series_id) REFERENCES recurring_series(id) ON DELETE CASCADE,
+    FOREIGN KEY (transaction_id) REFERENCES "transaction"(id) ON DELETE SET NULL
+);
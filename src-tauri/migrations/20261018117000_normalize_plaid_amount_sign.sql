@@ -0,0 +1,13 @@
+-- Plaid reports outflows as positive amounts while the ledger stores spending as negative.
+-- Synced rows were stored with Plaid's sign, so flip them and their splits. Amounts the user
+-- overrode were entered in the ledger's sign already and are left alone
+UPDATE transaction_split
+SET amount_cents = -amount_cents
+WHERE transaction_id IN (
+    SELECT id FROM "transaction"
+    WHERE plaid_transaction_id IS NOT NULL AND amount_override_cents IS NULL
+);
+
+UPDATE "transaction"
+SET amount_cents = -amount_cents
+WHERE plaid_transaction_id IS NOT NULL AND amount_override_cents IS NULL;
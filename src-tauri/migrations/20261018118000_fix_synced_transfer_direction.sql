@@ -0,0 +1,13 @@
+-- Transfers found while synced rows still carried Plaid's sign. Suggestions whose two sides
+-- now move money the same way weren't transfers at all, and pairs recorded the wrong way round
+-- get their outflow and inflow swapped
+DELETE FROM transfer
+WHERE status = 'SUGGESTED'
+    AND ((SELECT amount_cents FROM "transaction" WHERE id = outflow_transaction_id) > 0)
+        = ((SELECT amount_cents FROM "transaction" WHERE id = inflow_transaction_id) > 0);
+
+UPDATE transfer
+SET outflow_transaction_id = inflow_transaction_id,
+    inflow_transaction_id = outflow_transaction_id
+WHERE (SELECT amount_cents FROM "transaction" WHERE id = outflow_transaction_id) > 0
+    AND (SELECT amount_cents FROM "transaction" WHERE id = inflow_transaction_id) < 0;
@@ -0,0 +1,5 @@
+-- Lets transfer detection look up rows of the opposite amount near a date
+CREATE INDEX IF NOT EXISTS idx_transaction_amount_date ON "transaction"(amount_cents, date);
+
+-- Highest transaction id already checked for transfers, so saves only look at newer rows
+ALTER TABLE transfer_setting ADD COLUMN scanned_through_id INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Selected split transactions a recategorize left alone, as their lines keep their own categories
+ALTER TABLE bulk_operation ADD COLUMN skipped_split_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Lets startup find rows written before payees existed without scanning every transaction
+CREATE INDEX IF NOT EXISTS idx_transaction_payee_missing ON "transaction"(id) WHERE payee IS NULL;
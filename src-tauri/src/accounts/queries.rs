@@ -115,6 +115,25 @@ pub async fn get_accounts_of_item(
     Ok(accounts)
 }
 
+pub async fn get_account_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Account, sqlx::Error> {
+    let query = r#"
+        SELECT
+            a.id,
+            a.plaid_account_id,
+            a.name,
+            a.official_name,
+            a.bank_id,
+            a.account_type,
+            a.initial_balance_cents,
+            a.available_balance_cents,
+            a.current_balance_cents
+        FROM account a
+        WHERE a.id=?
+    "#;
+
+    sqlx::query_as(query).bind(id).fetch_one(pool).await
+}
+
 pub async fn get_full_accounts(pool: &Pool<Sqlite>) -> Result<Vec<FullAccount>, sqlx::Error> {
     let query = r#"
         SELECT
@@ -230,6 +249,21 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("accounts")))]
+    async fn get_account_by_id_returns_balances(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let account = get_account_by_id(&pool, 3).await?;
+
+        assert_eq!(account.name, "Joint Checking");
+        assert_eq!(account.current_balance, Cents(dec!(2480.75)));
+        assert!(matches!(
+            get_account_by_id(&pool, 99).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+
     async fn seed_bank(pool: &Pool<Sqlite>) -> Bank {
         sqlx::query(
             "INSERT INTO plaid_item (item_id, access_token) VALUES ('item-1', 'access-token-1')",
@@ -0,0 +1,72 @@
+use crate::bulk::queries;
+use crate::bulk::types::{BulkChange, BulkOperation, BulkSelection};
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_bulk_operations(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<BulkOperation>, String> {
+    queries::get_bulk_operations(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Recategorizes, tags, untags, deletes or restores many transactions at once
+
+Every row changes in one transaction. The returned operation holds how many transactions
+changed, how many split ones a recategorize skipped and its id for `undo_bulk_operation`
+*/
+#[tauri::command]
+pub async fn bulk_update_transactions(
+    state: tauri::State<'_, AppState>,
+    selection: BulkSelection,
+    change: BulkChange,
+) -> Result<BulkOperation, String> {
+    change.validate()?;
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let operation = queries::apply_bulk_operation(&mut tx, &selection, &change)
+        .await
+        .map_err(|e| format!("Failed to update transactions: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(operation)
+}
+
+/// Reverses a bulk operation and returns how many transactions were put back
+#[tauri::command]
+pub async fn undo_bulk_operation(
+    state: tauri::State<'_, AppState>,
+    operation_id: i64,
+) -> Result<u64, String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let restored = queries::undo_bulk_operation(&mut tx, operation_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                format!("Bulk operation {operation_id} does not exist or was already undone")
+            }
+            e => format!("Failed to undo bulk operation {operation_id}: {e}"),
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(restored)
+}
@@ -0,0 +1,396 @@
+use crate::bulk::types::{BulkAction, BulkChange, BulkOperation, BulkSelection};
+use crate::transactions;
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
+
+const BULK_OPERATION_QUERY: &str = r#"
+    SELECT id, action, category_id, tag_id, transaction_count, skipped_split_count, created_at,
+        undone_at
+    FROM bulk_operation
+"#;
+
+pub async fn get_bulk_operations(pool: &Pool<Sqlite>) -> Result<Vec<BulkOperation>, sqlx::Error> {
+    let query = format!("{BULK_OPERATION_QUERY} ORDER BY id DESC");
+
+    let operations: Vec<BulkOperation> = sqlx::query_as(&query).fetch_all(pool).await?;
+
+    Ok(operations)
+}
+
+/*
+Applies a change to every selected transaction and records it so it can be undone
+
+Rows the change wouldn't alter are left out, so deleting skips rows that are already deleted
+and tagging skips rows that already have the tag. Split transactions keep their lines and are
+skipped by recategorizing, the operation counts them so the user can be told
+*/
+pub async fn apply_bulk_operation(
+    conn: &mut SqliteConnection,
+    selection: &BulkSelection,
+    change: &BulkChange,
+) -> Result<BulkOperation, sqlx::Error> {
+    let operation_id: i64 = sqlx::query_scalar(
+        "INSERT INTO bulk_operation (action, category_id, tag_id) VALUES (?, ?, ?) RETURNING id",
+    )
+    .bind(change.action)
+    .bind(change.category_id)
+    .bind(change.tag_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "INSERT INTO bulk_operation_change (operation_id, transaction_id, previous_category_id, previous_deleted_at) SELECT ",
+    );
+    query_builder
+        .push_bind(operation_id)
+        .push(", t.id, t.category_id, t.deleted_at FROM 'transaction' t WHERE ");
+    match change.action {
+        BulkAction::SetCategory => {
+            query_builder
+                .push("t.deleted_at IS NULL AND t.category_id != ")
+                .push_bind(change.category_id)
+                .push(" AND NOT EXISTS (SELECT 1 FROM transaction_split s WHERE s.transaction_id = t.id)");
+        }
+        BulkAction::Delete => {
+            query_builder.push("t.deleted_at IS NULL");
+        }
+        BulkAction::Restore => {
+            query_builder.push("t.deleted_at IS NOT NULL");
+        }
+        BulkAction::AddTag | BulkAction::RemoveTag => {
+            let tagged = if change.action == BulkAction::AddTag { " NOT EXISTS" } else { " EXISTS" };
+            query_builder
+                .push("t.deleted_at IS NULL AND")
+                .push(tagged)
+                .push(" (SELECT 1 FROM transaction_tag tt WHERE tt.transaction_id = t.id AND tt.tag_id = ")
+                .push_bind(change.tag_id)
+                .push(")");
+        }
+    }
+    push_selection(&mut query_builder, selection);
+    let transaction_count = query_builder
+        .build()
+        .execute(&mut *conn)
+        .await?
+        .rows_affected() as i64;
+
+    let skipped_split_count: i64 = if change.action == BulkAction::SetCategory {
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT COUNT(*) FROM 'transaction' t WHERE t.deleted_at IS NULL AND EXISTS (SELECT 1 FROM transaction_split s WHERE s.transaction_id = t.id)",
+        );
+        push_selection(&mut query_builder, selection);
+        query_builder
+            .build_query_scalar()
+            .fetch_one(&mut *conn)
+            .await?
+    } else {
+        0
+    };
+
+    // $1 is the operation, whose changes list the rows to update, and $2 the category or tag
+    let (query, argument) = match change.action {
+        BulkAction::SetCategory => (
+            "UPDATE 'transaction' SET category_id=$2 WHERE id IN (SELECT transaction_id FROM bulk_operation_change WHERE operation_id=$1)",
+            change.category_id,
+        ),
+        BulkAction::Delete => (
+            "UPDATE 'transaction' SET deleted_at=date('now') WHERE id IN (SELECT transaction_id FROM bulk_operation_change WHERE operation_id=$1)",
+            None,
+        ),
+        BulkAction::Restore => (
+            "UPDATE 'transaction' SET deleted_at=NULL WHERE id IN (SELECT transaction_id FROM bulk_operation_change WHERE operation_id=$1)",
+            None,
+        ),
+        BulkAction::AddTag => (
+            "INSERT INTO transaction_tag (transaction_id, tag_id) SELECT transaction_id, $2 FROM bulk_operation_change WHERE operation_id=$1",
+            change.tag_id,
+        ),
+        BulkAction::RemoveTag => (
+            "DELETE FROM transaction_tag WHERE tag_id=$2 AND transaction_id IN (SELECT transaction_id FROM bulk_operation_change WHERE operation_id=$1)",
+            change.tag_id,
+        ),
+    };
+    let mut update = sqlx::query(query).bind(operation_id);
+    if argument.is_some() {
+        update = update.bind(argument);
+    }
+    update.execute(&mut *conn).await?;
+
+    sqlx::query("UPDATE bulk_operation SET transaction_count=?, skipped_split_count=? WHERE id=?")
+        .bind(transaction_count)
+        .bind(skipped_split_count)
+        .bind(operation_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let query = format!("{BULK_OPERATION_QUERY} WHERE id=?");
+    sqlx::query_as(&query)
+        .bind(operation_id)
+        .fetch_one(conn)
+        .await
+}
+
+// Narrows a query over `'transaction' t` to the selected rows
+fn push_selection(query_builder: &mut QueryBuilder<'_, Sqlite>, selection: &BulkSelection) {
+    match selection {
+        BulkSelection::Ids(ids) if ids.is_empty() => {
+            query_builder.push(" AND FALSE");
+        }
+        BulkSelection::Ids(ids) => {
+            query_builder.push(" AND t.id IN (");
+            let mut separated = query_builder.separated(", ");
+            for id in ids {
+                separated.push_bind(*id);
+            }
+            query_builder.push(")");
+        }
+        BulkSelection::Filter(filter) => {
+            transactions::queries::push_transaction_filter(query_builder, filter);
+        }
+    }
+}
+
+/*
+Puts the transactions a bulk operation changed back the way they were
+
+Rows changed again since, such as moved to yet another category or restored by hand, are
+left alone, as is everything when the operation's category or tag has been deleted. Returns
+how many transactions were put back
+*/
+pub async fn undo_bulk_operation(
+    conn: &mut SqliteConnection,
+    operation_id: i64,
+) -> Result<u64, sqlx::Error> {
+    let change: BulkChange = sqlx::query_as(
+        r#"
+        UPDATE bulk_operation SET undone_at = datetime('now')
+        WHERE id=? AND undone_at IS NULL
+        RETURNING action, category_id, tag_id
+        "#,
+    )
+    .bind(operation_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    let (query, argument) = match change.action {
+        BulkAction::SetCategory => (
+            r#"
+            UPDATE 'transaction'
+            SET category_id = c.previous_category_id
+            FROM bulk_operation_change c
+            WHERE c.operation_id=$1
+                AND c.transaction_id = "transaction".id
+                AND "transaction".category_id = $2
+                AND "transaction".deleted_at IS NULL
+                AND c.previous_category_id IN (SELECT id FROM category)
+            "#,
+            change.category_id,
+        ),
+        BulkAction::Delete => (
+            r#"
+            UPDATE 'transaction'
+            SET deleted_at = NULL
+            FROM bulk_operation_change c
+            WHERE c.operation_id=$1
+                AND c.transaction_id = "transaction".id
+                AND "transaction".deleted_at IS NOT NULL
+            "#,
+            None,
+        ),
+        BulkAction::Restore => (
+            r#"
+            UPDATE 'transaction'
+            SET deleted_at = c.previous_deleted_at
+            FROM bulk_operation_change c
+            WHERE c.operation_id=$1
+                AND c.transaction_id = "transaction".id
+                AND "transaction".deleted_at IS NULL
+            "#,
+            None,
+        ),
+        BulkAction::AddTag => (
+            r#"
+            DELETE FROM transaction_tag
+            WHERE tag_id=$2
+                AND transaction_id IN (SELECT transaction_id FROM bulk_operation_change WHERE operation_id=$1)
+            "#,
+            change.tag_id,
+        ),
+        BulkAction::RemoveTag => (
+            r#"
+            INSERT OR IGNORE INTO transaction_tag (transaction_id, tag_id)
+            SELECT transaction_id, $2 FROM bulk_operation_change WHERE operation_id=$1
+            "#,
+            change.tag_id,
+        ),
+    };
+
+    let needs_argument = matches!(
+        change.action,
+        BulkAction::SetCategory | BulkAction::AddTag | BulkAction::RemoveTag
+    );
+    if needs_argument && argument.is_none() {
+        return Ok(0);
+    }
+    let mut undo = sqlx::query(query).bind(operation_id);
+    if needs_argument {
+        undo = undo.bind(argument);
+    }
+    let res = undo.execute(&mut *conn).await?;
+
+    Ok(res.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags;
+    use crate::transactions::types::{SplitLine, TransactionFilter};
+
+    async fn categories_by_id(pool: &Pool<Sqlite>) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        sqlx::query_as("SELECT id, category_id FROM 'transaction' ORDER BY id")
+            .fetch_all(pool)
+            .await
+    }
+
+    async fn deleted_ids(pool: &Pool<Sqlite>) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT id FROM 'transaction' WHERE deleted_at IS NOT NULL ORDER BY id")
+            .fetch_all(pool)
+            .await
+    }
+
+    fn set_category(category_id: i64) -> BulkChange {
+        BulkChange {
+            action: BulkAction::SetCategory,
+            category_id: Some(category_id),
+            tag_id: None,
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn recategorizes_selected_rows_and_undoes(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        transactions::queries::set_transaction_splits(
+            &mut conn,
+            4,
+            &[
+                SplitLine { category_id: 4, amount_cents: -50, memo: None },
+                SplitLine { category_id: 5, amount_cents: -20, memo: None },
+            ],
+        )
+        .await?;
+
+        let operation =
+            apply_bulk_operation(&mut conn, &BulkSelection::Ids(vec![1, 2, 4]), &set_category(5))
+                .await?;
+        assert_eq!(operation.transaction_count, 2, "the split row is skipped");
+        assert_eq!(operation.skipped_split_count, 1);
+        assert_eq!(categories_by_id(&pool).await?, vec![(1, 5), (2, 5), (3, 1), (4, 4)]);
+
+        // Moved again by hand, so undo leaves it where the user put it
+        transactions::queries::update_transaction_category(&pool, 2, 11).await?;
+
+        assert_eq!(undo_bulk_operation(&mut conn, operation.id).await?, 1);
+        assert_eq!(categories_by_id(&pool).await?, vec![(1, 1), (2, 11), (3, 1), (4, 4)]);
+        assert!(matches!(
+            undo_bulk_operation(&mut conn, operation.id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+
+        let operations = get_bulk_operations(&pool).await?;
+        assert_eq!(operations.len(), 1);
+        assert!(operations[0].undone_at.is_some());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn deletes_and_restores_by_filter(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let december_16 = TransactionFilter {
+            date_from: chrono::NaiveDate::from_ymd_opt(2025, 12, 16),
+            date_to: chrono::NaiveDate::from_ymd_opt(2025, 12, 16),
+            ..Default::default()
+        };
+        let delete = BulkChange {
+            action: BulkAction::Delete,
+            category_id: None,
+            tag_id: None,
+        };
+        let restore = BulkChange {
+            action: BulkAction::Restore,
+            category_id: None,
+            tag_id: None,
+        };
+
+        transactions::queries::set_transaction_deleted(&pool, 3, true).await?;
+        let deleted =
+            apply_bulk_operation(&mut conn, &BulkSelection::Filter(december_16), &delete).await?;
+        assert_eq!(deleted.transaction_count, 2);
+        assert_eq!(deleted_ids(&pool).await?, vec![2, 3, 4]);
+
+        let restored = apply_bulk_operation(
+            &mut conn,
+            &BulkSelection::Filter(TransactionFilter::default()),
+            &restore,
+        )
+        .await?;
+        assert_eq!(restored.transaction_count, 3);
+        assert!(deleted_ids(&pool).await?.is_empty());
+
+        // Undoing the restore deletes all three again, undoing the delete brings back its two
+        assert_eq!(undo_bulk_operation(&mut conn, restored.id).await?, 3);
+        assert_eq!(deleted_ids(&pool).await?, vec![2, 3, 4]);
+        assert_eq!(undo_bulk_operation(&mut conn, deleted.id).await?, 2);
+        assert_eq!(deleted_ids(&pool).await?, vec![3]);
+
+        let nothing =
+            apply_bulk_operation(&mut conn, &BulkSelection::Ids(Vec::new()), &delete).await?;
+        assert_eq!(nothing.transaction_count, 0);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn tags_and_untags_selected_rows(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let tag_id = tags::queries::create_tag(&pool, "reimbursable").await?;
+        tags::queries::set_transaction_tags(&mut conn, 1, &[tag_id]).await?;
+        let tagged = TransactionFilter {
+            tag_ids: vec![tag_id],
+            ..Default::default()
+        };
+        let change = |action| BulkChange {
+            action,
+            category_id: None,
+            tag_id: Some(tag_id),
+        };
+
+        let added = apply_bulk_operation(
+            &mut conn,
+            &BulkSelection::Ids(vec![1, 2, 3]),
+            &change(BulkAction::AddTag),
+        )
+        .await?;
+        assert_eq!(added.transaction_count, 2);
+        assert_eq!(transactions::queries::get_num_transactions(&pool, &tagged).await?, 3);
+
+        let removed = apply_bulk_operation(
+            &mut conn,
+            &BulkSelection::Filter(TransactionFilter::default()),
+            &change(BulkAction::RemoveTag),
+        )
+        .await?;
+        assert_eq!(removed.transaction_count, 3);
+        assert_eq!(transactions::queries::get_num_transactions(&pool, &tagged).await?, 0);
+
+        assert_eq!(undo_bulk_operation(&mut conn, removed.id).await?, 3);
+        assert_eq!(undo_bulk_operation(&mut conn, added.id).await?, 2);
+        assert_eq!(transactions::queries::get_num_transactions(&pool, &tagged).await?, 1);
+        Ok(())
+    }
+}
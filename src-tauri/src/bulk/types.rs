@@ -0,0 +1,58 @@
+use crate::transactions::types::TransactionFilter;
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BulkAction {
+    SetCategory,
+    /// Soft-deletes, so the rows can still be restored
+    Delete,
+    Restore,
+    AddTag,
+    RemoveTag,
+}
+
+/// What a bulk operation does to each transaction it selects
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct BulkChange {
+    pub action: BulkAction,
+    /// Required by `SetCategory`
+    pub category_id: Option<i64>,
+    /// Required by `AddTag` and `RemoveTag`
+    pub tag_id: Option<i64>,
+}
+
+impl BulkChange {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.action == BulkAction::SetCategory && self.category_id.is_none() {
+            return Err("A category is required to recategorize transactions".to_string());
+        }
+        if matches!(self.action, BulkAction::AddTag | BulkAction::RemoveTag) && self.tag_id.is_none()
+        {
+            return Err("A tag is required to tag or untag transactions".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The transactions a bulk operation applies to
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub enum BulkSelection {
+    Ids(Vec<i64>),
+    /// Everything the transactions list shows with this filter, or hides when restoring
+    Filter(TransactionFilter),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct BulkOperation {
+    pub id: i64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub change: BulkChange,
+    /// Transactions the operation changed, rows it would have left as they were are not counted
+    pub transaction_count: i64,
+    /// Selected split transactions a recategorize left alone
+    pub skipped_split_count: i64,
+    pub created_at: NaiveDateTime,
+    pub undone_at: Option<NaiveDateTime>,
+}
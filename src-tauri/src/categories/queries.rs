@@ -1,5 +1,6 @@
 use crate::types::{Category, CategoryOverview};
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Sqlite, SqliteConnection, SqliteExecutor};
+use std::collections::HashMap;
 
 pub async fn get_all_categories(pool: &Pool<Sqlite>) -> Result<Vec<Category>, sqlx::Error> {
     let query = "SELECT id, name, color, icon FROM category ORDER BY id";
@@ -25,6 +26,18 @@ pub async fn get_category_overviews(
                 WHERE t.category_id = c.id
                     AND t.deleted_at IS NULL
                     AND strftime('%Y-%m', t.date) = strftime('%Y-%m', 'now')
+                    AND t.id NOT IN (SELECT transaction_id FROM transfer_transaction)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM transaction_split s WHERE s.transaction_id = t.id
+                    )
+            ), 0) + COALESCE((
+                SELECT -SUM(s.amount_cents)
+                FROM transaction_split s
+                JOIN "transaction" t ON t.id = s.transaction_id
+                WHERE s.category_id = c.id
+                    AND t.deleted_at IS NULL
+                    AND strftime('%Y-%m', t.date) = strftime('%Y-%m', 'now')
+                    AND t.id NOT IN (SELECT transaction_id FROM transfer_transaction)
             ), 0) AS spent_cents
         FROM category c
         LEFT JOIN budget b ON b.category_id = c.id
@@ -103,13 +116,29 @@ pub async fn update_category(
     Ok(())
 }
 
-pub async fn get_uncategorized_category(pool: &Pool<Sqlite>) -> Result<Category, sqlx::Error> {
+pub async fn get_uncategorized_category<'e>(
+    executor: impl SqliteExecutor<'e>,
+) -> Result<Category, sqlx::Error> {
     let query = "SELECT id, name, color, icon FROM Category WHERE name='Uncategorized'";
-    let uncategorized: Category = sqlx::query_as(query).fetch_one(pool).await?;
+    let uncategorized: Category = sqlx::query_as(query).fetch_one(executor).await?;
 
     Ok(uncategorized)
 }
 
+/// Category ids keyed by lowercased name, for matching names that come from imported files
+pub async fn get_category_ids_by_name(
+    conn: &mut SqliteConnection,
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let categories: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM category")
+        .fetch_all(conn)
+        .await?;
+
+    Ok(categories
+        .into_iter()
+        .map(|(id, name)| (name.to_lowercase(), id))
+        .collect())
+}
+
 pub async fn delete_category(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
     let mut tx = pool.begin().await?;
 
@@ -123,6 +152,11 @@ pub async fn delete_category(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::E
         .bind(id)
         .execute(&mut *tx)
         .await?;
+    sqlx::query("UPDATE transaction_split SET category_id = ? WHERE category_id = ?")
+        .bind(uncategorized_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
 
     sqlx::query("DELETE FROM category WHERE id = ?")
         .bind(id)
@@ -242,6 +276,28 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn test_overviews_count_split_amounts_instead_of_parent(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        seed_account(&pool).await?;
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+        // A 100.00 charge in Groceries (4), split 60/40 with Shopping (11).
+        insert_txn(&pool, 4, -10000, &today).await?;
+        sqlx::query(
+            "INSERT INTO transaction_split (transaction_id, category_id, amount_cents) VALUES (1, 4, -6000), (1, 11, -4000)",
+        )
+        .execute(&pool)
+        .await?;
+        insert_txn(&pool, 4, -500, &today).await?;
+
+        let overviews = get_category_overviews(&pool).await?;
+        assert_eq!(overview_for(&overviews, 4).spent_cents, 6500);
+        assert_eq!(overview_for(&overviews, 11).spent_cents, 4000);
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn test_upsert_budget_inserts_then_updates(
         pool: Pool<Sqlite>,
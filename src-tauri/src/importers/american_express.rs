@@ -1,27 +1,78 @@
-use crate::importers::types::TransactionImport;
+use crate::importers::registry::Importer;
+use crate::importers::types::{leading_records, ParseReport, TransactionImport};
 use csv::ReaderBuilder;
+use rust_decimal::Decimal;
+use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
-pub fn parse_csv_statement<P: AsRef<Path>>(
-    filename: P,
-) -> Result<Vec<TransactionImport>, std::io::Error> {
-    let mut reader = ReaderBuilder::new().flexible(true).from_path(filename)?;
+const HEADER: [&str; 5] = ["Date", "Description", "Card Member", "Account #", "Amount"];
+const AMOUNT_COLUMN: usize = 4;
+const SNIFF_ROWS: usize = 10;
+
+pub struct AmericanExpressImporter;
+
+impl Importer for AmericanExpressImporter {
+    fn id(&self) -> &str {
+        "american_express"
+    }
+
+    fn display_name(&self) -> &str {
+        "American Express"
+    }
+
+    fn sniff(&self, contents: &[u8]) -> f32 {
+        let records = leading_records(contents, SNIFF_ROWS);
+        let Some((header, rows)) = records.split_first() else {
+            return 0.0;
+        };
+        if !header.iter().eq(HEADER) {
+            return 0.0;
+        }
+
+        // Charges are exported as positive amounts, the opposite of the other banks
+        let amounts: Vec<Decimal> = rows
+            .iter()
+            .filter_map(|row| row.get(AMOUNT_COLUMN))
+            .filter_map(|amount| Decimal::from_str(amount).ok())
+            .collect();
+        let charges = amounts.iter().filter(|a| a.is_sign_positive()).count();
+        if charges * 2 > amounts.len() {
+            0.95
+        } else {
+            0.7
+        }
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String> {
+        Ok(parse_csv_bytes(contents))
+    }
+}
+
+pub fn parse_csv_statement<P: AsRef<Path>>(filename: P) -> Result<ParseReport, std::io::Error> {
+    let contents = fs::read(filename)?;
+    Ok(parse_csv_bytes(&contents))
+}
+
+pub fn parse_csv_bytes(contents: &[u8]) -> ParseReport {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(contents);
 
     let headers =
         csv::StringRecord::from(vec!["date", "name", "not_used_1", "not_used_2", "amount"]);
-    // TODO: We filter out lines that may have errors, we should handle them explicitly, line 16, 22
-    let transactions: Vec<TransactionImport> = reader
-        .records()
-        .filter_map(|item| item.ok())
-        .filter_map(|transaction_record| transaction_record.deserialize(Some(&headers)).ok())
-        // AmEx tracks increases/decreases to credit card balance so we invert the amount
-        .map(|transaction: TransactionImport| TransactionImport {
-            amount: -transaction.amount,
-            ..transaction
-        })
-        .collect();
+    let report = ParseReport::from_records(contents, reader.records(), Some(&headers));
 
-    Ok(transactions)
+    ParseReport {
+        // AmEx tracks increases/decreases to credit card balance so we invert the amount
+        transactions: report
+            .transactions
+            .into_iter()
+            .map(|transaction: TransactionImport| TransactionImport {
+                amount: -transaction.amount,
+                ..transaction
+            })
+            .collect(),
+        ..report
+    }
 }
 
 #[cfg(test)]
@@ -41,57 +92,87 @@ mod tests {
                 date: NaiveDate::from_ymd_opt(2025, 12, 23).unwrap(),
                 name: "UBERBV              CITY           ST".to_string(),
                 amount: dec!(-6.71),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 22).unwrap(),
                 name: "XXXX#FLIGHT.AIRLINE               FA".to_string(),
                 amount: dec!(-2404.89),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 22).unwrap(),
                 name: "AMEX Airline Fee Reimbursement".to_string(),
                 amount: dec!(27.60),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 22).unwrap(),
                 name: "AplPay UMM PLAZA GIFCITY             ST".to_string(),
                 amount: dec!(-6.62),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 21).unwrap(),
                 name: "SOME AIRLINES   XXX-XXX-XXXX        ST".to_string(),
                 amount: dec!(-198.48),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 21).unwrap(),
                 name: "AMEX CLEAR PLUS CREDIT".to_string(),
                 amount: dec!(209.00),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 21).unwrap(),
                 name: "CLEAR *CLEARME.COM  CITY            ST".to_string(),
                 amount: dec!(-209.00),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 21).unwrap(),
                 name: "HALF PRICE THINGS    CITY            ST".to_string(),
                 amount: dec!(-4.41),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 21).unwrap(),
                 name: "REGISTRATION FEE       CITY".to_string(),
                 amount: dec!(-355.00),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 20).unwrap(),
                 name: "AplPay AIRLINE AIRLINCITY            ST".to_string(),
                 amount: dec!(-35.00),
+                ..Default::default()
             },
         ];
 
-        let transactions = parse_csv_statement(&transactions_path)?;
+        let report = parse_csv_statement(&transactions_path)?;
 
-        assert_eq!(transactions, transactions_expected);
+        assert_eq!(report.transactions, transactions_expected);
+        assert!(report.rejected.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_parse_reports_rejected_rows() {
+        let contents = b"Date,Description,Card Member,Account #,Amount\n\
+12/23/2025,UBERBV CITY ST,JOHN DOE,-00006,6.71\n\
+2025-12-22,AMEX Airline Fee Reimbursement,JOHN DOE,-00006,-27.60\n";
+
+        let report = parse_csv_bytes(contents);
+
+        assert_eq!(report.transactions.len(), 1);
+        assert_eq!(report.transactions[0].amount, dec!(-6.71));
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 3);
+        assert_eq!(
+            report.rejected[0].raw,
+            "2025-12-22,AMEX Airline Fee Reimbursement,JOHN DOE,-00006,-27.60"
+        );
+        assert!(report.rejected[0].reason.contains("invalid date '2025-12-22'"));
+    }
 }
@@ -1,29 +1,70 @@
-use crate::importers::types::TransactionImport;
+use crate::importers::registry::Importer;
+use crate::importers::types::{leading_records, ParseReport};
 use csv::ReaderBuilder;
+use std::fs;
 use std::path::Path;
 
 const HEADER_START: &str = "Date";
+const HEADER: [&str; 4] = ["Date", "Description", "Amount", "Running Bal."];
+const SUMMARY_HEADER: [&str; 3] = ["Description", "", "Summary Amt."];
+// The header sits below a short summary block
+const MAX_HEADER_LINE: usize = 10;
 
-pub fn parse_csv_statement<P: AsRef<Path>>(
-    filename: P,
-) -> Result<Vec<TransactionImport>, std::io::Error> {
+pub struct BankOfAmericaImporter;
+
+impl Importer for BankOfAmericaImporter {
+    fn id(&self) -> &str {
+        "bank_of_america"
+    }
+
+    fn display_name(&self) -> &str {
+        "Bank of America"
+    }
+
+    fn sniff(&self, contents: &[u8]) -> f32 {
+        let records = leading_records(contents, MAX_HEADER_LINE);
+        let Some(header_line) = records.iter().position(|record| record.iter().eq(HEADER)) else {
+            return 0.0;
+        };
+
+        // Other exports share the column names, the summary block above them is what
+        // identifies Bank of America
+        let has_summary = records[..header_line]
+            .iter()
+            .any(|record| record.iter().eq(SUMMARY_HEADER));
+        if has_summary {
+            0.95
+        } else {
+            0.6
+        }
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String> {
+        Ok(parse_csv_bytes(contents))
+    }
+}
+
+pub fn parse_csv_statement<P: AsRef<Path>>(filename: P) -> Result<ParseReport, std::io::Error> {
+    let contents = fs::read(filename)?;
+    Ok(parse_csv_bytes(&contents))
+}
+
+pub fn parse_csv_bytes(contents: &[u8]) -> ParseReport {
     let mut reader = ReaderBuilder::new()
         .has_headers(false)
         .flexible(true)
-        .from_path(filename)?;
+        .from_reader(contents);
 
-    // TODO: We filter out lines that may have errors, we should handle them explicitly, line 16, 22
-    let transactions = reader
+    let records = reader
         .records()
-        .filter_map(|item| item.ok())
-        // Skip rows until you pass the header
-        .skip_while(|item| !item.as_slice().starts_with(HEADER_START))
+        // Skip the summary rows until you pass the header
+        .skip_while(|item| {
+            !matches!(item, Ok(record) if record.as_slice().starts_with(HEADER_START))
+        })
         // Skip header and "Beginning balance..." line
-        .skip(2)
-        .filter_map(|transaction_record| transaction_record.deserialize(None).ok())
-        .collect();
+        .skip(2);
 
-    Ok(transactions)
+    ParseReport::from_records(contents, records, None)
 }
 
 #[cfg(test)]
@@ -33,6 +74,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::importers::types::TransactionImport;
 
     #[test]
     fn test_parse_statement() -> Result<(), Box<dyn std::error::Error>> {
@@ -43,98 +85,139 @@ mod tests {
                 date: NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
                 name: "Zelle payment from JOHN DOE for food\"; Conf# 11bu1u1\"".to_string(),
                 amount: dec!(5),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
                 name: "VENMO DES:PAYMENT ID:XXXXX INDN:JOHN DOE CO ID:XXXXX WEB".to_string(),
                 amount: dec!(-10),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
                 name: "VENMO DES:PAYMENT ID:XXXXX INDN:JOHN DOE CO ID:XXXXX WEB".to_string(),
                 amount: dec!(-7.20),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
                 name: "VENMO DES:PAYMENT ID:XXXXX INDN:JOHN DOE CO ID:XXXXX WEB".to_string(),
                 amount: dec!(-0.60),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 12).unwrap(),
                 name: "COSTCO WHSE#1111 11/11 PURCHASE CITY ST".to_string(),
                 amount: dec!(-141.02),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 12).unwrap(),
                 name: "WELLS FARGO CARD DES:CRD ID:XXXXXXXXXX INDN:JOHN DOE CO ID:XXXXX WEB"
                     .to_string(),
                 amount: dec!(-481.86),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 17).unwrap(),
                 name: "VENMO DES:CASHOUT ID:XXXXX INDN:JOHN DOE CO ID:XXXXX PPD".to_string(),
                 amount: dec!(906.30),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 17).unwrap(),
                 name: "COSTCO WHSE#1111 11/14 PURCHASE CITY ST".to_string(),
                 amount: dec!(-4.44),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 21).unwrap(),
                 name: "SimpleBills Prod DES:WEB PMTS ID:AAAA INDN:JOHN DOE CO ID:XXXXX WEB"
                     .to_string(),
                 amount: dec!(-31.94),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 25).unwrap(),
                 name: "WELLS FARGO CARD DES:CRD ID:XXXXXXXXXX INDN:JOHN DOE CO ID:XXXXX WEB"
                     .to_string(),
                 amount: dec!(-474.74),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 11, 28).unwrap(),
                 name: "VENMO DES:PAYMENT ID:XXXXX INDN:JOHN DOE CO ID:XXXXX WEB".to_string(),
                 amount: dec!(-49.50),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
                 name: "Online Recurring transfer from CHK 1111 Confirmation# aaaaaaa; DOE, JANE"
                     .to_string(),
                 amount: dec!(1000),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
                 name: "PAYPAL *NEWBALANCEA 11/27 PURCHASE 111-111-11111 MO".to_string(),
                 amount: dec!(-93.51),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 4).unwrap(),
                 name: "BILTPYMTS DES:RENT PMT ID:XXXXXXXXXX INDN:JOHN DOE CO ID:XXXXX WEB"
                     .to_string(),
                 amount: dec!(-939.00),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 8).unwrap(),
                 name: "PAYPAL DES:INST XFER ID:AMAZON INDN:JOHN DOE CO ID:PAYPALS WEB".to_string(),
                 amount: dec!(-53.84),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 8).unwrap(),
                 name: "PAYPAL DES:INST XFER ID:EBAY INDN:JOHN DOE CO ID:PAYPALS WEB".to_string(),
                 amount: dec!(-46.13),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 9).unwrap(),
                 name: "WELLS FARGO CARD DES:CRD ID:XXXXXXXXXX INDN:JOHN DOE CO ID:XXXXX WEB"
                     .to_string(),
                 amount: dec!(-397.57),
+                ..Default::default()
             },
         ];
 
-        let transactions = parse_csv_statement(&transactions_path)?;
+        let report = parse_csv_statement(&transactions_path)?;
 
-        assert_eq!(transactions, transactions_expected);
+        assert_eq!(report.transactions, transactions_expected);
+        assert!(report.rejected.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_parse_reports_rejected_rows() {
+        let contents = b"Description,,Summary Amt.\r\n\
+Beginning balance as of 11/07/2025,,\"5,437.89\"\r\n\
+\r\n\
+Date,Description,Amount,Running Bal.\r\n\
+11/07/2025,Beginning balance as of 11/07/2025,,\"5,437.89\"\r\n\
+11/10/2025,\"VENMO DES:PAYMENT\",\"-10.00\",\"5,427.89\"\r\n\
+11/10/2025,\"VENMO DES:PAYMENT\",\"ten\",\"5,417.89\"\r\n";
+
+        let report = parse_csv_bytes(contents);
+
+        assert_eq!(report.transactions.len(), 1);
+        assert_eq!(report.rejected.len(), 1);
+        let rejected = &report.rejected[0];
+        assert_eq!(rejected.line, 7);
+        assert_eq!(
+            rejected.raw,
+            "11/10/2025,\"VENMO DES:PAYMENT\",\"ten\",\"5,417.89\""
+        );
+        assert!(rejected.reason.contains("invalid amount 'ten'"));
+    }
 }
@@ -1,26 +1,397 @@
-use crate::importers::american_express;
-use crate::importers::bank_of_america;
-use crate::importers::types::TransactionImport;
-use crate::importers::wells_fargo;
+use crate::accounts;
+use crate::categories;
+use crate::importers::generic::{ColumnMapping, ImportProfile, ProfileImporter};
+use crate::importers::queries;
+use crate::importers::registry::{Detection, DetectionError, ImporterInfo, ImporterRegistry};
+use crate::importers::types::{
+    AcceptedRow, ImportBatch, ImportPreview, ImportSummary, ParseReport, Reconciliation,
+    StatementBalance, StatementFile,
+};
+use crate::recurring;
+use crate::transactions;
+use crate::transfers;
+use crate::AppState;
+use chrono::Local;
+use sqlx::{Pool, Sqlite};
+use std::fs;
+
+// Built-in importers plus one for every saved column mapping profile
+async fn load_registry(pool: &Pool<Sqlite>) -> Result<ImporterRegistry, String> {
+    let profiles = queries::get_import_profiles(pool)
+        .await
+        .map_err(|e| format!("Error fetching import profiles: {e}"))?;
+
+    let mut registry = ImporterRegistry::with_builtin();
+    for profile in profiles {
+        registry.register(Box::new(ProfileImporter::new(profile)));
+    }
+    Ok(registry)
+}
+
+async fn parse_statement(
+    pool: &Pool<Sqlite>,
+    file: &StatementFile,
+    importer_id: &str,
+) -> Result<ParseReport, String> {
+    let registry = load_registry(pool).await?;
+    let importer = registry
+        .get(importer_id)
+        .ok_or(format!("Unsupported importer {importer_id}"))?;
+
+    importer.parse(&file.contents)
+}
+
+// Compares a statement's closing balance, when it has one, with the account's balance
+async fn reconcile(
+    pool: &Pool<Sqlite>,
+    account_id: i64,
+    statement_balance: Option<StatementBalance>,
+) -> Result<Option<Reconciliation>, String> {
+    let Some(statement_balance) = statement_balance else {
+        return Ok(None);
+    };
+
+    let account = accounts::queries::get_account_by_id(pool, account_id)
+        .await
+        .map_err(|e| format!("Error fetching account {account_id}: {e}"))?;
+    Ok(Some(Reconciliation::new(&statement_balance, &account)))
+}
+
+#[tauri::command]
+pub async fn get_importers(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ImporterInfo>, String> {
+    Ok(load_registry(&state.db.0).await?.list())
+}
+
+/*
+Works out which importer understands a statement from its header and first rows
+
+The error lists the candidates when more than one importer matches equally well
+*/
+#[tauri::command]
+pub async fn detect_statement_format(
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+) -> Result<Detection, DetectionError> {
+    let contents = fs::read(&file_path).map_err(|e| DetectionError::Unreadable {
+        message: format!("Error reading {file_path}: {e}"),
+    })?;
+    let registry = load_registry(&state.db.0)
+        .await
+        .map_err(|message| DetectionError::Unreadable { message })?;
+
+    registry.detect(&contents)
+}
 
 /*
 Dispatcher for importing transactions from a file
 
-Returns the transactions imported or an error
+Returns the transactions imported along with any lines that could not be parsed, or an error
 */
 #[tauri::command]
-pub fn import_transactions(
+pub async fn import_transactions(
+    state: tauri::State<'_, AppState>,
     file_path: String,
-    bank_name: String,
-) -> Result<Vec<TransactionImport>, String> {
-    match bank_name.as_str() {
-        "Bank of America" => {
-            bank_of_america::parse_csv_statement(file_path).map_err(|e| e.to_string())
-        }
-        "Wells Fargo" => wells_fargo::parse_csv_statement(file_path).map_err(|e| e.to_string()),
-        "American Express" => {
-            american_express::parse_csv_statement(file_path).map_err(|e| e.to_string())
-        }
-        _ => Err("Unsupported bank name".to_string()),
+    importer_id: String,
+) -> Result<ParseReport, String> {
+    let file = StatementFile::read(&file_path)?;
+    parse_statement(&state.db.0, &file, &importer_id).await
+}
+
+#[tauri::command]
+pub async fn get_import_profiles(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ImportProfile>, String> {
+    queries::get_import_profiles(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_import_profile(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    mapping: ColumnMapping,
+) -> Result<i64, String> {
+    mapping.validate()?;
+    queries::create_import_profile(&state.db.0, &name, &mapping)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_import_profile(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    name: String,
+    mapping: ColumnMapping,
+) -> Result<(), String> {
+    mapping.validate()?;
+    queries::update_import_profile(&state.db.0, id, &name, &mapping)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!("Import profile {id} doesn't exist"),
+            e => e.to_string(),
+        })
+}
+
+#[tauri::command]
+pub async fn delete_import_profile(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    queries::delete_import_profile(&state.db.0, id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!("Import profile {id} doesn't exist"),
+            e => e.to_string(),
+        })
+}
+
+/*
+Parses a statement and writes its rows into the given account
+
+All rows are inserted in one transaction, recorded as one import batch, and start out as
+Uncategorized. When the statement includes a closing balance, the summary compares it with
+the account's current balance
+*/
+#[tauri::command]
+pub async fn save_imported_transactions(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    importer_id: String,
+    account_id: i64,
+) -> Result<ImportSummary, String> {
+    let db = &state.db;
+    let file = StatementFile::read(&file_path)?;
+    let parsed = parse_statement(&db.0, &file, &importer_id).await?;
+
+    let uncategorized = categories::queries::get_uncategorized_category(&db.0)
+        .await
+        .map_err(|e| format!("Error fetching uncategorized category: {e}"))?;
+
+    let mut tx =
+        db.0.begin()
+            .await
+            .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let batch_id =
+        queries::create_import_batch(&mut tx, account_id, &importer_id, &file.path, &file.hash())
+            .await
+            .map_err(|e| format!("Failed to record import batch: {e}"))?;
+    let mut summary = transactions::queries::add_imported_transactions(
+        &mut tx,
+        account_id,
+        parsed.transactions,
+        uncategorized.id(),
+        Some(batch_id),
+    )
+    .await
+    .map_err(|e| format!("Failed to add imported transactions: {e}"))?;
+    queries::set_import_batch_row_count(&mut tx, batch_id, summary.inserted)
+        .await
+        .map_err(|e| format!("Failed to record import batch: {e}"))?;
+    transfers::queries::detect_transfers(&mut tx, true)
+        .await
+        .map_err(|e| format!("Failed to detect transfers: {e}"))?;
+    let recurring_scan =
+        recurring::queries::detect_recurring(&mut tx, Local::now().date_naive())
+            .await
+            .map_err(|e| format!("Failed to detect recurring charges: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+    recurring::commands::notify_new_alerts(&app_handle, recurring_scan);
+
+    summary.reconciliation = reconcile(&db.0, account_id, parsed.statement_balance).await?;
+    summary.rejected = parsed.rejected;
+    Ok(summary)
+}
+
+/*
+First phase of a reviewed import: parses a statement into a staging session
+
+Nothing is written to the ledger yet. Each row comes back with a proposed category, a
+duplicate flag and whether it could be parsed, and stays staged until the session is
+committed, discarded or expires
+*/
+#[tauri::command]
+pub async fn preview_import(
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    importer_id: String,
+    account_id: i64,
+) -> Result<ImportPreview, String> {
+    let db = &state.db;
+    let file = StatementFile::read(&file_path)?;
+    let parsed = parse_statement(&db.0, &file, &importer_id).await?;
+
+    queries::delete_expired_import_sessions(&db.0)
+        .await
+        .map_err(|e| format!("Error clearing expired import sessions: {e}"))?;
+
+    let uncategorized = categories::queries::get_uncategorized_category(&db.0)
+        .await
+        .map_err(|e| format!("Error fetching uncategorized category: {e}"))?;
+
+    let mut tx =
+        db.0.begin()
+            .await
+            .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let session_id = queries::create_import_session(
+        &mut tx,
+        account_id,
+        &importer_id,
+        &file,
+        parsed,
+        *uncategorized.id(),
+    )
+    .await
+    .map_err(|e| format!("Failed to stage import: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    let session = queries::get_import_session(&db.0, session_id)
+        .await
+        .map_err(|e| format!("Error fetching import session {session_id}: {e}"))?;
+    let rows = queries::get_staged_rows(&db.0, session_id, uncategorized.id())
+        .await
+        .map_err(|e| format!("Error fetching staged rows: {e}"))?;
+
+    Ok(ImportPreview { session, rows })
+}
+
+/*
+Second phase of a reviewed import: writes the accepted rows of a session to its account
+
+Rows keep their proposed category unless the user picked another one, and are recorded as
+one import batch. The session is removed once its rows are committed
+*/
+#[tauri::command]
+pub async fn commit_import(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+    accepted_rows: Vec<AcceptedRow>,
+) -> Result<ImportSummary, String> {
+    let db = &state.db;
+    queries::delete_expired_import_sessions(&db.0)
+        .await
+        .map_err(|e| format!("Error clearing expired import sessions: {e}"))?;
+
+    let session = queries::get_import_session(&db.0, session_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                format!("Import session {session_id} has expired or was already committed")
+            }
+            e => format!("Error fetching import session {session_id}: {e}"),
+        })?;
+    let uncategorized = categories::queries::get_uncategorized_category(&db.0)
+        .await
+        .map_err(|e| format!("Error fetching uncategorized category: {e}"))?;
+    let staged = queries::get_staged_rows(&db.0, session_id, uncategorized.id())
+        .await
+        .map_err(|e| format!("Error fetching staged rows: {e}"))?;
+
+    let mut rows = vec![];
+    for accepted in accepted_rows {
+        let row = staged
+            .iter()
+            .find(|row| row.row_index == accepted.row_index)
+            .ok_or(format!("Row {} is not part of this import", accepted.row_index))?;
+        let category_id = accepted.category_id.unwrap_or(row.proposed_category_id);
+        rows.push(row.to_imported_row(category_id)?);
     }
+
+    let mut tx =
+        db.0.begin()
+            .await
+            .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let batch_id = queries::create_import_batch(
+        &mut tx,
+        session.account_id,
+        &session.importer_id,
+        &session.file_path,
+        &session.file_hash,
+    )
+    .await
+    .map_err(|e| format!("Failed to record import batch: {e}"))?;
+    let mut summary = transactions::queries::insert_imported_rows(
+        &mut tx,
+        session.account_id,
+        rows,
+        Some(batch_id),
+    )
+    .await
+    .map_err(|e| format!("Failed to add imported transactions: {e}"))?;
+    queries::set_import_batch_row_count(&mut tx, batch_id, summary.inserted)
+        .await
+        .map_err(|e| format!("Failed to record import batch: {e}"))?;
+    transfers::queries::detect_transfers(&mut tx, true)
+        .await
+        .map_err(|e| format!("Failed to detect transfers: {e}"))?;
+    let recurring_scan =
+        recurring::queries::detect_recurring(&mut tx, Local::now().date_naive())
+            .await
+            .map_err(|e| format!("Failed to detect recurring charges: {e}"))?;
+    queries::delete_import_session(&mut *tx, session_id)
+        .await
+        .map_err(|e| format!("Failed to close import session: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+    recurring::commands::notify_new_alerts(&app_handle, recurring_scan);
+
+    summary.reconciliation =
+        reconcile(&db.0, session.account_id, session.statement_balance()).await?;
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn discard_import(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+) -> Result<(), String> {
+    queries::delete_import_session(&state.db.0, session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_import_batches(
+    state: tauri::State<'_, AppState>,
+    account_id: Option<i64>,
+) -> Result<Vec<ImportBatch>, String> {
+    queries::get_import_batches(&state.db.0, account_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Undoes a past import, soft-deleting every transaction it added
+
+Returns how many transactions were removed
+*/
+#[tauri::command]
+pub async fn rollback_import_batch(
+    state: tauri::State<'_, AppState>,
+    batch_id: i64,
+) -> Result<u64, String> {
+    queries::rollback_import_batch(&state.db.0, batch_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                format!("Import {batch_id} does not exist or was already rolled back")
+            }
+            e => format!("Failed to roll back import {batch_id}: {e}"),
+        })
 }
@@ -0,0 +1,325 @@
+use crate::importers::registry::Importer;
+use crate::importers::types::{parse_date, parse_money, ParseReport, TransactionImport};
+use csv::{ReaderBuilder, StringRecord};
+use rust_decimal::Decimal;
+
+// Rows looked at when deciding whether a profile fits a file
+const SNIFF_ROWS: usize = 5;
+// Profiles only check that rows parse, so they defer to the built-in importers' stronger signals
+const MAX_SNIFF_CONFIDENCE: f32 = 0.7;
+
+/// How a single amount column represents money leaving the account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SignConvention {
+    DebitsNegative,
+    DebitsPositive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AmountColumns {
+    Single {
+        column: usize,
+        sign_convention: SignConvention,
+    },
+    /// Separate debit and credit columns, where only one of them is filled in per row
+    Split { debit_column: usize, credit_column: usize },
+}
+
+/// Where each transaction field lives in a bank's CSV export. Column indexes start at 0
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ColumnMapping {
+    pub delimiter: char,
+    pub skip_rows: usize,
+    pub date_column: usize,
+    /// chrono format string, e.g. %m/%d/%Y
+    pub date_format: String,
+    pub amount: AmountColumns,
+    /// Joined with a space to build the transaction name
+    pub description_columns: Vec<usize>,
+}
+
+impl ColumnMapping {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.delimiter.is_ascii() {
+            return Err("Delimiter must be a single ASCII character".to_string());
+        }
+        if self.description_columns.is_empty() {
+            return Err("At least one description column is required".to_string());
+        }
+        if self.date_format.trim().is_empty() {
+            return Err("Date format is required".to_string());
+        }
+        Ok(())
+    }
+
+    fn parse_record(&self, record: &StringRecord) -> Result<TransactionImport, String> {
+        let field = |column: usize| {
+            record
+                .get(column)
+                .ok_or(format!("missing column {column}"))
+        };
+
+        let date = parse_date(field(self.date_column)?, &self.date_format)?;
+        let amount = match self.amount {
+            AmountColumns::Single {
+                column,
+                sign_convention,
+            } => {
+                let amount = parse_money(field(column)?)?;
+                match sign_convention {
+                    SignConvention::DebitsNegative => amount,
+                    SignConvention::DebitsPositive => -amount,
+                }
+            }
+            AmountColumns::Split {
+                debit_column,
+                credit_column,
+            } => {
+                let debit = parse_optional_money(field(debit_column)?)?;
+                let credit = parse_optional_money(field(credit_column)?)?;
+                credit.abs() - debit.abs()
+            }
+        };
+
+        let mut name_parts = vec![];
+        for column in &self.description_columns {
+            let part = field(*column)?.trim();
+            if !part.is_empty() {
+                name_parts.push(part);
+            }
+        }
+
+        Ok(TransactionImport {
+            date,
+            name: name_parts.join(" "),
+            amount,
+            ..Default::default()
+        })
+    }
+
+    fn reader<'a>(&self, contents: &'a [u8]) -> csv::Reader<&'a [u8]> {
+        ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter as u8)
+            .from_reader(contents)
+    }
+}
+
+fn parse_optional_money(s: &str) -> Result<Decimal, String> {
+    if s.trim().is_empty() {
+        Ok(Decimal::ZERO)
+    } else {
+        parse_money(s)
+    }
+}
+
+pub fn parse_with_mapping(contents: &[u8], mapping: &ColumnMapping) -> ParseReport {
+    let mut reader = mapping.reader(contents);
+    let records = reader.records().skip(mapping.skip_rows);
+
+    ParseReport::from_records_with(contents, records, |record| mapping.parse_record(record))
+}
+
+/// A user-defined mapping saved so a bank without a built-in importer can be imported
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ImportProfile {
+    pub id: i64,
+    pub name: String,
+    pub mapping: ColumnMapping,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for ImportProfile {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+        let decode_error = |column: &str, e: String| sqlx::Error::ColumnDecode {
+            index: column.to_owned(),
+            source: e.into(),
+        };
+
+        let delimiter: String = row.try_get("delimiter")?;
+        let amount = match row.try_get::<Option<i64>, _>("amount_column")? {
+            Some(column) => AmountColumns::Single {
+                column: column as usize,
+                sign_convention: row.try_get("sign_convention")?,
+            },
+            None => AmountColumns::Split {
+                debit_column: row.try_get::<i64, _>("debit_column")? as usize,
+                credit_column: row.try_get::<i64, _>("credit_column")? as usize,
+            },
+        };
+        let description_columns: String = row.try_get("description_columns")?;
+
+        Ok(ImportProfile {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            mapping: ColumnMapping {
+                delimiter: delimiter
+                    .chars()
+                    .next()
+                    .ok_or(decode_error("delimiter", "empty delimiter".to_string()))?,
+                skip_rows: row.try_get::<i64, _>("skip_rows")? as usize,
+                date_column: row.try_get::<i64, _>("date_column")? as usize,
+                date_format: row.try_get("date_format")?,
+                amount,
+                description_columns: serde_json::from_str(&description_columns)
+                    .map_err(|e| decode_error("description_columns", e.to_string()))?,
+            },
+        })
+    }
+}
+
+pub struct ProfileImporter {
+    id: String,
+    profile: ImportProfile,
+}
+
+impl ProfileImporter {
+    pub fn new(profile: ImportProfile) -> Self {
+        ProfileImporter {
+            id: format!("profile:{}", profile.id),
+            profile,
+        }
+    }
+}
+
+impl Importer for ProfileImporter {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.profile.name
+    }
+
+    fn sniff(&self, contents: &[u8]) -> f32 {
+        let mapping = &self.profile.mapping;
+        let mut reader = mapping.reader(contents);
+        let rows: Vec<StringRecord> = reader
+            .records()
+            .skip(mapping.skip_rows)
+            .take(SNIFF_ROWS)
+            .filter_map(|record| record.ok())
+            .collect();
+        if rows.is_empty() {
+            return 0.0;
+        }
+
+        let parsed = rows
+            .iter()
+            .filter(|row| mapping.parse_record(row).is_ok())
+            .count();
+        MAX_SNIFF_CONFIDENCE * parsed as f32 / rows.len() as f32
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String> {
+        Ok(parse_with_mapping(contents, &self.profile.mapping))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::dec;
+
+    fn split_mapping() -> ColumnMapping {
+        ColumnMapping {
+            delimiter: ';',
+            skip_rows: 1,
+            date_column: 0,
+            date_format: "%Y-%m-%d".to_owned(),
+            amount: AmountColumns::Split {
+                debit_column: 3,
+                credit_column: 4,
+            },
+            description_columns: vec![1, 2],
+        }
+    }
+
+    #[test]
+    fn parses_split_debit_and_credit_columns() {
+        let contents = b"Posted;Payee;Memo;Debit;Credit\n\
+2025-12-01;COFFEE SHOP;CARD 1234;4.50;\n\
+2025-12-02;PAYROLL;;;1,200.00\n";
+
+        let report = parse_with_mapping(contents, &split_mapping());
+
+        assert!(report.rejected.is_empty());
+        assert_eq!(
+            report.transactions,
+            vec![
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+                    name: "COFFEE SHOP CARD 1234".to_owned(),
+                    amount: dec!(-4.50),
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 2).unwrap(),
+                    name: "PAYROLL".to_owned(),
+                    amount: dec!(1200.00),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_sign_convention_to_single_amount_column() {
+        let mapping = ColumnMapping {
+            delimiter: ',',
+            skip_rows: 0,
+            date_column: 1,
+            date_format: "%d.%m.%Y".to_owned(),
+            amount: AmountColumns::Single {
+                column: 2,
+                sign_convention: SignConvention::DebitsPositive,
+            },
+            description_columns: vec![0],
+        };
+
+        let report = parse_with_mapping(b"GROCERY STORE,03.12.2025,$25.10\n", &mapping);
+
+        assert_eq!(report.transactions[0].amount, dec!(-25.10));
+        assert_eq!(
+            report.transactions[0].date,
+            NaiveDate::from_ymd_opt(2025, 12, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_rows_that_do_not_fit_the_mapping() {
+        let contents = b"Posted;Payee;Memo;Debit;Credit\n\
+12/01/2025;COFFEE SHOP;;4.50;\n\
+2025-12-02;SHORT ROW\n";
+
+        let report = parse_with_mapping(contents, &split_mapping());
+
+        assert!(report.transactions.is_empty());
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].line, 2);
+        assert!(report.rejected[0].reason.contains("invalid date '12/01/2025'"));
+        assert_eq!(report.rejected[1].raw, "2025-12-02;SHORT ROW");
+        assert!(report.rejected[1].reason.contains("missing column"));
+    }
+
+    #[test]
+    fn profile_sniff_scales_with_parsed_rows() {
+        let importer = ProfileImporter::new(ImportProfile {
+            id: 7,
+            name: "Credit Union".to_owned(),
+            mapping: split_mapping(),
+        });
+
+        assert_eq!(importer.id(), "profile:7");
+        assert_eq!(
+            importer.sniff(b"Posted;Payee;Memo;Debit;Credit\n2025-12-01;COFFEE;;4.50;\n"),
+            MAX_SNIFF_CONFIDENCE
+        );
+        assert_eq!(importer.sniff(b"Date,Description,Amount\n12/01/2025,COFFEE,-4.50\n"), 0.0);
+    }
+}
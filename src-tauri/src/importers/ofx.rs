@@ -0,0 +1,348 @@
+use crate::importers::registry::Importer;
+use crate::importers::types::{
+    parse_date, parse_money, ParseReport, RejectedRow, StatementBalance, TransactionImport,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+// OFX headers (SGML or XML) always come before the first transaction
+const SNIFF_BYTES: usize = 1024;
+
+/// OFX 1.x (SGML) and 2.x (XML) statements, which is also what QFX files contain
+pub struct OfxImporter;
+
+impl Importer for OfxImporter {
+    fn id(&self) -> &str {
+        "ofx"
+    }
+
+    fn display_name(&self) -> &str {
+        "OFX / QFX"
+    }
+
+    fn sniff(&self, contents: &[u8]) -> f32 {
+        let head = &contents[..contents.len().min(SNIFF_BYTES)];
+        let head = String::from_utf8_lossy(head).to_uppercase();
+
+        if head.contains("OFXHEADER") || head.contains("<OFX>") {
+            0.95
+        } else {
+            0.0
+        }
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String> {
+        parse_ofx_bytes(contents)
+    }
+}
+
+struct Element<'a> {
+    tag: String,
+    closing: bool,
+    value: &'a str,
+    offset: usize,
+}
+
+/*
+Splits an OFX body into its tags and the text that follows each one
+
+SGML statements leave leaf elements unclosed (`<TRNAMT>-4.50` with no `</TRNAMT>`), so
+rather than building a tree the text up to the next tag is taken as the element's value.
+This reads XML statements the same way
+*/
+fn elements(body: &str) -> Vec<Element<'_>> {
+    let mut elements = vec![];
+    let mut rest = body;
+    let mut offset = 0;
+
+    while let Some(open) = rest.find('<') {
+        let Some(close) = rest[open..].find('>').map(|i| open + i) else {
+            break;
+        };
+        let tag = rest[open + 1..close].trim().trim_end_matches('/');
+        let value_end = rest[close + 1..]
+            .find('<')
+            .map_or(rest.len(), |i| close + 1 + i);
+
+        let (closing, name) = match tag.strip_prefix('/') {
+            Some(name) => (true, name),
+            None => (false, tag),
+        };
+        elements.push(Element {
+            tag: name.trim().to_uppercase(),
+            closing,
+            value: rest[close + 1..value_end].trim(),
+            offset: offset + open,
+        });
+
+        offset += value_end;
+        rest = &rest[value_end..];
+    }
+
+    elements
+}
+
+// Only the entities banks actually emit
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+// Dates are YYYYMMDD optionally followed by a time and timezone, e.g. 20251202120000[-5:EST]
+fn parse_ofx_date(value: &str) -> Result<NaiveDate, String> {
+    let date = value.get(..8).ok_or(format!("invalid date '{value}'"))?;
+    parse_date(date, "%Y%m%d")
+}
+
+// Some banks write amounts with a decimal comma
+fn parse_ofx_amount(value: &str) -> Result<Decimal, String> {
+    if value.contains(',') && !value.contains('.') {
+        parse_money(&value.replace(',', "."))
+    } else {
+        parse_money(value)
+    }
+}
+
+struct TransactionBlock {
+    offset: usize,
+    end: usize,
+    fields: HashMap<String, String>,
+}
+
+impl TransactionBlock {
+    fn field(&self, tag: &str) -> Result<&str, String> {
+        self.fields
+            .get(tag)
+            .map(|value| value.as_str())
+            .ok_or(format!("missing {tag}"))
+    }
+
+    fn parse(&self) -> Result<TransactionImport, String> {
+        let name = self
+            .fields
+            .get("NAME")
+            .or(self.fields.get("MEMO"))
+            .ok_or("missing NAME and MEMO".to_string())?;
+
+        Ok(TransactionImport {
+            date: parse_ofx_date(self.field("DTPOSTED")?)?,
+            name: name.clone(),
+            amount: parse_ofx_amount(self.field("TRNAMT")?)?,
+            external_id: Some(self.field("FITID")?.to_owned()),
+            ..Default::default()
+        })
+    }
+
+    fn rejected(&self, text: &str, reason: String) -> RejectedRow {
+        RejectedRow {
+            line: text[..self.offset].matches('\n').count() as u64 + 1,
+            raw: text[self.offset..self.end]
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" "),
+            reason,
+        }
+    }
+}
+
+pub fn parse_ofx_bytes(contents: &[u8]) -> Result<ParseReport, String> {
+    let text = String::from_utf8_lossy(contents);
+    let body_start = text
+        .to_ascii_uppercase()
+        .find("<OFX>")
+        .ok_or("Not an OFX statement, no <OFX> element found".to_string())?;
+
+    let mut report = ParseReport::default();
+    let mut blocks = vec![];
+    let mut current: Option<TransactionBlock> = None;
+    let mut in_ledger_balance = false;
+    let mut balance_amount = None;
+    let mut balance_date = None;
+
+    for element in elements(&text[body_start..]) {
+        let offset = body_start + element.offset;
+        match (element.closing, element.tag.as_str()) {
+            (false, "STMTTRN") => {
+                // An unterminated transaction ends where the next one starts
+                if let Some(mut block) = current.take() {
+                    block.end = offset;
+                    blocks.push(block);
+                }
+                current = Some(TransactionBlock {
+                    offset,
+                    end: offset,
+                    fields: HashMap::new(),
+                });
+            }
+            (true, "STMTTRN") => {
+                if let Some(mut block) = current.take() {
+                    block.end = offset + "</STMTTRN>".len();
+                    blocks.push(block);
+                }
+            }
+            (true, "BANKTRANLIST") => {
+                if let Some(mut block) = current.take() {
+                    block.end = offset;
+                    blocks.push(block);
+                }
+            }
+            (false, "LEDGERBAL") => in_ledger_balance = true,
+            (true, "LEDGERBAL") => in_ledger_balance = false,
+            (false, tag) if !element.value.is_empty() => {
+                if let Some(block) = current.as_mut() {
+                    block
+                        .fields
+                        .insert(tag.to_owned(), decode_entities(element.value));
+                } else if in_ledger_balance && tag == "BALAMT" {
+                    balance_amount = parse_ofx_amount(element.value).ok();
+                } else if in_ledger_balance && tag == "DTASOF" {
+                    balance_date = parse_ofx_date(element.value).ok();
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(mut block) = current.take() {
+        block.end = text.len();
+        blocks.push(block);
+    }
+
+    for block in blocks {
+        match block.parse() {
+            Ok(transaction) => report.transactions.push(transaction),
+            Err(reason) => report.rejected.push(block.rejected(&text, reason)),
+        }
+    }
+
+    if let (Some(amount), Some(as_of)) = (balance_amount, balance_date) {
+        report.statement_balance = Some(StatementBalance { amount, as_of });
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    const XML_STATEMENT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>
+<OFX>
+  <CREDITCARDMSGSRSV1>
+    <CCSTMTTRNRS>
+      <CCSTMTRS>
+        <CURDEF>USD</CURDEF>
+        <BANKTRANLIST>
+          <STMTTRN>
+            <TRNTYPE>DEBIT</TRNTYPE>
+            <DTPOSTED>20251203</DTPOSTED>
+            <TRNAMT>-25.10</TRNAMT>
+            <FITID>3201</FITID>
+            <MEMO>GROCERY STORE</MEMO>
+          </STMTTRN>
+          <STMTTRN>
+            <TRNTYPE>DEBIT</TRNTYPE>
+            <DTPOSTED>2025-12-04</DTPOSTED>
+            <TRNAMT>-8.00</TRNAMT>
+            <FITID>3202</FITID>
+            <NAME>PARKING</NAME>
+          </STMTTRN>
+        </BANKTRANLIST>
+        <LEDGERBAL>
+          <BALAMT>-425.10</BALAMT>
+          <DTASOF>20251231</DTASOF>
+        </LEDGERBAL>
+      </CCSTMTRS>
+    </CCSTMTTRNRS>
+  </CREDITCARDMSGSRSV1>
+</OFX>
+"#;
+
+    #[test]
+    fn parses_sgml_statement() -> Result<(), Box<dyn std::error::Error>> {
+        let report = parse_ofx_bytes(include_bytes!("../../../data/ofxtransactions.qfx"))?;
+
+        assert!(report.rejected.is_empty());
+        assert_eq!(
+            report.transactions,
+            vec![
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 2).unwrap(),
+                    name: "COFFEE SHOP".to_owned(),
+                    amount: dec!(-4.50),
+                    external_id: Some("202512020001".to_owned()),
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 15).unwrap(),
+                    name: "PAYROLL ACME CO".to_owned(),
+                    amount: dec!(1200.00),
+                    external_id: Some("202512150001".to_owned()),
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 20).unwrap(),
+                    name: "JOE&S MARKET".to_owned(),
+                    amount: dec!(-62.18),
+                    external_id: Some("202512200001".to_owned()),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(
+            report.statement_balance,
+            Some(StatementBalance {
+                amount: dec!(1133.32),
+                as_of: NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parses_xml_statement_and_rejects_bad_transactions() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let report = parse_ofx_bytes(XML_STATEMENT.as_bytes())?;
+
+        assert_eq!(report.transactions.len(), 1);
+        assert_eq!(report.transactions[0].name, "GROCERY STORE");
+        assert_eq!(report.transactions[0].external_id.as_deref(), Some("3201"));
+
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 16);
+        assert!(report.rejected[0].raw.starts_with("<STMTTRN> <TRNTYPE>DEBIT</TRNTYPE>"));
+        assert!(report.rejected[0].raw.ends_with("</STMTTRN>"));
+        assert!(report.rejected[0].reason.contains("invalid date '2025-12-'"));
+
+        assert_eq!(
+            report.statement_balance.map(|balance| balance.amount),
+            Some(dec!(-425.10))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sniff_recognises_both_ofx_versions() {
+        assert_eq!(
+            OfxImporter.sniff(include_bytes!("../../../data/ofxtransactions.qfx")),
+            0.95
+        );
+        assert_eq!(OfxImporter.sniff(XML_STATEMENT.as_bytes()), 0.95);
+        assert_eq!(
+            OfxImporter.sniff(include_bytes!("../../../data/wellsfargotransactions.csv")),
+            0.0
+        );
+    }
+
+    #[test]
+    fn parse_fails_without_ofx_body() {
+        assert!(parse_ofx_bytes(b"OFXHEADER:100\nDATA:OFXSGML\n").is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use crate::importers::registry::Importer;
+use crate::importers::types::{
+    parse_money, ImportSplit, ParseReport, RejectedRow, TransactionImport,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+// Account types whose records are plain transactions, investment sections are skipped
+const TRANSACTION_SECTIONS: [&str; 2] = ["!TYPE:BANK", "!TYPE:CCARD"];
+
+/// Quicken Interchange Format exports from Quicken, GnuCash and similar tools
+pub struct QifImporter;
+
+impl Importer for QifImporter {
+    fn id(&self) -> &str {
+        "qif"
+    }
+
+    fn display_name(&self) -> &str {
+        "QIF"
+    }
+
+    fn sniff(&self, contents: &[u8]) -> f32 {
+        let text = String::from_utf8_lossy(contents);
+        let Some(first_line) = text.lines().map(str::trim).find(|line| !line.is_empty()) else {
+            return 0.0;
+        };
+
+        let first_line = first_line.trim_start_matches('\u{feff}').to_uppercase();
+        if first_line.starts_with("!TYPE:")
+            || first_line.starts_with("!ACCOUNT")
+            || first_line.starts_with("!OPTION:")
+        {
+            0.95
+        } else {
+            0.0
+        }
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String> {
+        Ok(parse_qif_bytes(contents))
+    }
+}
+
+/*
+Reads QIF dates in either of the styles Quicken has used
+
+`MM/DD'YY` marks years from 2000 on, while a two digit year after a slash is in the 1900s.
+Single digit days and months are often padded with a space, e.g. `12/ 3'25`
+*/
+fn parse_qif_date(value: &str) -> Result<NaiveDate, String> {
+    let compact = value.replace(' ', "");
+    let invalid = || format!("invalid date '{value}'");
+
+    let (month_day, year, century) = match compact.split_once('\'') {
+        Some((month_day, year)) => (month_day, year, 2000),
+        None => {
+            let (month_day, year) = compact.rsplit_once('/').ok_or_else(invalid)?;
+            (month_day, year, 1900)
+        }
+    };
+    let (month, day) = month_day.split_once('/').ok_or_else(invalid)?;
+
+    let mut year: i32 = year.parse().map_err(|_| invalid())?;
+    if year < 100 {
+        year += century;
+    }
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let day: u32 = day.parse().map_err(|_| invalid())?;
+
+    NaiveDate::from_ymd_opt(year, month, day).ok_or_else(invalid)
+}
+
+/*
+Turns an L or S field into a category name
+
+Transfers are written as `[Account Name]` and have no category, and anything after a `/` is
+a Quicken class rather than part of the category
+*/
+fn category_label(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.starts_with('[') || value.eq_ignore_ascii_case("--Split--") {
+        return None;
+    }
+
+    let category = value.split('/').next().unwrap_or_default().trim();
+    if category.is_empty() {
+        None
+    } else {
+        Some(category.to_owned())
+    }
+}
+
+#[derive(Default)]
+struct Record<'a> {
+    line: u64,
+    lines: Vec<&'a str>,
+}
+
+impl Record<'_> {
+    fn parse(&self) -> Result<TransactionImport, String> {
+        let mut date = None;
+        let mut amount = None;
+        let mut payee = None;
+        let mut memo = None;
+        let mut category = None;
+        let mut splits: Vec<ImportSplit> = vec![];
+
+        for line in &self.lines {
+            let mut chars = line.chars();
+            let Some(code) = chars.next() else {
+                continue;
+            };
+            let value = chars.as_str().trim();
+
+            match code {
+                'D' => date = Some(parse_qif_date(value)?),
+                'T' | 'U' => amount = Some(parse_money(value)?),
+                'P' => payee = Some(value),
+                'M' => memo = Some(value),
+                'L' => category = category_label(value),
+                'S' => splits.push(ImportSplit {
+                    category: category_label(value),
+                    memo: None,
+                    amount: Decimal::ZERO,
+                }),
+                'E' => {
+                    if let Some(split) = splits.last_mut() {
+                        split.memo = Some(value.to_owned());
+                    }
+                }
+                '$' => {
+                    let split = splits
+                        .last_mut()
+                        .ok_or("split amount without a split category".to_string())?;
+                    split.amount = parse_money(value)?;
+                }
+                // Check number, cleared status, address lines and the like
+                _ => {}
+            }
+        }
+
+        let amount = amount.ok_or("missing amount".to_string())?;
+        if !splits.is_empty() {
+            let split_total: Decimal = splits.iter().map(|split| split.amount).sum();
+            if split_total != amount {
+                return Err(format!(
+                    "splits total {split_total} but the transaction amount is {amount}"
+                ));
+            }
+        }
+
+        Ok(TransactionImport {
+            date: date.ok_or("missing date".to_string())?,
+            name: payee
+                .or(memo)
+                .filter(|name| !name.is_empty())
+                .ok_or("missing payee and memo".to_string())?
+                .to_owned(),
+            amount,
+            category,
+            splits,
+            ..Default::default()
+        })
+    }
+
+    fn rejected(&self, reason: String) -> RejectedRow {
+        RejectedRow {
+            line: self.line,
+            raw: self.lines.join(" "),
+            reason,
+        }
+    }
+}
+
+pub fn parse_qif_bytes(contents: &[u8]) -> ParseReport {
+    let text = String::from_utf8_lossy(contents);
+    let mut report = ParseReport::default();
+    let mut in_transactions = false;
+    let mut record = Record::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.starts_with('!') {
+            let header = line.replace(' ', "").to_uppercase();
+            // Option lines only toggle exporter settings, the current section carries on
+            if !header.starts_with("!OPTION") {
+                in_transactions = TRANSACTION_SECTIONS.contains(&header.as_str());
+            }
+            record = Record::default();
+            continue;
+        }
+
+        if line.starts_with('^') {
+            if in_transactions && !record.lines.is_empty() {
+                match record.parse() {
+                    Ok(transaction) => report.transactions.push(transaction),
+                    Err(reason) => report.rejected.push(record.rejected(reason)),
+                }
+            }
+            record = Record::default();
+            continue;
+        }
+
+        if record.lines.is_empty() {
+            record.line = index as u64 + 1;
+        }
+        record.lines.push(line);
+    }
+
+    // Some exporters leave off the terminator after the last record
+    if in_transactions && !record.lines.is_empty() {
+        match record.parse() {
+            Ok(transaction) => report.transactions.push(transaction),
+            Err(reason) => report.rejected.push(record.rejected(reason)),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    #[test]
+    fn parses_bank_section_with_splits() {
+        let report = parse_qif_bytes(include_bytes!("../../../data/qiftransactions.qif"));
+
+        assert!(report.rejected.is_empty());
+        assert_eq!(
+            report.transactions,
+            vec![
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+                    name: "LANDLORD PROPERTY MGMT".to_owned(),
+                    amount: dec!(-1250.00),
+                    category: Some("Housing".to_owned()),
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 3).unwrap(),
+                    name: "CORNER GROCERY".to_owned(),
+                    amount: dec!(-84.27),
+                    category: Some("Food:Groceries".to_owned()),
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 5).unwrap(),
+                    name: "WAREHOUSE CLUB".to_owned(),
+                    amount: dec!(-120.00),
+                    category: None,
+                    splits: vec![
+                        ImportSplit {
+                            category: Some("Groceries".to_owned()),
+                            memo: Some("Food".to_owned()),
+                            amount: dec!(-75.00),
+                        },
+                        ImportSplit {
+                            category: Some("Shopping".to_owned()),
+                            memo: Some("Household".to_owned()),
+                            amount: dec!(-45.00),
+                        },
+                    ],
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 15).unwrap(),
+                    name: "ACME CORP PAYROLL".to_owned(),
+                    amount: dec!(2400.00),
+                    category: Some("Income".to_owned()),
+                    ..Default::default()
+                },
+                TransactionImport {
+                    date: NaiveDate::from_ymd_opt(2025, 12, 20).unwrap(),
+                    name: "TRANSFER TO SAVINGS".to_owned(),
+                    amount: dec!(-500.00),
+                    category: None,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_both_date_styles() {
+        assert_eq!(
+            parse_qif_date("12/ 3'25"),
+            Ok(NaiveDate::from_ymd_opt(2025, 12, 3).unwrap())
+        );
+        assert_eq!(
+            parse_qif_date("1/15/2024"),
+            Ok(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+        );
+        assert_eq!(
+            parse_qif_date("07/04/98"),
+            Ok(NaiveDate::from_ymd_opt(1998, 7, 4).unwrap())
+        );
+        assert!(parse_qif_date("2025-12-03").is_err());
+    }
+
+    #[test]
+    fn skips_non_transaction_sections_and_rejects_bad_records() {
+        let contents = b"!Account\nNChecking\nTBank\n^\n\
+!Type:CCard\nD12/10'25\nT-30.00\nPBOOKSTORE\n^\n\
+D13/10'25\nT-5.00\nPCOFFEE\n^\n\
+D12/11'25\nT-50.00\nPSTORE\nSShopping\n$-20.00\n^\n\
+!Type:Invst\nD12/12'25\nNBuy\nYACME\n^\n\
+!Type:CCard\nD12/13'25\nT-9.99\nMSTREAMING SERVICE\n";
+
+        let report = parse_qif_bytes(contents);
+
+        let names: Vec<&str> = report.transactions.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["BOOKSTORE", "STREAMING SERVICE"]);
+
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].line, 10);
+        assert_eq!(report.rejected[0].raw, "D13/10'25 T-5.00 PCOFFEE");
+        assert!(report.rejected[0].reason.contains("invalid date '13/10'25'"));
+        assert!(report.rejected[1].reason.contains("splits total -20.00"));
+    }
+
+    #[test]
+    fn sniff_requires_qif_header() {
+        assert_eq!(
+            QifImporter.sniff(include_bytes!("../../../data/qiftransactions.qif")),
+            0.95
+        );
+        assert_eq!(
+            QifImporter.sniff(include_bytes!("../../../data/ofxtransactions.qfx")),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,721 @@
+use crate::categories;
+use crate::importers::generic::{AmountColumns, ColumnMapping, ImportProfile, SignConvention};
+use crate::importers::types::{
+    file_name, import_fingerprints, CategoryResolver, ImportBatch, ImportSession, ParseReport,
+    RowStatus, StagedRow, StatementFile,
+};
+use crate::rules;
+use crate::suggestions;
+use crate::types::Cents;
+use chrono::NaiveDate;
+use sqlx::{Pool, Sqlite, SqliteConnection, SqliteExecutor};
+
+const PROFILE_COLUMNS: &str = r#"
+    id,
+    name,
+    delimiter,
+    skip_rows,
+    date_column,
+    date_format,
+    amount_column,
+    sign_convention,
+    debit_column,
+    credit_column,
+    description_columns
+"#;
+
+// Flattens AmountColumns into (amount_column, sign_convention, debit_column, credit_column)
+fn amount_columns(
+    amount: &AmountColumns,
+) -> (Option<i64>, SignConvention, Option<i64>, Option<i64>) {
+    match *amount {
+        AmountColumns::Single {
+            column,
+            sign_convention,
+        } => (Some(column as i64), sign_convention, None, None),
+        AmountColumns::Split {
+            debit_column,
+            credit_column,
+        } => (
+            None,
+            SignConvention::DebitsNegative,
+            Some(debit_column as i64),
+            Some(credit_column as i64),
+        ),
+    }
+}
+
+pub async fn get_import_profiles(pool: &Pool<Sqlite>) -> Result<Vec<ImportProfile>, sqlx::Error> {
+    let query = format!("SELECT {PROFILE_COLUMNS} FROM import_profile ORDER BY name");
+
+    let profiles: Vec<ImportProfile> = sqlx::query_as(&query).fetch_all(pool).await?;
+
+    Ok(profiles)
+}
+
+pub async fn create_import_profile(
+    pool: &Pool<Sqlite>,
+    name: &str,
+    mapping: &ColumnMapping,
+) -> Result<i64, sqlx::Error> {
+    let query = r#"
+        INSERT INTO import_profile (
+            name,
+            delimiter,
+            skip_rows,
+            date_column,
+            date_format,
+            amount_column,
+            sign_convention,
+            debit_column,
+            credit_column,
+            description_columns
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+    "#;
+    let (amount_column, sign_convention, debit_column, credit_column) =
+        amount_columns(&mapping.amount);
+
+    let id: i64 = sqlx::query_scalar(query)
+        .bind(name)
+        .bind(mapping.delimiter.to_string())
+        .bind(mapping.skip_rows as i64)
+        .bind(mapping.date_column as i64)
+        .bind(&mapping.date_format)
+        .bind(amount_column)
+        .bind(sign_convention)
+        .bind(debit_column)
+        .bind(credit_column)
+        .bind(serde_json::json!(mapping.description_columns).to_string())
+        .fetch_one(pool)
+        .await?;
+
+    Ok(id)
+}
+
+pub async fn update_import_profile(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    name: &str,
+    mapping: &ColumnMapping,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE import_profile
+        SET name = ?,
+            delimiter = ?,
+            skip_rows = ?,
+            date_column = ?,
+            date_format = ?,
+            amount_column = ?,
+            sign_convention = ?,
+            debit_column = ?,
+            credit_column = ?,
+            description_columns = ?
+        WHERE id = ?
+    "#;
+    let (amount_column, sign_convention, debit_column, credit_column) =
+        amount_columns(&mapping.amount);
+
+    let res = sqlx::query(query)
+        .bind(name)
+        .bind(mapping.delimiter.to_string())
+        .bind(mapping.skip_rows as i64)
+        .bind(mapping.date_column as i64)
+        .bind(&mapping.date_format)
+        .bind(amount_column)
+        .bind(sign_convention)
+        .bind(debit_column)
+        .bind(credit_column)
+        .bind(serde_json::json!(mapping.description_columns).to_string())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+pub async fn delete_import_profile(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+    let res = sqlx::query("DELETE FROM import_profile WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+// How long an uncommitted import session is kept, as an SQLite datetime modifier
+const SESSION_LIFETIME: &str = "+1 day";
+
+const SESSION_COLUMNS: &str = r#"
+    id,
+    account_id,
+    importer_id,
+    file_path,
+    file_hash,
+    statement_balance_cents,
+    statement_balance_as_of,
+    created_at,
+    expires_at
+"#;
+
+pub async fn category_resolver(
+    conn: &mut SqliteConnection,
+) -> Result<CategoryResolver, sqlx::Error> {
+    let category_ids = categories::queries::get_category_ids_by_name(&mut *conn).await?;
+    let rules = rules::queries::get_rule_set(&mut *conn).await?;
+    let model = suggestions::queries::get_category_model(conn).await?;
+
+    Ok(CategoryResolver::new(category_ids, rules, model))
+}
+
+/*
+Stages a parsed statement for review and returns the new session's id
+
+Each row gets a proposed category from the name its file gave it, the user's rules or past
+corrections, and is flagged as a duplicate when the account already holds a row with the same
+fingerprint. Rejected lines are staged too, as invalid rows, so the preview can list everything
+in the file
+*/
+pub async fn create_import_session(
+    conn: &mut SqliteConnection,
+    account_id: i64,
+    importer_id: &str,
+    file: &StatementFile,
+    report: ParseReport,
+    default_category: i64,
+) -> Result<i64, sqlx::Error> {
+    let account_exists: Option<i64> = sqlx::query_scalar("SELECT id FROM account WHERE id=?")
+        .bind(account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if account_exists.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    let session_query = r#"
+        INSERT INTO import_session (
+            account_id,
+            importer_id,
+            file_path,
+            file_hash,
+            statement_balance_cents,
+            statement_balance_as_of,
+            expires_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, datetime('now', ?))
+        RETURNING id
+    "#;
+    let session_id: i64 = sqlx::query_scalar(session_query)
+        .bind(account_id)
+        .bind(importer_id)
+        .bind(&file.path)
+        .bind(file.hash())
+        .bind(report.statement_balance.as_ref().map(|b| Cents(b.amount)))
+        .bind(report.statement_balance.as_ref().map(|b| b.as_of))
+        .bind(SESSION_LIFETIME)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let row_query = r#"
+        INSERT INTO import_staged_row (
+            session_id,
+            row_index,
+            date,
+            name,
+            amount_cents,
+            external_id,
+            category_hint,
+            splits,
+            import_fingerprint,
+            proposed_category_id,
+            duplicate,
+            status,
+            reason
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    "#;
+
+    let resolver = category_resolver(&mut *conn).await?;
+    let fingerprints = import_fingerprints(&report.transactions);
+
+    let mut row_index: i64 = 0;
+    for (t, fingerprint) in report.transactions.iter().zip(fingerprints) {
+        let duplicate: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM 'transaction' WHERE account_id=? AND import_fingerprint=?)",
+        )
+        .bind(account_id)
+        .bind(&fingerprint)
+        .fetch_one(&mut *conn)
+        .await?;
+        let proposed_category = resolver.resolve(t, account_id).unwrap_or(default_category);
+        let splits = serde_json::to_string(&t.splits)
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        sqlx::query(row_query)
+            .bind(session_id)
+            .bind(row_index)
+            .bind(t.date)
+            .bind(&t.name)
+            .bind(Cents(t.amount))
+            .bind(&t.external_id)
+            .bind(&t.category)
+            .bind(splits)
+            .bind(fingerprint)
+            .bind(proposed_category)
+            .bind(duplicate)
+            .bind(RowStatus::Valid)
+            .bind(None::<String>)
+            .execute(&mut *conn)
+            .await?;
+        row_index += 1;
+    }
+
+    for rejected in report.rejected {
+        sqlx::query(row_query)
+            .bind(session_id)
+            .bind(row_index)
+            .bind(None::<NaiveDate>)
+            .bind(rejected.raw)
+            .bind(None::<Cents>)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind("[]")
+            .bind(None::<String>)
+            .bind(default_category)
+            .bind(false)
+            .bind(RowStatus::Invalid)
+            .bind(format!("line {}: {}", rejected.line, rejected.reason))
+            .execute(&mut *conn)
+            .await?;
+        row_index += 1;
+    }
+
+    Ok(session_id)
+}
+
+/// Fails with `RowNotFound` once the session has expired or been committed
+pub async fn get_import_session(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<ImportSession, sqlx::Error> {
+    let query = format!(
+        "SELECT {SESSION_COLUMNS} FROM import_session WHERE id=? AND expires_at > datetime('now')"
+    );
+
+    sqlx::query_as(&query).bind(id).fetch_one(pool).await
+}
+
+/// Rows whose proposed category was deleted since staging propose `default_category` instead
+pub async fn get_staged_rows(
+    pool: &Pool<Sqlite>,
+    session_id: i64,
+    default_category: &i64,
+) -> Result<Vec<StagedRow>, sqlx::Error> {
+    let query = r#"
+        SELECT
+            row_index,
+            date,
+            name,
+            amount_cents,
+            COALESCE(proposed_category_id, $2) AS proposed_category_id,
+            duplicate,
+            status,
+            reason,
+            external_id,
+            category_hint,
+            splits,
+            import_fingerprint
+        FROM import_staged_row
+        WHERE session_id=$1
+        ORDER BY row_index
+    "#;
+
+    let rows: Vec<StagedRow> = sqlx::query_as(query)
+        .bind(session_id)
+        .bind(default_category)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn delete_import_session<'e>(
+    executor: impl SqliteExecutor<'e>,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM import_session WHERE id=?")
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// Drops sessions that were never committed, returning how many were removed
+pub async fn delete_expired_import_sessions(pool: &Pool<Sqlite>) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM import_session WHERE expires_at <= datetime('now')")
+        .execute(pool)
+        .await?;
+
+    Ok(res.rows_affected())
+}
+
+pub async fn create_import_batch(
+    conn: &mut SqliteConnection,
+    account_id: i64,
+    importer_id: &str,
+    file_path: &str,
+    file_hash: &str,
+) -> Result<i64, sqlx::Error> {
+    let query = r#"
+        INSERT INTO import_batch (account_id, importer_id, file_name, file_hash)
+        VALUES (?, ?, ?, ?)
+        RETURNING id
+    "#;
+
+    sqlx::query_scalar(query)
+        .bind(account_id)
+        .bind(importer_id)
+        .bind(file_name(file_path))
+        .bind(file_hash)
+        .fetch_one(conn)
+        .await
+}
+
+pub async fn set_import_batch_row_count(
+    conn: &mut SqliteConnection,
+    id: i64,
+    row_count: u64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE import_batch SET row_count=? WHERE id=?")
+        .bind(row_count as i64)
+        .bind(id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Past imports, newest first, optionally only those into one account
+pub async fn get_import_batches(
+    pool: &Pool<Sqlite>,
+    account_id: Option<i64>,
+) -> Result<Vec<ImportBatch>, sqlx::Error> {
+    let query = r#"
+        SELECT
+            b.id,
+            b.account_id,
+            a.name AS account_name,
+            b.importer_id,
+            b.file_name,
+            b.file_hash,
+            b.row_count,
+            b.imported_at,
+            b.rolled_back_at
+        FROM import_batch b
+        INNER JOIN account a ON b.account_id = a.id
+        WHERE ? IS NULL OR b.account_id = ?
+        ORDER BY b.imported_at DESC, b.id DESC
+    "#;
+
+    let batches: Vec<ImportBatch> = sqlx::query_as(query)
+        .bind(account_id)
+        .bind(account_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(batches)
+}
+
+/*
+Undoes an import by soft-deleting every transaction it added
+
+Fingerprints are cleared on the deleted rows so the same statement can be imported again.
+Fails with `RowNotFound` if the batch doesn't exist or was already rolled back. Returns the
+number of transactions removed
+*/
+pub async fn rollback_import_batch(pool: &Pool<Sqlite>, id: i64) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let res = sqlx::query(
+        "UPDATE import_batch SET rolled_back_at = datetime('now') WHERE id=? AND rolled_back_at IS NULL",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    let query = r#"
+        UPDATE "transaction"
+        SET deleted_at=date('now'), import_fingerprint=NULL
+        WHERE import_batch_id=? AND deleted_at IS NULL
+    "#;
+    let res = sqlx::query(query).bind(id).execute(&mut *tx).await?;
+
+    tx.commit().await?;
+
+    Ok(res.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::types::{RejectedRow, TransactionImport};
+    use crate::transactions;
+    use crate::transactions::types::TransactionFilter;
+    use rust_decimal::dec;
+
+    fn single_column_mapping() -> ColumnMapping {
+        ColumnMapping {
+            delimiter: ',',
+            skip_rows: 1,
+            date_column: 0,
+            date_format: "%m/%d/%Y".to_owned(),
+            amount: AmountColumns::Single {
+                column: 3,
+                sign_convention: SignConvention::DebitsPositive,
+            },
+            description_columns: vec![1, 2],
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_round_trips_mapping(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let id = create_import_profile(&pool, "Credit Union", &single_column_mapping()).await?;
+
+        let profiles = get_import_profiles(&pool).await?;
+        assert_eq!(
+            profiles,
+            vec![ImportProfile {
+                id,
+                name: "Credit Union".to_owned(),
+                mapping: single_column_mapping(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn update_switches_to_split_amount_columns(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let id = create_import_profile(&pool, "Credit Union", &single_column_mapping()).await?;
+
+        let split = ColumnMapping {
+            delimiter: ';',
+            amount: AmountColumns::Split {
+                debit_column: 3,
+                credit_column: 4,
+            },
+            ..single_column_mapping()
+        };
+        update_import_profile(&pool, id, "Local Bank", &split).await?;
+
+        let profiles = get_import_profiles(&pool).await?;
+        assert_eq!(profiles[0].name, "Local Bank");
+        assert_eq!(profiles[0].mapping, split);
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn delete_removes_profile(pool: Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+        let id = create_import_profile(&pool, "Credit Union", &single_column_mapping()).await?;
+
+        delete_import_profile(&pool, id).await?;
+
+        assert!(get_import_profiles(&pool).await?.is_empty());
+        assert!(matches!(
+            delete_import_profile(&pool, id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+
+    fn file(path: &str) -> StatementFile {
+        StatementFile {
+            path: path.to_owned(),
+            contents: path.as_bytes().to_vec(),
+        }
+    }
+
+    fn statement() -> ParseReport {
+        let txn = |name: &str, amount, day| TransactionImport {
+            date: NaiveDate::from_ymd_opt(2025, 12, day).unwrap(),
+            name: name.to_owned(),
+            amount,
+            ..Default::default()
+        };
+
+        ParseReport {
+            transactions: vec![
+                txn("TST*PIZZA", dec!(-10.14), 12),
+                TransactionImport {
+                    category: Some("Groceries".to_owned()),
+                    ..txn("FOOD MARKET #111", dec!(-7.25), 13)
+                },
+            ],
+            rejected: vec![RejectedRow {
+                line: 4,
+                raw: "12/40/2025,-1.00,*,,BAD DATE".to_owned(),
+                reason: "invalid date '12/40/2025'".to_owned(),
+            }],
+            statement_balance: None,
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn staged_rows_flag_duplicates_and_invalid_lines(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let pizza = statement().transactions[0].clone();
+        transactions::queries::add_imported_transactions(&mut conn, 2, vec![pizza], &1, None)
+            .await?;
+
+        let session_id =
+            create_import_session(&mut conn, 2, "wells_fargo", &file("statement.csv"), statement(), 1)
+                .await?;
+
+        let session = get_import_session(&pool, session_id).await?;
+        assert_eq!(session.account_id, 2);
+        assert!(session.expires_at > session.created_at);
+
+        let rows = get_staged_rows(&pool, session_id, &1).await?;
+        let summary: Vec<(i64, &str, bool, RowStatus, i64)> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.row_index,
+                    r.name.as_str(),
+                    r.duplicate,
+                    r.status,
+                    r.proposed_category_id,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, "TST*PIZZA", true, RowStatus::Valid, 1),
+                (1, "FOOD MARKET #111", false, RowStatus::Valid, 4),
+                (2, "12/40/2025,-1.00,*,,BAD DATE", false, RowStatus::Invalid, 1),
+            ]
+        );
+        assert_eq!(
+            rows[2].reason.as_deref(),
+            Some("line 4: invalid date '12/40/2025'")
+        );
+        assert!(rows[2].to_imported_row(1).is_err());
+        assert_eq!(rows[1].to_imported_row(5)?.category_id, 5);
+
+        // Deleting a proposed category keeps the row, proposing the default category instead
+        categories::queries::delete_category(&pool, 4).await?;
+        let rows = get_staged_rows(&pool, session_id, &1).await?;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].proposed_category_id, 1);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn staging_rejects_unknown_account(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let result =
+            create_import_session(&mut conn, 999, "wells_fargo", &file("statement.csv"), statement(), 1)
+                .await;
+
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn expired_sessions_are_deleted_with_their_rows(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let stale =
+            create_import_session(&mut conn, 2, "wells_fargo", &file("old.csv"), statement(), 1).await?;
+        let fresh =
+            create_import_session(&mut conn, 2, "wells_fargo", &file("new.csv"), statement(), 1).await?;
+        sqlx::query("UPDATE import_session SET expires_at = datetime('now', '-1 minute') WHERE id=?")
+            .bind(stale)
+            .execute(&pool)
+            .await?;
+
+        assert!(matches!(
+            get_import_session(&pool, stale).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        assert_eq!(delete_expired_import_sessions(&pool).await?, 1);
+
+        assert!(get_staged_rows(&pool, stale, &1).await?.is_empty());
+        assert_eq!(get_staged_rows(&pool, fresh, &1).await?.len(), 3);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn rollback_removes_batch_rows_and_allows_reimport(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let statement_file = file("/home/user/Downloads/december.csv");
+        let batch_id = create_import_batch(
+            &mut conn,
+            2,
+            "wells_fargo",
+            &statement_file.path,
+            &statement_file.hash(),
+        )
+        .await?;
+        let summary = transactions::queries::add_imported_transactions(
+            &mut conn,
+            2,
+            statement().transactions,
+            &1,
+            Some(batch_id),
+        )
+        .await?;
+        set_import_batch_row_count(&mut conn, batch_id, summary.inserted).await?;
+
+        let batches = get_import_batches(&pool, Some(2)).await?;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].file_name, "december.csv");
+        assert_eq!(batches[0].account_name, "Manual Savings");
+        assert_eq!(batches[0].row_count, 2);
+        assert!(batches[0].rolled_back_at.is_none());
+        assert!(get_import_batches(&pool, Some(1)).await?.is_empty());
+
+        assert_eq!(rollback_import_batch(&pool, batch_id).await?, 2);
+        let everything = TransactionFilter::default();
+        assert_eq!(transactions::queries::get_num_transactions(&pool, &everything).await?, 0);
+        assert!(get_import_batches(&pool, None).await?[0].rolled_back_at.is_some());
+        assert!(matches!(
+            rollback_import_batch(&pool, batch_id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+
+        // The same statement can be imported again once its batch is rolled back
+        let reimported = transactions::queries::add_imported_transactions(
+            &mut conn,
+            2,
+            statement().transactions,
+            &1,
+            None,
+        )
+        .await?;
+        assert_eq!(reimported.inserted, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::importers::american_express::AmericanExpressImporter;
+use crate::importers::bank_of_america::BankOfAmericaImporter;
+use crate::importers::ofx::OfxImporter;
+use crate::importers::qif::QifImporter;
+use crate::importers::types::ParseReport;
+use crate::importers::wells_fargo::WellsFargoImporter;
+
+/// A statement format that can be recognised and parsed into transactions
+pub trait Importer: Send + Sync {
+    /// Stable identifier the frontend passes back when importing
+    fn id(&self) -> &str;
+
+    fn display_name(&self) -> &str;
+
+    /// Confidence between 0 and 1 that `contents` is a statement in this format
+    fn sniff(&self, contents: &[u8]) -> f32;
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String>;
+}
+
+// Sniff scores below this are treated as "not this format"
+const MIN_CONFIDENCE: f32 = 0.5;
+// Candidates scoring within this margin of the best one are too close to call
+const AMBIGUITY_MARGIN: f32 = 0.1;
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
+pub struct ImporterInfo {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct Detection {
+    pub importer_id: String,
+    pub display_name: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DetectionError {
+    Unreadable { message: String },
+    NoMatch,
+    Ambiguous { candidates: Vec<Detection> },
+}
+
+#[derive(Default)]
+pub struct ImporterRegistry {
+    importers: Vec<Box<dyn Importer>>,
+}
+
+impl ImporterRegistry {
+    pub fn new() -> Self {
+        ImporterRegistry::default()
+    }
+
+    /// Registry holding every importer that ships with the app
+    pub fn with_builtin() -> Self {
+        let mut registry = ImporterRegistry::new();
+        registry.register(Box::new(BankOfAmericaImporter));
+        registry.register(Box::new(WellsFargoImporter));
+        registry.register(Box::new(AmericanExpressImporter));
+        registry.register(Box::new(OfxImporter));
+        registry.register(Box::new(QifImporter));
+        registry
+    }
+
+    pub fn register(&mut self, importer: Box<dyn Importer>) {
+        self.importers.push(importer);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Importer> {
+        self.importers
+            .iter()
+            .find(|importer| importer.id() == id)
+            .map(|importer| importer.as_ref())
+    }
+
+    /*
+    Picks the importer whose sniff is most confident about `contents`
+
+    Fails when nothing clears the minimum confidence, or when the runner-up is too close to
+    the best match to choose between them
+    */
+    pub fn detect(&self, contents: &[u8]) -> Result<Detection, DetectionError> {
+        let mut candidates: Vec<Detection> = self
+            .importers
+            .iter()
+            .map(|importer| Detection {
+                importer_id: importer.id().to_owned(),
+                display_name: importer.display_name().to_owned(),
+                confidence: importer.sniff(contents),
+            })
+            .filter(|detection| detection.confidence >= MIN_CONFIDENCE)
+            .collect();
+        candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+        let Some(best_confidence) = candidates.first().map(|c| c.confidence) else {
+            return Err(DetectionError::NoMatch);
+        };
+        candidates.retain(|c| best_confidence - c.confidence < AMBIGUITY_MARGIN);
+
+        if candidates.len() > 1 {
+            return Err(DetectionError::Ambiguous { candidates });
+        }
+        Ok(candidates.remove(0))
+    }
+
+    pub fn list(&self) -> Vec<ImporterInfo> {
+        self.importers
+            .iter()
+            .map(|importer| ImporterInfo {
+                id: importer.id().to_owned(),
+                display_name: importer.display_name().to_owned(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_registry_lists_all_banks() {
+        let ids: Vec<String> = ImporterRegistry::with_builtin()
+            .list()
+            .into_iter()
+            .map(|info| info.id)
+            .collect();
+
+        assert_eq!(
+            ids,
+            vec!["bank_of_america", "wells_fargo", "american_express", "ofx", "qif"]
+        );
+    }
+
+    #[test]
+    fn get_finds_importer_by_id() {
+        let registry = ImporterRegistry::with_builtin();
+
+        assert_eq!(
+            registry.get("wells_fargo").map(|i| i.display_name()),
+            Some("Wells Fargo")
+        );
+        assert!(registry.get("Wells Fargo").is_none());
+    }
+
+    struct FixedImporter(&'static str, f32);
+
+    impl Importer for FixedImporter {
+        fn id(&self) -> &str {
+            self.0
+        }
+
+        fn display_name(&self) -> &str {
+            self.0
+        }
+
+        fn sniff(&self, _contents: &[u8]) -> f32 {
+            self.1
+        }
+
+        fn parse(&self, _contents: &[u8]) -> Result<ParseReport, String> {
+            Ok(ParseReport::default())
+        }
+    }
+
+    #[test]
+    fn detect_recognises_each_bank() {
+        let registry = ImporterRegistry::with_builtin();
+
+        let detected = |contents: &[u8]| registry.detect(contents).map(|d| d.importer_id);
+        assert_eq!(
+            detected(include_bytes!("../../../data/bankofamericatransactions.csv")),
+            Ok("bank_of_america".to_owned())
+        );
+        assert_eq!(
+            detected(include_bytes!("../../../data/wellsfargotransactions.csv")),
+            Ok("wells_fargo".to_owned())
+        );
+        assert_eq!(
+            detected(include_bytes!("../../../data/americanexpresstransactions.csv")),
+            Ok("american_express".to_owned())
+        );
+        assert_eq!(
+            detected(include_bytes!("../../../data/ofxtransactions.qfx")),
+            Ok("ofx".to_owned())
+        );
+        assert_eq!(
+            detected(include_bytes!("../../../data/qiftransactions.qif")),
+            Ok("qif".to_owned())
+        );
+    }
+
+    #[test]
+    fn detect_rejects_unknown_formats() {
+        let registry = ImporterRegistry::with_builtin();
+
+        assert_eq!(
+            registry.detect(b"Posted,Payee,Debit,Credit\n2025-12-01,Coffee,4.50,\n"),
+            Err(DetectionError::NoMatch)
+        );
+        assert_eq!(registry.detect(b""), Err(DetectionError::NoMatch));
+    }
+
+    #[test]
+    fn detect_reports_close_candidates_as_ambiguous() {
+        let mut registry = ImporterRegistry::new();
+        registry.register(Box::new(FixedImporter("first", 0.8)));
+        registry.register(Box::new(FixedImporter("second", 0.75)));
+        registry.register(Box::new(FixedImporter("unlikely", 0.2)));
+
+        let Err(DetectionError::Ambiguous { candidates }) = registry.detect(b"") else {
+            panic!("close scores should be ambiguous");
+        };
+        let ids: Vec<&str> = candidates.iter().map(|c| c.importer_id.as_str()).collect();
+        assert_eq!(ids, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn detect_picks_clear_winner() {
+        let mut registry = ImporterRegistry::new();
+        registry.register(Box::new(FixedImporter("weak", 0.55)));
+        registry.register(Box::new(FixedImporter("strong", 0.9)));
+
+        let detection = registry.detect(b"").expect("strong should win");
+        assert_eq!(detection.importer_id, "strong");
+        assert_eq!(detection.confidence, 0.9);
+    }
+
+    #[test]
+    fn sniff_prefers_matching_importer() {
+        let registry = ImporterRegistry::with_builtin();
+        let statements: [(&str, &[u8]); 5] = [
+            (
+                "bank_of_america",
+                include_bytes!("../../../data/bankofamericatransactions.csv"),
+            ),
+            (
+                "wells_fargo",
+                include_bytes!("../../../data/wellsfargotransactions.csv"),
+            ),
+            (
+                "american_express",
+                include_bytes!("../../../data/americanexpresstransactions.csv"),
+            ),
+            ("ofx", include_bytes!("../../../data/ofxtransactions.qfx")),
+            ("qif", include_bytes!("../../../data/qiftransactions.qif")),
+        ];
+
+        for (expected, contents) in statements {
+            for info in registry.list() {
+                let confidence = registry.get(&info.id).unwrap().sniff(contents);
+                if info.id == expected {
+                    assert!(confidence >= 0.5, "{expected} should recognise its own file");
+                } else {
+                    assert!(confidence < 0.5, "{} should not claim {expected}", info.id);
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,424 @@
-use chrono::NaiveDate;
+use crate::rules::types::{RuleCandidate, RuleSet};
+use crate::suggestions::types::CategoryModel;
+use crate::transactions::types::SplitLine;
+use crate::types::{Account, AccountType, Cents};
+use chrono::{NaiveDate, NaiveDateTime};
+use csv::{Position, StringRecord};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Default, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
 pub struct TransactionImport {
     #[serde(deserialize_with = "de_date_from_str")]
     pub date: NaiveDate,
     pub name: String,
     #[serde(deserialize_with = "de_decimal_from_money_str")]
     pub amount: Decimal,
+    /// Id the bank assigned to the transaction (OFX FITID), when the format carries one
+    #[serde(skip_deserializing)]
+    pub external_id: Option<String>,
+    /// Category name from the source file, matched against existing categories on import
+    #[serde(skip_deserializing)]
+    pub category: Option<String>,
+    #[serde(skip_deserializing)]
+    pub splits: Vec<ImportSplit>,
+}
+
+/// One part of a transaction the source file divided across several categories
+#[derive(Debug, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ImportSplit {
+    pub category: Option<String>,
+    pub memo: Option<String>,
+    pub amount: Decimal,
+}
+
+impl TransactionImport {
+    /// The file's split lines, with categories matched by name or else `category_id`
+    pub fn split_lines(
+        &self,
+        category_id: i64,
+        category_ids: &HashMap<String, i64>,
+    ) -> Vec<SplitLine> {
+        self.splits
+            .iter()
+            .map(|split| SplitLine {
+                category_id: split
+                    .category
+                    .as_deref()
+                    .and_then(|name| match_category(name, category_ids))
+                    .unwrap_or(category_id),
+                amount_cents: Cents(split.amount).to_cents(),
+                memo: split.memo.clone(),
+            })
+            .collect()
+    }
+
+    // Split transactions are filed under the category holding the largest share
+    pub fn category_hint(&self) -> Option<&str> {
+        self.category.as_deref().or_else(|| {
+            self.splits
+                .iter()
+                .max_by_key(|split| split.amount.abs())
+                .and_then(|split| split.category.as_deref())
+        })
+    }
+}
+
+/*
+Picks the category for imported rows
+
+The category named by the file wins when it exists, then the user's categorization rules,
+then a confident suggestion learned from past corrections. Rows that none of them can place
+are left for the caller's default
+*/
+pub struct CategoryResolver {
+    category_ids: HashMap<String, i64>,
+    rules: RuleSet,
+    model: CategoryModel,
+}
+
+impl CategoryResolver {
+    pub fn new(category_ids: HashMap<String, i64>, rules: RuleSet, model: CategoryModel) -> Self {
+        CategoryResolver {
+            category_ids,
+            rules,
+            model,
+        }
+    }
+
+    pub fn resolve(&self, transaction: &TransactionImport, account_id: i64) -> Option<i64> {
+        transaction
+            .category_hint()
+            .and_then(|hint| match_category(hint, &self.category_ids))
+            .or_else(|| {
+                self.rules.categorize(&RuleCandidate {
+                    name: &transaction.name,
+                    merchant_entity_id: None,
+                    account_id,
+                    amount: transaction.amount,
+                })
+            })
+            .or_else(|| self.model.auto_category(&transaction.name, None))
+    }
+}
+
+/*
+Finds the category named by an imported row in a map of lowercased category names, ignoring case
+
+Nested names like `Food:Groceries` fall back to their most specific part that exists, so
+`Groceries` is tried before `Food`
+*/
+pub fn match_category(name: &str, category_ids: &HashMap<String, i64>) -> Option<i64> {
+    let name = name.to_lowercase();
+    if let Some(id) = category_ids.get(&name) {
+        return Some(*id);
+    }
+
+    name.rsplit(':')
+        .find_map(|part| category_ids.get(part.trim()))
+        .copied()
+}
+
+/// A transaction ready to be written, with the identity and category it will be stored under
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportedRow {
+    pub transaction: TransactionImport,
+    pub fingerprint: String,
+    pub category_id: i64,
+}
+
+/// A statement line the parser could not turn into a transaction
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
+pub struct RejectedRow {
+    pub line: u64,
+    pub raw: String,
+    pub reason: String,
+}
+
+/// Everything a parser got out of a statement, including the lines it had to reject
+#[derive(Debug, Default, PartialEq, Clone, serde::Serialize)]
+pub struct ParseReport {
+    pub transactions: Vec<TransactionImport>,
+    pub rejected: Vec<RejectedRow>,
+    /// Closing balance printed on the statement, for formats that include one
+    pub statement_balance: Option<StatementBalance>,
+}
+
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct StatementBalance {
+    pub amount: Decimal,
+    pub as_of: NaiveDate,
+}
+
+impl ParseReport {
+    /*
+    Deserializes each record into a transaction, recording the ones that fail
+
+    `contents` is the statement the records were read from and is used to recover the raw
+    line of rejected records
+    */
+    pub fn from_records<I>(contents: &[u8], records: I, headers: Option<&StringRecord>) -> Self
+    where
+        I: Iterator<Item = Result<StringRecord, csv::Error>>,
+    {
+        Self::from_records_with(contents, records, |record| {
+            record
+                .deserialize::<TransactionImport>(headers)
+                .map_err(|e| rejection_reason(&e))
+        })
+    }
+
+    /// Like `from_records` but with a caller-supplied conversion from record to transaction
+    pub fn from_records_with<I, F>(contents: &[u8], records: I, mut parse: F) -> Self
+    where
+        I: Iterator<Item = Result<StringRecord, csv::Error>>,
+        F: FnMut(&StringRecord) -> Result<TransactionImport, String>,
+    {
+        let mut report = ParseReport::default();
+
+        for record in records {
+            let (position, parsed) = match record {
+                Ok(r) => (r.position().cloned(), parse(&r)),
+                Err(e) => (e.position().cloned(), Err(rejection_reason(&e))),
+            };
+
+            match parsed {
+                Ok(transaction) => report.transactions.push(transaction),
+                Err(reason) => {
+                    let (line, raw) = locate_line(contents, position.as_ref());
+                    report.rejected.push(RejectedRow { line, raw, reason })
+                }
+            }
+        }
+
+        report
+    }
+}
+
+// Line numbers are counted from the byte offset since the csv reader's own line count
+// drifts on CRLF files and blank lines
+fn locate_line(contents: &[u8], position: Option<&Position>) -> (u64, String) {
+    let Some(position) = position else {
+        return (0, String::new());
+    };
+    // Records can start on the line terminator left over from the previous record
+    let mut start = (position.byte() as usize).min(contents.len());
+    while start < contents.len() && matches!(contents[start], b'\r' | b'\n') {
+        start += 1;
+    }
+    let line = contents[..start].iter().filter(|b| **b == b'\n').count() as u64 + 1;
+    let raw = contents[start..].split(|b| *b == b'\n').next().unwrap_or_default();
+
+    (
+        line,
+        String::from_utf8_lossy(raw).trim_end_matches('\r').to_string(),
+    )
+}
+
+// Deserialize errors already name the offending field, the csv wrapper only adds noise
+fn rejection_reason(error: &csv::Error) -> String {
+    match error.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+        _ => error.to_string(),
+    }
+}
+
+/// The first `n` records of a statement, read without assuming a header or fixed width
+pub fn leading_records(contents: &[u8], n: usize) -> Vec<StringRecord> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(contents)
+        .records()
+        .take(n)
+        .filter_map(|record| record.ok())
+        .collect()
+}
+
+/// Outcome of persisting a parsed statement into an account
+#[derive(Debug, Default, PartialEq, Clone, serde::Serialize)]
+pub struct ImportSummary {
+    pub inserted: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    /// Rows written without their file's splits, as the splits didn't add up to the amount
+    pub unbalanced_splits: u64,
+    pub rejected: Vec<RejectedRow>,
+    pub reconciliation: Option<Reconciliation>,
+}
+
+/// The statement's closing balance next to the balance the app has for the account
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct Reconciliation {
+    pub statement_balance: Cents,
+    pub as_of: NaiveDate,
+    pub account_balance: Cents,
+    /// Statement minus account, zero when the two agree
+    pub difference: Cents,
+}
+
+impl Reconciliation {
+    pub fn new(statement: &StatementBalance, account: &Account) -> Self {
+        // Card statements report the amount owed as negative, the account stores it as positive
+        let statement_amount = match account.account_type {
+            AccountType::Credit => -statement.amount,
+            _ => statement.amount,
+        };
+
+        Reconciliation {
+            statement_balance: Cents(statement_amount),
+            as_of: statement.as_of,
+            account_balance: account.current_balance,
+            difference: Cents(statement_amount - account.current_balance.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum RowStatus {
+    Valid,
+    /// The parser rejected the line, `reason` says why
+    Invalid,
+}
+
+/// A statement line held in an import session until the user accepts or drops it
+#[derive(Debug, PartialEq, Clone, sqlx::FromRow, serde::Serialize)]
+pub struct StagedRow {
+    pub row_index: i64,
+    pub date: Option<NaiveDate>,
+    /// The raw line for rows that could not be parsed
+    pub name: String,
+    #[sqlx(rename = "amount_cents")]
+    pub amount: Option<Cents>,
+    pub proposed_category_id: i64,
+    /// Already imported into the account, committing it again is a no-op
+    pub duplicate: bool,
+    pub status: RowStatus,
+    pub reason: Option<String>,
+    #[serde(skip)]
+    pub external_id: Option<String>,
+    #[serde(skip)]
+    pub category_hint: Option<String>,
+    #[serde(skip)]
+    pub splits: String,
+    #[serde(skip)]
+    pub import_fingerprint: Option<String>,
+}
+
+impl StagedRow {
+    /// The row as it will be written to the ledger, if it was parsed successfully
+    pub fn to_imported_row(&self, category_id: i64) -> Result<ImportedRow, String> {
+        let invalid = || format!("Row {} could not be parsed", self.row_index);
+        if self.status == RowStatus::Invalid {
+            return Err(invalid());
+        }
+        let (Some(date), Some(amount), Some(fingerprint)) =
+            (self.date, self.amount, self.import_fingerprint.clone())
+        else {
+            return Err(invalid());
+        };
+
+        Ok(ImportedRow {
+            transaction: TransactionImport {
+                date,
+                name: self.name.clone(),
+                amount: amount.0,
+                external_id: self.external_id.clone(),
+                category: self.category_hint.clone(),
+                splits: serde_json::from_str(&self.splits).map_err(|e| e.to_string())?,
+            },
+            fingerprint,
+            category_id,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, sqlx::FromRow, serde::Serialize)]
+pub struct ImportSession {
+    pub id: i64,
+    pub account_id: i64,
+    pub importer_id: String,
+    pub file_path: String,
+    pub file_hash: String,
+    #[sqlx(rename = "statement_balance_cents")]
+    pub statement_balance: Option<Cents>,
+    pub statement_balance_as_of: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+impl ImportSession {
+    pub fn statement_balance(&self) -> Option<StatementBalance> {
+        Some(StatementBalance {
+            amount: self.statement_balance?.0,
+            as_of: self.statement_balance_as_of?,
+        })
+    }
+}
+
+/// A statement file read from disk
+pub struct StatementFile {
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+impl StatementFile {
+    pub fn read(path: &str) -> Result<Self, String> {
+        let contents = fs::read(path).map_err(|e| format!("Error reading {path}: {e}"))?;
+        Ok(StatementFile {
+            path: path.to_owned(),
+            contents,
+        })
+    }
+
+    /// SHA-256 of the contents, hex encoded
+    pub fn hash(&self) -> String {
+        format!("{:x}", Sha256::digest(&self.contents))
+    }
+}
+
+/// The file name without its directory, which is what import history shows
+pub fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or(path.to_owned())
+}
+
+/// A statement that was written to an account, kept so the import can be undone
+#[derive(Debug, PartialEq, Clone, sqlx::FromRow, serde::Serialize)]
+pub struct ImportBatch {
+    pub id: i64,
+    pub account_id: i64,
+    pub account_name: String,
+    pub importer_id: String,
+    pub file_name: String,
+    pub file_hash: String,
+    /// Transactions the import added, duplicates it skipped are not counted
+    pub row_count: i64,
+    pub imported_at: NaiveDateTime,
+    pub rolled_back_at: Option<NaiveDateTime>,
+}
+
+/// What the first phase of an import returns for the user to review
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct ImportPreview {
+    pub session: ImportSession,
+    pub rows: Vec<StagedRow>,
+}
+
+/// A staged row the user chose to import, optionally refiled under another category
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct AcceptedRow {
+    pub row_index: i64,
+    pub category_id: Option<i64>,
 }
 
 impl fmt::Display for TransactionImport {
@@ -23,20 +431,151 @@ impl fmt::Display for TransactionImport {
     }
 }
 
+// Banks pad and re-case descriptions inconsistently between exports
+fn normalize_description(description: &str) -> String {
+    description
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+/*
+Builds a stable identity for each imported row from its date, amount and description
+
+Identical rows within one statement (e.g. two coffees on the same day) are told apart by
+their occurrence index, so overlapping statements produce the same fingerprints for the
+rows they share. Rows carrying a bank-assigned id use that id instead
+*/
+pub fn import_fingerprints(transactions: &[TransactionImport]) -> Vec<String> {
+    let mut occurrences: HashMap<String, u32> = HashMap::new();
+
+    transactions
+        .iter()
+        .map(|t| {
+            if let Some(external_id) = &t.external_id {
+                return format!("fitid:{external_id}");
+            }
+            let key = format!(
+                "{}|{}|{}",
+                t.date.format("%Y-%m-%d"),
+                (t.amount * Decimal::ONE_HUNDRED).round(),
+                normalize_description(&t.name)
+            );
+            let occurrence = occurrences.entry(key.clone()).or_insert(0);
+            let fingerprint = format!("{key}|{occurrence}");
+            *occurrence += 1;
+            fingerprint
+        })
+        .collect()
+}
+
+pub fn parse_date(s: &str, format: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s.trim(), format).map_err(|e| format!("invalid date '{s}': {e}"))
+}
+
+// Banks often include thousands seperators and currency symbols
+pub fn parse_money(s: &str) -> Result<Decimal, String> {
+    let clean = s.replace(['"', ',', '$'], "").trim().to_string();
+    Decimal::from_str(&clean).map_err(|e| format!("invalid amount '{s}': {e}"))
+}
+
 fn de_date_from_str<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
 where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    NaiveDate::parse_from_str(&s, "%m/%d/%Y").map_err(serde::de::Error::custom)
+    parse_date(&s, "%m/%d/%Y").map_err(serde::de::Error::custom)
 }
 
-// Custom deserializer since banks often include thousands seperators
 fn de_decimal_from_money_str<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
 where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    let clean = s.replace(['"', ','], "").trim().to_string();
-    Decimal::from_str(&clean).map_err(serde::de::Error::custom)
+    parse_money(&s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn txn(name: &str, amount: Decimal, day: u32) -> TransactionImport {
+        TransactionImport {
+            date: NaiveDate::from_ymd_opt(2025, 12, day).unwrap(),
+            name: name.to_owned(),
+            amount,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_whitespace_and_case() {
+        let fingerprints = import_fingerprints(&[
+            txn("UBERBV              CITY           ST", dec!(-6.71), 23),
+            txn("uberbv city st", dec!(-6.71), 24),
+        ]);
+
+        assert_eq!(fingerprints[0], "2025-12-23|-671|UBERBV CITY ST|0");
+        assert_eq!(fingerprints[1], "2025-12-24|-671|UBERBV CITY ST|0");
+    }
+
+    #[test]
+    fn fingerprint_counts_identical_rows() {
+        let fingerprints = import_fingerprints(&[
+            txn("FOOD MARKET #111", dec!(-7.25), 11),
+            txn("FOOD MARKET #111", dec!(-7.25), 11),
+            txn("FOOD MARKET #111", dec!(-7.250), 12),
+        ]);
+
+        assert_eq!(
+            fingerprints,
+            vec![
+                "2025-12-11|-725|FOOD MARKET #111|0",
+                "2025-12-11|-725|FOOD MARKET #111|1",
+                "2025-12-12|-725|FOOD MARKET #111|0",
+            ]
+        );
+    }
+
+    #[test]
+    fn reconciliation_flips_card_statement_balance() {
+        let account = |account_type| {
+            Account::new(
+                1,
+                None,
+                "Card".to_owned(),
+                None,
+                1,
+                account_type,
+                Cents(dec!(0)),
+                Cents(dec!(0)),
+                Cents(dec!(425.10)),
+            )
+        };
+        let statement = StatementBalance {
+            amount: dec!(-425.10),
+            as_of: NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        };
+
+        let card = Reconciliation::new(&statement, &account(AccountType::Credit));
+        assert_eq!(card.statement_balance, Cents(dec!(425.10)));
+        assert_eq!(card.difference, Cents(dec!(0)));
+
+        let checking = Reconciliation::new(&statement, &account(AccountType::Checkings));
+        assert_eq!(checking.difference, Cents(dec!(-850.20)));
+    }
+
+    #[test]
+    fn fingerprint_prefers_bank_assigned_id() {
+        let with_id = TransactionImport {
+            external_id: Some("20251211-0001".to_owned()),
+            ..txn("FOOD MARKET #111", dec!(-7.25), 11)
+        };
+
+        let fingerprints = import_fingerprints(&[with_id.clone(), with_id]);
+
+        assert_eq!(fingerprints, vec!["fitid:20251211-0001", "fitid:20251211-0001"]);
+    }
 }
@@ -1,23 +1,65 @@
-use crate::importers::types::TransactionImport;
-use csv::ReaderBuilder;
+use crate::importers::registry::Importer;
+use crate::importers::types::{leading_records, ParseReport};
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, StringRecord};
+use rust_decimal::Decimal;
+use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
-pub fn parse_csv_statement<P: AsRef<Path>>(
-    filename: P,
-) -> Result<Vec<TransactionImport>, std::io::Error> {
+const SNIFF_ROWS: usize = 5;
+
+pub struct WellsFargoImporter;
+
+impl Importer for WellsFargoImporter {
+    fn id(&self) -> &str {
+        "wells_fargo"
+    }
+
+    fn display_name(&self) -> &str {
+        "Wells Fargo"
+    }
+
+    // No header, so look for the date, amount, *, blank, description layout instead
+    fn sniff(&self, contents: &[u8]) -> f32 {
+        let records = leading_records(contents, SNIFF_ROWS);
+        if records.is_empty() || !records.iter().all(is_statement_row) {
+            return 0.0;
+        }
+
+        // Wells Fargo quotes every field, other headerless exports usually don't
+        if contents.first() == Some(&b'"') {
+            0.9
+        } else {
+            0.6
+        }
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<ParseReport, String> {
+        Ok(parse_csv_bytes(contents))
+    }
+}
+
+fn is_statement_row(record: &StringRecord) -> bool {
+    record.len() == 5
+        && NaiveDate::parse_from_str(&record[0], "%m/%d/%Y").is_ok()
+        && Decimal::from_str(&record[1]).is_ok()
+}
+
+pub fn parse_csv_statement<P: AsRef<Path>>(filename: P) -> Result<ParseReport, std::io::Error> {
+    let contents = fs::read(filename)?;
+    Ok(parse_csv_bytes(&contents))
+}
+
+pub fn parse_csv_bytes(contents: &[u8]) -> ParseReport {
     let mut reader = ReaderBuilder::new()
         .has_headers(false)
-        .from_path(filename)?;
+        .from_reader(contents);
 
     let headers =
-        csv::StringRecord::from(vec!["date", "amount", "not_used_1", "not_used_2", "name"]);
-    // TODO: We filter out lines that may have errors, we should handle them explicitly, line 14
-    let transactions = reader
-        .records()
-        .filter_map(|transaction_record| transaction_record.ok()?.deserialize(Some(&headers)).ok())
-        .collect();
-
-    Ok(transactions)
+        StringRecord::from(vec!["date", "amount", "not_used_1", "not_used_2", "name"]);
+
+    ParseReport::from_records(contents, reader.records(), Some(&headers))
 }
 
 #[cfg(test)]
@@ -27,6 +69,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::importers::types::TransactionImport;
 
     #[test]
     fn test_parse_statement() -> Result<(), Box<dyn std::error::Error>> {
@@ -37,57 +80,88 @@ mod tests {
                 date: NaiveDate::from_ymd_opt(2025, 12, 15).unwrap(),
                 amount: dec!(-5.77),
                 name: "SQ *ESPRESSO HOUSE CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 14).unwrap(),
                 amount: dec!(-25.24),
                 name: "SIGNATURE FOOD CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 14).unwrap(),
                 amount: dec!(-15.25),
                 name: "MCDONALD'S F1111 CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 12).unwrap(),
                 amount: dec!(-10.14),
                 name: "TST*PIZZA CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 11).unwrap(),
                 amount: dec!(-19.26),
                 name: "FOOD MARKET #111 CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 11).unwrap(),
                 amount: dec!(-7.25),
                 name: "FOOD MARKET #111 CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 11).unwrap(),
                 amount: dec!(-27.75),
                 name: "SQ *THE FOOD COMPANY".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 10).unwrap(),
                 amount: dec!(-4.52),
                 name: "FOOD MARKET #111 CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 10).unwrap(),
                 amount: dec!(-17.04),
                 name: "SQ *FOOD GRILL CITY STATE".to_string(),
+                ..Default::default()
             },
             TransactionImport {
                 date: NaiveDate::from_ymd_opt(2025, 12, 10).unwrap(),
                 amount: dec!(-3.89),
                 name: "SQ *ESPRESSO HOUSE URBANA IL".to_string(),
+                ..Default::default()
             },
         ];
 
-        let transactions = parse_csv_statement(&transactions_path)?;
+        let report = parse_csv_statement(&transactions_path)?;
 
-        assert_eq!(transactions, transactions_expected);
+        assert_eq!(report.transactions, transactions_expected);
+        assert!(report.rejected.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_parse_reports_rejected_rows() {
+        let contents = b"\"12/15/2025\",\"-5.77\",\"*\",\"\",\"SQ *ESPRESSO HOUSE CITY STATE\"\n\
+\"13/40/2025\",\"-25.24\",\"*\",\"\",\"SIGNATURE FOOD CITY STATE\"\n\
+\"12/14/2025\",\"N/A\",\"*\",\"\",\"MCDONALD'S F1111 CITY STATE\"\n";
+
+        let report = parse_csv_bytes(contents);
+
+        assert_eq!(report.transactions.len(), 1);
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].line, 2);
+        assert_eq!(
+            report.rejected[0].raw,
+            "\"13/40/2025\",\"-25.24\",\"*\",\"\",\"SIGNATURE FOOD CITY STATE\""
+        );
+        assert!(report.rejected[0].reason.contains("invalid date '13/40/2025'"));
+        assert_eq!(report.rejected[1].line, 3);
+        assert!(report.rejected[1].reason.contains("invalid amount 'N/A'"));
+    }
 }
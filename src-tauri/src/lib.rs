@@ -6,6 +6,11 @@ mod importers {
     pub(crate) mod american_express;
     pub(crate) mod bank_of_america;
     pub(crate) mod commands;
+    pub(crate) mod generic;
+    pub(crate) mod ofx;
+    pub(crate) mod qif;
+    pub(crate) mod queries;
+    pub(crate) mod registry;
     pub(crate) mod types;
     pub(crate) mod wells_fargo;
 }
@@ -20,6 +25,7 @@ mod plaid {
 mod transactions {
     pub(crate) mod commands;
     pub(crate) mod queries;
+    pub(crate) mod types;
 }
 mod accounts {
     pub(crate) mod commands;
@@ -33,6 +39,46 @@ mod banks {
     pub(crate) mod commands;
     pub(crate) mod queries;
 }
+mod rules {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod suggestions {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod bulk {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod tags {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod transfers {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod payees {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod merchants {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
+mod recurring {
+    pub(crate) mod commands;
+    pub(crate) mod queries;
+    pub(crate) mod types;
+}
 mod db;
 mod types;
 
@@ -85,6 +131,22 @@ pub fn run() {
             let database = tauri::async_runtime::block_on(db::Database::new(&app_data_dir))
                 .expect("Failed to initialize database");
 
+            /* Drop import sessions abandoned in earlier runs */
+            if let Err(e) = tauri::async_runtime::block_on(
+                importers::queries::delete_expired_import_sessions(&database.pool),
+            ) {
+                eprintln!("Failed to clear expired import sessions: {e}");
+            }
+
+            /* Name payees of rows written before payees existed */
+            if let Err(e) = tauri::async_runtime::block_on(async {
+                let mut conn = database.pool.acquire().await?;
+                payees::queries::normalize_payees(&mut conn, payees::types::PayeeScope::Missing)
+                    .await
+            }) {
+                eprintln!("Failed to name missing payees: {e}");
+            }
+
             app.manage(AppState {
                 db: db::DatabaseState(database.pool),
                 link_token: Mutex::new(None),
@@ -116,17 +178,78 @@ pub fn run() {
             accounts::commands::get_all_accounts,
             accounts::commands::get_accounts_of_item,
             banks::commands::get_linked_institutions,
+            importers::commands::get_importers,
+            importers::commands::detect_statement_format,
             importers::commands::import_transactions,
+            importers::commands::save_imported_transactions,
+            importers::commands::preview_import,
+            importers::commands::commit_import,
+            importers::commands::discard_import,
+            importers::commands::get_import_batches,
+            importers::commands::rollback_import_batch,
+            importers::commands::get_import_profiles,
+            importers::commands::create_import_profile,
+            importers::commands::update_import_profile,
+            importers::commands::delete_import_profile,
+            rules::commands::get_categorization_rules,
+            rules::commands::create_categorization_rule,
+            rules::commands::update_categorization_rule,
+            rules::commands::delete_categorization_rule,
+            rules::commands::apply_categorization_rule,
             plaid::commands::generate_link_token,
             plaid::commands::generate_access_token_from_hosted_link,
             plaid::commands::sync_transactions,
             plaid::commands::add_new_plaid_accounts,
             plaid::commands::get_accounts_of_item_from_plaid,
             plaid::commands::save_plaid_credentials,
+            plaid::commands::get_plaid_category_mappings,
+            plaid::commands::set_plaid_category_mapping,
+            plaid::commands::delete_plaid_category_mapping,
             credentials::commands::save_plaid_client_id,
             credentials::commands::save_plaid_secret,
             transactions::commands::get_paginated_sorted_transactions,
-            transactions::commands::update_transaction_category
+            transactions::commands::get_transactions_page,
+            transactions::commands::update_transaction_category,
+            transactions::commands::get_transaction_splits,
+            transactions::commands::set_transaction_splits,
+            transactions::commands::create_transaction,
+            transactions::commands::update_transaction,
+            transactions::commands::delete_transaction,
+            transactions::commands::restore_transaction,
+            bulk::commands::get_bulk_operations,
+            bulk::commands::bulk_update_transactions,
+            bulk::commands::undo_bulk_operation,
+            tags::commands::get_tags,
+            tags::commands::create_tag,
+            tags::commands::rename_tag,
+            tags::commands::delete_tag,
+            tags::commands::merge_tags,
+            tags::commands::get_transaction_tags,
+            tags::commands::set_transaction_tags,
+            tags::commands::get_tag_spending,
+            transfers::commands::get_transfers,
+            transfers::commands::detect_transfers,
+            transfers::commands::get_transfer_window_days,
+            transfers::commands::set_transfer_window_days,
+            transfers::commands::confirm_transfer,
+            transfers::commands::reject_transfer,
+            transfers::commands::link_transfer,
+            payees::commands::get_payee_rules,
+            payees::commands::create_payee_rule,
+            payees::commands::update_payee_rule,
+            payees::commands::delete_payee_rule,
+            payees::commands::renormalize_payees,
+            merchants::commands::get_merchants,
+            merchants::commands::update_merchant,
+            merchants::commands::merge_merchants,
+            recurring::commands::get_recurring_series,
+            recurring::commands::detect_recurring,
+            recurring::commands::confirm_recurring_series,
+            recurring::commands::dismiss_recurring_series,
+            recurring::commands::update_recurring_series,
+            recurring::commands::get_recurring_alerts,
+            recurring::commands::acknowledge_recurring_alert,
+            suggestions::commands::suggest_transaction_category
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,61 @@
+use crate::merchants::queries;
+use crate::merchants::types::{MerchantDetails, MerchantSummary};
+use crate::AppState;
+use chrono::NaiveDate;
+
+/// Merchants with their lifetime spend and what was spent between `period_from` and `period_to`
+#[tauri::command]
+pub async fn get_merchants(
+    state: tauri::State<'_, AppState>,
+    period_from: NaiveDate,
+    period_to: NaiveDate,
+) -> Result<Vec<MerchantSummary>, String> {
+    queries::get_merchants(&state.db.0, period_from, period_to)
+        .await
+        .map_err(|e| format!("Error fetching merchants: {e}"))
+}
+
+/// Renames a merchant and sets its default category, logo and notes
+#[tauri::command]
+pub async fn update_merchant(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    details: MerchantDetails,
+) -> Result<(), String> {
+    details.validate()?;
+    queries::update_merchant(&state.db.0, id, &details)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!("Merchant {id} doesn't exist"),
+            e => format!("Error updating merchant {id}: {e}"),
+        })
+}
+
+/// Folds one merchant into another, keeping the target's name
+#[tauri::command]
+pub async fn merge_merchants(
+    state: tauri::State<'_, AppState>,
+    source_id: i64,
+    target_id: i64,
+) -> Result<(), String> {
+    if source_id == target_id {
+        return Err("Can't merge a merchant into itself".to_string());
+    }
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    queries::merge_merchants(&mut tx, source_id, target_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Both merchants must exist to merge them".to_string(),
+            e => format!("Failed to merge merchants: {e}"),
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
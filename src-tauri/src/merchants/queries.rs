@@ -0,0 +1,331 @@
+use crate::categories;
+use crate::merchants::types::{merchant_key, MerchantDetails, MerchantKeyKind, MerchantSummary};
+use chrono::NaiveDate;
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+
+pub async fn get_merchants(
+    pool: &Pool<Sqlite>,
+    period_from: NaiveDate,
+    period_to: NaiveDate,
+) -> Result<Vec<MerchantSummary>, sqlx::Error> {
+    let query = r#"
+        SELECT
+            m.id,
+            m.name,
+            m.default_category_id,
+            m.logo_url,
+            m.notes,
+            COUNT(t.id) AS transaction_count,
+            COALESCE(-SUM(t.amount_cents), 0) AS lifetime_spent_cents,
+            COALESCE(-SUM(CASE WHEN t.date >= $1 AND t.date <= $2 THEN t.amount_cents END), 0)
+                AS period_spent_cents
+        FROM merchant m
+        LEFT JOIN "transaction" t ON t.merchant_id = m.id
+            AND t.deleted_at IS NULL
+            AND t.id NOT IN (SELECT transaction_id FROM transfer_transaction)
+        GROUP BY m.id
+        ORDER BY lifetime_spent_cents DESC, m.name
+    "#;
+
+    let merchants: Vec<MerchantSummary> = sqlx::query_as(query)
+        .bind(period_from)
+        .bind(period_to)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(merchants)
+}
+
+pub async fn update_merchant(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    details: &MerchantDetails,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE merchant
+        SET name=$1, default_category_id=$2, logo_url=$3, notes=$4
+        WHERE id=$5
+    "#;
+
+    let res = sqlx::query(query)
+        .bind(details.name.trim())
+        .bind(details.default_category_id)
+        .bind(&details.logo_url)
+        .bind(&details.notes)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/*
+Folds one merchant into another
+
+The target keeps its own details, taking the source's where it has none. Its keys move too, so
+rows that would have joined the source join the target from now on
+*/
+pub async fn merge_merchants(
+    conn: &mut SqliteConnection,
+    source_id: i64,
+    target_id: i64,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE merchant
+        SET default_category_id = COALESCE(merchant.default_category_id, source.default_category_id),
+            logo_url = COALESCE(merchant.logo_url, source.logo_url),
+            notes = COALESCE(merchant.notes, source.notes)
+        FROM merchant source
+        WHERE merchant.id=$1 AND source.id=$2
+    "#;
+    let res = sqlx::query(query)
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    sqlx::query("UPDATE merchant_key SET merchant_id=$1 WHERE merchant_id=$2")
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE 'transaction' SET merchant_id=$1 WHERE merchant_id=$2")
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM merchant WHERE id=?")
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/*
+Groups transactions that aren't under a merchant yet, creating merchants for keys not seen before
+
+Only `ids` are looked at when given. New merchants are named after the payee. A row still in Uncategorized and not split is filed
+under its merchant's default category, as nothing more specific matched it. Returns how many
+transactions were linked
+*/
+pub async fn link_merchants(
+    conn: &mut SqliteConnection,
+    ids: Option<&[i64]>,
+) -> Result<u64, sqlx::Error> {
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT id, merchant_entity_id, COALESCE(payee, name) FROM 'transaction' WHERE merchant_id IS NULL",
+    );
+    if let Some(ids) = ids {
+        query_builder.push(" AND id IN (");
+        let mut separated = query_builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query_builder.push(")");
+    }
+    query_builder.push(" ORDER BY id");
+    let rows: Vec<(i64, Option<String>, String)> =
+        query_builder.build_query_as().fetch_all(&mut *conn).await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let keys: Vec<(MerchantKeyKind, String, i64)> =
+        sqlx::query_as("SELECT kind, value, merchant_id FROM merchant_key")
+            .fetch_all(&mut *conn)
+            .await?;
+    let mut merchant_ids: HashMap<(MerchantKeyKind, String), i64> = keys
+        .into_iter()
+        .map(|(kind, value, merchant_id)| ((kind, value), merchant_id))
+        .collect();
+
+    let uncategorized = categories::queries::get_uncategorized_category(&mut *conn).await?;
+    let link = r#"
+        UPDATE 'transaction'
+        SET merchant_id=$1,
+            category_id = CASE
+                WHEN category_id = $3
+                    AND NOT EXISTS (SELECT 1 FROM transaction_split WHERE transaction_id=$2)
+                THEN COALESCE((SELECT default_category_id FROM merchant WHERE id=$1), category_id)
+                ELSE category_id
+            END
+        WHERE id=$2
+    "#;
+
+    let mut linked = 0;
+    for (id, merchant_entity_id, payee) in rows {
+        let Some(key) = merchant_key(merchant_entity_id.as_deref(), &payee) else {
+            continue;
+        };
+        let merchant_id = match merchant_ids.get(&key) {
+            Some(merchant_id) => *merchant_id,
+            None => {
+                let merchant_id: i64 =
+                    sqlx::query_scalar("INSERT INTO merchant (name) VALUES (?) RETURNING id")
+                        .bind(payee.trim())
+                        .fetch_one(&mut *conn)
+                        .await?;
+                sqlx::query("INSERT INTO merchant_key (kind, value, merchant_id) VALUES (?, ?, ?)")
+                    .bind(key.0)
+                    .bind(&key.1)
+                    .bind(merchant_id)
+                    .execute(&mut *conn)
+                    .await?;
+                merchant_ids.insert(key, merchant_id);
+                merchant_id
+            }
+        };
+
+        let res = sqlx::query(link)
+            .bind(merchant_id)
+            .bind(id)
+            .bind(uncategorized.id())
+            .execute(&mut *conn)
+            .await?;
+        linked += res.rows_affected();
+    }
+
+    Ok(linked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payees;
+    use crate::payees::types::PayeeScope;
+    use crate::transactions;
+    use crate::transactions::types::TransactionFilter;
+
+    fn december(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 12, day).unwrap()
+    }
+
+    async fn merchant_of(pool: &Pool<Sqlite>, id: i64) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT merchant_id FROM 'transaction' WHERE id=?")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn links_by_merchant_id_then_payee(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("UPDATE 'transaction' SET name='SQ *BLUE BOTTLE #12', merchant_entity_id='bb' WHERE id=1")
+            .execute(&pool)
+            .await?;
+        sqlx::query("UPDATE 'transaction' SET name='BLUE BOTTLE COFFEE', merchant_entity_id='bb' WHERE id=2")
+            .execute(&pool)
+            .await?;
+        sqlx::query("UPDATE 'transaction' SET name='CORNER CAFE 0042' WHERE id IN (3, 4)")
+            .execute(&pool)
+            .await?;
+
+        payees::queries::normalize_payees(&mut conn, PayeeScope::Missing).await?;
+
+        assert_eq!(merchant_of(&pool, 1).await?, merchant_of(&pool, 2).await?);
+        assert_eq!(merchant_of(&pool, 3).await?, merchant_of(&pool, 4).await?);
+        assert_ne!(merchant_of(&pool, 1).await?, merchant_of(&pool, 3).await?);
+
+        let merchants = get_merchants(&pool, december(16), december(31)).await?;
+        let summary: Vec<(&str, i64, i64, i64)> = merchants
+            .iter()
+            .map(|m| {
+                (
+                    m.details.name.as_str(),
+                    m.transaction_count,
+                    m.lifetime_spent_cents,
+                    m.period_spent_cents,
+                )
+            })
+            .collect();
+        // Named after the first row seen under each
+        assert_eq!(
+            summary,
+            vec![("Blue Bottle", 2, 1667, 1090), ("Corner Cafe", 2, 260, 260)]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn merchants_are_edited_and_merged(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("UPDATE 'transaction' SET name='TRADER JOES' WHERE id IN (1, 2)")
+            .execute(&pool)
+            .await?;
+        sqlx::query("UPDATE 'transaction' SET name='TRADER JOE''S #552' WHERE id IN (3, 4)")
+            .execute(&pool)
+            .await?;
+        payees::queries::normalize_payees(&mut conn, PayeeScope::Missing).await?;
+        let (source, target) = (
+            merchant_of(&pool, 3).await?.unwrap(),
+            merchant_of(&pool, 1).await?.unwrap(),
+        );
+
+        let details = MerchantDetails {
+            name: " Trader Joe's ".to_owned(),
+            default_category_id: None,
+            logo_url: None,
+            notes: Some("Weekly shop".to_owned()),
+        };
+        update_merchant(&pool, target, &details).await?;
+        let with_groceries = MerchantDetails {
+            default_category_id: Some(4),
+            ..details.clone()
+        };
+        update_merchant(&pool, source, &with_groceries).await?;
+
+        merge_merchants(&mut conn, source, target).await?;
+        let merchants = get_merchants(&pool, december(1), december(31)).await?;
+        assert_eq!(merchants.len(), 1);
+        assert_eq!(
+            merchants[0].details,
+            MerchantDetails {
+                name: "Trader Joe's".to_owned(),
+                default_category_id: Some(4),
+                ..details
+            }
+        );
+        assert_eq!(merchants[0].transaction_count, 4);
+
+        let filter = TransactionFilter {
+            merchant_ids: vec![target],
+            ..Default::default()
+        };
+        assert_eq!(
+            transactions::queries::get_num_transactions(&pool, &filter).await?,
+            4
+        );
+
+        // New rows under the source's payee now join the target, taking its default category
+        sqlx::query(
+            "INSERT INTO 'transaction' (name, amount_cents, date, account_id, category_id) VALUES ('TRADER JOE''S #552', -900, '2025-12-20', 1, 1)",
+        )
+        .execute(&pool)
+        .await?;
+        payees::queries::normalize_payees(&mut conn, PayeeScope::Missing).await?;
+        let (merchant_id, category_id): (Option<i64>, i64) = sqlx::query_as(
+            "SELECT merchant_id, category_id FROM 'transaction' ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!((merchant_id, category_id), (Some(target), 4));
+
+        assert!(matches!(
+            merge_merchants(&mut conn, source, target).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, serde::Serialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum MerchantKeyKind {
+    /// Plaid's merchant_entity_id
+    Entity,
+    /// The lowercased payee, for rows Plaid didn't identify such as imported ones
+    Payee,
+}
+
+/*
+The key a transaction is grouped under a merchant by
+
+Plaid's merchant id is preferred since it stays the same however the description is written.
+Rows without either have no merchant
+*/
+pub fn merchant_key(
+    merchant_entity_id: Option<&str>,
+    payee: &str,
+) -> Option<(MerchantKeyKind, String)> {
+    match merchant_entity_id.filter(|id| !id.is_empty()) {
+        Some(id) => Some((MerchantKeyKind::Entity, id.to_owned())),
+        None => {
+            let payee = payee.trim().to_lowercase();
+            (!payee.is_empty()).then_some((MerchantKeyKind::Payee, payee))
+        }
+    }
+}
+
+/// The parts of a merchant the user edits
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct MerchantDetails {
+    pub name: String,
+    pub default_category_id: Option<i64>,
+    pub logo_url: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl MerchantDetails {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Merchant name is required".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A merchant with what was spent there, net of refunds and leaving out transfers
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct MerchantSummary {
+    pub id: i64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub details: MerchantDetails,
+    pub transaction_count: i64,
+    pub lifetime_spent_cents: i64,
+    pub period_spent_cents: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merchant_id_is_preferred_over_payee() {
+        assert_eq!(
+            merchant_key(Some("merchant-1"), "Blue Bottle"),
+            Some((MerchantKeyKind::Entity, "merchant-1".to_owned()))
+        );
+        assert_eq!(
+            merchant_key(None, " Blue Bottle "),
+            Some((MerchantKeyKind::Payee, "blue bottle".to_owned()))
+        );
+        assert_eq!(
+            merchant_key(Some(""), "Blue Bottle"),
+            Some((MerchantKeyKind::Payee, "blue bottle".to_owned()))
+        );
+        assert_eq!(merchant_key(None, "  "), None);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::payees::queries;
+use crate::payees::types::{PayeeRule, PayeeRuleDefinition, PayeeScope};
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_payee_rules(state: tauri::State<'_, AppState>) -> Result<Vec<PayeeRule>, String> {
+    queries::get_payee_rules(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_payee_rule(
+    state: tauri::State<'_, AppState>,
+    rule: PayeeRuleDefinition,
+) -> Result<i64, String> {
+    rule.validate()?;
+    queries::create_payee_rule(&state.db.0, &rule)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_payee_rule(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    rule: PayeeRuleDefinition,
+) -> Result<(), String> {
+    rule.validate()?;
+    queries::update_payee_rule(&state.db.0, id, &rule)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_payee_rule(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    queries::delete_payee_rule(&state.db.0, id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Recomputes the payee of every transaction, usually after payee rules were changed
+
+Returns how many payees changed
+*/
+#[tauri::command]
+pub async fn renormalize_payees(state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let changed = queries::normalize_payees(&mut tx, PayeeScope::All)
+        .await
+        .map_err(|e| format!("Failed to normalize payees: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(changed)
+}
@@ -0,0 +1,179 @@
+use crate::merchants;
+use crate::payees::types::{PayeeNormalizer, PayeeRule, PayeeRuleDefinition, PayeeScope};
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
+
+pub async fn get_payee_rules(pool: &Pool<Sqlite>) -> Result<Vec<PayeeRule>, sqlx::Error> {
+    let query = "SELECT id, pattern, description_match, payee FROM payee_rule ORDER BY id";
+
+    let rules: Vec<PayeeRule> = sqlx::query_as(query).fetch_all(pool).await?;
+
+    Ok(rules)
+}
+
+pub async fn create_payee_rule(
+    pool: &Pool<Sqlite>,
+    rule: &PayeeRuleDefinition,
+) -> Result<i64, sqlx::Error> {
+    let query = r#"
+        INSERT INTO payee_rule (pattern, description_match, payee)
+        VALUES (?, ?, ?)
+        RETURNING id
+    "#;
+
+    sqlx::query_scalar(query)
+        .bind(&rule.pattern)
+        .bind(rule.description_match)
+        .bind(rule.payee.trim())
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn update_payee_rule(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    rule: &PayeeRuleDefinition,
+) -> Result<(), sqlx::Error> {
+    let res =
+        sqlx::query("UPDATE payee_rule SET pattern=?, description_match=?, payee=? WHERE id=?")
+            .bind(&rule.pattern)
+            .bind(rule.description_match)
+            .bind(rule.payee.trim())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+pub async fn delete_payee_rule(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM payee_rule WHERE id=?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_payee_normalizer(
+    conn: &mut SqliteConnection,
+) -> Result<PayeeNormalizer, sqlx::Error> {
+    let rules: Vec<PayeeRule> =
+        sqlx::query_as("SELECT id, pattern, description_match, payee FROM payee_rule")
+            .fetch_all(conn)
+            .await?;
+
+    Ok(PayeeNormalizer::new(rules))
+}
+
+/*
+Fills in the payee of the transactions in `scope` from their raw name
+
+Writes to transactions pass the ids they wrote, so only those rows are looked at. Rows grouped
+under a merchant by payee are moved to the merchant for their new payee. Returns how many
+payees changed
+*/
+pub async fn normalize_payees(
+    conn: &mut SqliteConnection,
+    scope: PayeeScope<'_>,
+) -> Result<u64, sqlx::Error> {
+    let mut query_builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT id, name FROM 'transaction' WHERE ");
+    match scope {
+        PayeeScope::Ids([]) => return Ok(0),
+        PayeeScope::Ids(ids) => {
+            query_builder.push("id IN (");
+            let mut separated = query_builder.separated(", ");
+            for id in ids {
+                separated.push_bind(*id);
+            }
+            query_builder.push(")");
+        }
+        PayeeScope::Missing => {
+            query_builder.push("payee IS NULL");
+        }
+        PayeeScope::All => {
+            query_builder.push("TRUE");
+        }
+    }
+    let normalizer = get_payee_normalizer(&mut *conn).await?;
+    let rows: Vec<(i64, String)> = query_builder.build_query_as().fetch_all(&mut *conn).await?;
+
+    let query = r#"
+        UPDATE 'transaction'
+        SET payee=$1,
+            merchant_id = CASE WHEN COALESCE(merchant_entity_id, '') = '' THEN NULL ELSE merchant_id END
+        WHERE id=$2 AND payee IS NOT $1
+    "#;
+
+    let mut changed = 0;
+    for (id, name) in rows {
+        let res = sqlx::query(query)
+            .bind(normalizer.normalize(&name))
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        changed += res.rows_affected();
+    }
+    let linked_ids = match scope {
+        PayeeScope::Ids(ids) => Some(ids),
+        PayeeScope::Missing | PayeeScope::All => None,
+    };
+    merchants::queries::link_merchants(conn, linked_ids).await?;
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::types::DescriptionMatch;
+
+    async fn payees(pool: &Pool<Sqlite>) -> Result<Vec<Option<String>>, sqlx::Error> {
+        sqlx::query_scalar("SELECT payee FROM 'transaction' ORDER BY id")
+            .fetch_all(pool)
+            .await
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn renormalizes_existing_transactions_with_user_rules(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("UPDATE 'transaction' SET name='SQ *BLUE BOTTLE #12 OAKLAND CA' WHERE id=1")
+            .execute(&pool)
+            .await?;
+
+        // Only the rows without a payee yet
+        assert_eq!(normalize_payees(&mut conn, PayeeScope::Missing).await?, 4);
+        assert_eq!(payees(&pool).await?[0].as_deref(), Some("Blue Bottle"));
+        assert_eq!(normalize_payees(&mut conn, PayeeScope::Missing).await?, 0);
+
+        // Or only the rows just written
+        sqlx::query("UPDATE 'transaction' SET name='TST* PIZZA PALACE 0042', payee=NULL WHERE id=2")
+            .execute(&pool)
+            .await?;
+        assert_eq!(normalize_payees(&mut conn, PayeeScope::Ids(&[1, 3])).await?, 0);
+        assert_eq!(normalize_payees(&mut conn, PayeeScope::Ids(&[2])).await?, 1);
+        assert_eq!(payees(&pool).await?[1].as_deref(), Some("Pizza Palace"));
+
+        let rule = PayeeRuleDefinition {
+            pattern: "blue bottle".to_owned(),
+            description_match: DescriptionMatch::Contains,
+            payee: "Blue Bottle Coffee".to_owned(),
+        };
+        let id = create_payee_rule(&pool, &rule).await?;
+        assert_eq!(normalize_payees(&mut conn, PayeeScope::All).await?, 1);
+        assert_eq!(
+            payees(&pool).await?[0].as_deref(),
+            Some("Blue Bottle Coffee")
+        );
+
+        delete_payee_rule(&pool, id).await?;
+        assert_eq!(normalize_payees(&mut conn, PayeeScope::All).await?, 1);
+        assert_eq!(payees(&pool).await?[0].as_deref(), Some("Blue Bottle"));
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use crate::rules::types::DescriptionMatch;
+use regex::{Regex, RegexBuilder};
+
+// Card processors and wallets that put their own name in front of the merchant's
+const PROCESSOR_PREFIXES: &str = r"^(sq|tst|sp|py|pp|paypal|google|goog|ckc|sqsp|in|pos)\s*\*\s*|^(aplpay|applepay|apple pay|gpay|google pay)\s+";
+
+// Words banks add that say nothing about the merchant
+const NOISE_WORDS: [&str; 8] = [
+    "purchase",
+    "pos",
+    "debit",
+    "card",
+    "recurring",
+    "payment",
+    "online",
+    "authorized",
+];
+
+const US_STATES: [&str; 51] = [
+    "al", "ak", "az", "ar", "ca", "co", "ct", "de", "dc", "fl", "ga", "hi", "id", "il", "in", "ia",
+    "ks", "ky", "la", "me", "md", "ma", "mi", "mn", "ms", "mo", "mt", "ne", "nv", "nh", "nj", "nm",
+    "ny", "nc", "nd", "oh", "ok", "or", "pa", "ri", "sc", "sd", "tn", "tx", "ut", "vt", "va", "wa",
+    "wv", "wi", "wy",
+];
+
+// First words of multi-word city names, removed along with the rest of the city
+const CITY_PREFIXES: [&str; 12] = [
+    "san", "santa", "los", "las", "new", "saint", "st", "fort", "ft", "el", "palo", "salt",
+];
+
+/// A user rewrite from matching descriptions to a payee name
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct PayeeRuleDefinition {
+    pub pattern: String,
+    pub description_match: DescriptionMatch,
+    /// For regex rules `$1` and `$name` are replaced by the pattern's capture groups
+    pub payee: String,
+}
+
+impl PayeeRuleDefinition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.is_empty() {
+            return Err("Pattern can't be empty".to_string());
+        }
+        if self.payee.trim().is_empty() {
+            return Err("Payee name is required".to_string());
+        }
+        if self.description_match == DescriptionMatch::Regex {
+            compile_pattern(&self.pattern).map_err(|e| format!("Invalid regex: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct PayeeRule {
+    pub id: i64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub definition: PayeeRuleDefinition,
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+/// Which transactions `normalize_payees` looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayeeScope<'a> {
+    /// Rows that were just written
+    Ids(&'a [i64]),
+    /// Rows without a payee yet, such as those from before payees existed
+    Missing,
+    /// Every row, as needed after payee rules change
+    All,
+}
+
+enum PayeeMatcher {
+    Contains(String),
+    Regex(Regex),
+}
+
+/*
+Turns raw bank descriptions into payee names
+
+User rules are tried first, oldest first, and the first match names the payee. Otherwise the
+description is cleaned up step by step: processor prefixes are stripped, then words that are
+mostly digits such as store numbers and embedded dates, then noise words, then a trailing
+city and state. Descriptions that would clean up to nothing keep their raw text
+*/
+pub struct PayeeNormalizer {
+    rules: Vec<(PayeeMatcher, String)>,
+    prefixes: Regex,
+}
+
+impl PayeeNormalizer {
+    pub fn new(mut rules: Vec<PayeeRule>) -> Self {
+        rules.sort_by_key(|rule| rule.id);
+        let rules = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let matcher = match rule.definition.description_match {
+                    DescriptionMatch::Contains => {
+                        PayeeMatcher::Contains(rule.definition.pattern.to_lowercase())
+                    }
+                    DescriptionMatch::Regex => match compile_pattern(&rule.definition.pattern) {
+                        Ok(regex) => PayeeMatcher::Regex(regex),
+                        Err(e) => {
+                            eprintln!("Skipping payee rule {}: invalid regex: {e}", rule.id);
+                            return None;
+                        }
+                    },
+                };
+                Some((matcher, rule.definition.payee))
+            })
+            .collect();
+
+        PayeeNormalizer {
+            rules,
+            prefixes: compile_pattern(PROCESSOR_PREFIXES).expect("built-in pattern compiles"),
+        }
+    }
+
+    pub fn normalize(&self, name: &str) -> String {
+        self.rewrite(name).unwrap_or_else(|| self.clean(name))
+    }
+
+    fn rewrite(&self, name: &str) -> Option<String> {
+        self.rules
+            .iter()
+            .find_map(|(matcher, payee)| match matcher {
+                PayeeMatcher::Contains(needle) => {
+                    name.to_lowercase().contains(needle).then(|| payee.clone())
+                }
+                PayeeMatcher::Regex(regex) => regex.captures(name).map(|captures| {
+                    let mut expanded = String::new();
+                    captures.expand(payee, &mut expanded);
+                    expanded.trim().to_owned()
+                }),
+            })
+    }
+
+    fn clean(&self, name: &str) -> String {
+        let mut cleaned = name.trim();
+        // Wallet payments can wrap a processor, as in "AplPay SQ *CAFE"
+        while let Some(prefix) = self.prefixes.find(cleaned) {
+            cleaned = &cleaned[prefix.end()..];
+        }
+
+        let mut words: Vec<&str> = cleaned
+            .split(|c: char| c.is_whitespace() || c == '*')
+            .filter(|word| !is_mostly_digits(word))
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '&'))
+            .filter(|word| !word.is_empty())
+            .filter(|word| !NOISE_WORDS.contains(&word.to_lowercase().as_str()))
+            .collect();
+        strip_city_and_state(&mut words);
+
+        if words.is_empty() {
+            return name.trim().to_owned();
+        }
+        words
+            .iter()
+            .map(|word| title_case(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// Store numbers like #1234, dates like 11/27 and reference codes, but not "7-Eleven" or "1st"
+fn is_mostly_digits(word: &str) -> bool {
+    let digits = word.chars().filter(char::is_ascii_digit).count();
+    let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+    digits > 0 && digits >= letters
+}
+
+/*
+Drops a trailing US state code and the city before it
+
+Only done when a merchant name would be left over, so a store called "Ca" or a one word
+description ending in a state code survive. Cities are taken to be one word unless they start
+with a common prefix such as "San"
+*/
+fn strip_city_and_state(words: &mut Vec<&str>) {
+    let is_state = words
+        .last()
+        .is_some_and(|word| word.len() == 2 && US_STATES.contains(&word.to_lowercase().as_str()));
+    if !is_state || words.len() < 3 {
+        return;
+    }
+    words.truncate(words.len() - 2);
+
+    if words.len() > 1
+        && words
+            .last()
+            .is_some_and(|word| CITY_PREFIXES.contains(&word.to_lowercase().as_str()))
+    {
+        words.pop();
+    }
+}
+
+// Shouting words are title-cased, words that already mix cases are left as written
+fn title_case(word: &str) -> String {
+    if word.chars().any(char::is_lowercase) {
+        return word.to_owned();
+    }
+    let mut previous = ' ';
+    word.chars()
+        .flat_map(|c| {
+            // Each part of "7-ELEVEN" starts a word, but the s in "JOE'S" doesn't
+            let starts_word = !previous.is_alphanumeric() && previous != '\'';
+            previous = c;
+            if starts_word {
+                c.to_uppercase().collect::<Vec<_>>()
+            } else {
+                c.to_lowercase().collect::<Vec<_>>()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i64, pattern: &str, description_match: DescriptionMatch, payee: &str) -> PayeeRule {
+        PayeeRule {
+            id,
+            definition: PayeeRuleDefinition {
+                pattern: pattern.to_owned(),
+                description_match,
+                payee: payee.to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn cleans_processor_prefixes_dates_numbers_and_locations() {
+        let normalizer = PayeeNormalizer::new(vec![]);
+
+        assert_eq!(
+            normalizer.normalize("SQ *ESPRESSO HOUSE SEATTLE WA"),
+            "Espresso House"
+        );
+        assert_eq!(normalizer.normalize("TST*PIZZA"), "Pizza");
+        assert_eq!(normalizer.normalize("AplPay UMM PLAZA"), "Umm Plaza");
+        assert_eq!(
+            normalizer.normalize("PAYPAL *NEWBALANCEA 11/27 PURCHASE"),
+            "Newbalancea"
+        );
+        assert_eq!(
+            normalizer.normalize("SAFEWAY #1234 SAN FRANCISCO CA"),
+            "Safeway"
+        );
+        assert_eq!(
+            normalizer.normalize("WHOLEFDS MKT 10234 AUSTIN TX 12/01"),
+            "Wholefds Mkt"
+        );
+        assert_eq!(normalizer.normalize("Trader Joe's"), "Trader Joe's");
+        assert_eq!(normalizer.normalize("7-ELEVEN 38127 DALLAS TX"), "7-Eleven");
+    }
+
+    #[test]
+    fn drops_check_numbers() {
+        let normalizer = PayeeNormalizer::new(vec![]);
+
+        assert_eq!(normalizer.normalize("CHECK 1042"), "Check");
+    }
+
+    #[test]
+    fn keeps_raw_text_when_nothing_would_be_left() {
+        let normalizer = PayeeNormalizer::new(vec![]);
+
+        assert_eq!(normalizer.normalize("  12345  "), "12345");
+        assert_eq!(normalizer.normalize("PIZZA CA"), "Pizza Ca");
+    }
+
+    #[test]
+    fn user_rules_win_in_the_order_they_were_added() {
+        let normalizer = PayeeNormalizer::new(vec![
+            rule(2, "amzn", DescriptionMatch::Contains, "Amazon Anything"),
+            rule(1, r"^amzn mktp (\w+)", DescriptionMatch::Regex, "Amazon $1"),
+            rule(3, "(", DescriptionMatch::Regex, "Broken"),
+        ]);
+
+        assert_eq!(normalizer.normalize("AMZN Mktp US*2K3AB1"), "Amazon US");
+        assert_eq!(normalizer.normalize("AMZN DIGITAL"), "Amazon Anything");
+        assert_eq!(normalizer.normalize("TST*PIZZA"), "Pizza");
+    }
+}
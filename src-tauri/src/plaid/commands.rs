@@ -3,8 +3,10 @@ use crate::banks;
 use crate::credentials;
 use crate::categories;
 use crate::plaid;
-use crate::plaid::types::PlaidTransaction;
+use crate::plaid::types::{PlaidCategoryMapping, PlaidTransaction};
+use crate::recurring;
 use crate::transactions;
+use crate::transfers;
 use crate::types::Cents;
 use crate::AppState;
 use ::plaid::{
@@ -16,6 +18,7 @@ use ::plaid::{
     request::link_token_create::LinkTokenCreateRequired,
     PlaidAuth, PlaidClient,
 };
+use chrono::Local;
 use dotenvy_macro::dotenv;
 use httpclient::{Client, InMemoryResponseExt};
 
@@ -117,6 +120,13 @@ pub async fn sync_transactions(
     transactions::queries::remove_plaid_transactions(&mut tx, synced_transactions.removed)
         .await
         .map_err(|e| format!("Failed to remove plaid transactions: {e}"))?;
+    transfers::queries::detect_transfers(&mut tx, true)
+        .await
+        .map_err(|e| format!("Failed to detect transfers: {e}"))?;
+    let recurring_scan =
+        recurring::queries::detect_recurring(&mut tx, Local::now().date_naive())
+            .await
+            .map_err(|e| format!("Failed to detect recurring charges: {e}"))?;
 
     plaid::queries::update_plaid_item_cursor(&mut tx, &item_id, &new_cursor)
         .await
@@ -125,15 +135,53 @@ pub async fn sync_transactions(
     tx.commit()
         .await
         .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+    recurring::commands::notify_new_alerts(&app_handle, recurring_scan);
 
     Ok(num_added + num_modified + num_removed)
 }
 
+#[tauri::command]
+pub async fn get_plaid_category_mappings(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<PlaidCategoryMapping>, String> {
+    plaid::queries::get_plaid_category_mappings(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Files new transactions with the given Plaid category code under `category_id`
+
+Codes can be primary (FOOD_AND_DRINK) or detailed (FOOD_AND_DRINK_GROCERIES), and a detailed
+code takes precedence over its primary one
+*/
+#[tauri::command]
+pub async fn set_plaid_category_mapping(
+    state: tauri::State<'_, AppState>,
+    pfc_code: String,
+    category_id: i64,
+) -> Result<(), String> {
+    plaid::queries::set_plaid_category_mapping(&state.db.0, &pfc_code, category_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_plaid_category_mapping(
+    state: tauri::State<'_, AppState>,
+    pfc_code: String,
+) -> Result<(), String> {
+    plaid::queries::delete_plaid_category_mapping(&state.db.0, &pfc_code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 fn plaid_transaction_to_new_transaction(
     plaid_transaction: Transaction,
 ) -> Result<PlaidTransaction, String> {
     let plaid_transaction_id = plaid_transaction.transaction_id.clone();
-    let amount = Cents::from_dollars_f64(plaid_transaction.amount).ok_or(format!(
+    // Plaid reports money leaving the account as positive, the ledger stores it as negative
+    let amount = Cents::from_dollars_f64(-plaid_transaction.amount).ok_or(format!(
         "transaction {plaid_transaction_id} does not have a valid amount"
     ))?;
     // The name field of Transaction is non-nullable so using a default value here is fine
@@ -144,7 +192,7 @@ fn plaid_transaction_to_new_transaction(
             .unwrap_or(plaid_transaction.name.clone().unwrap_or("".to_string())),
     );
 
-    Ok(PlaidTransaction::new(
+    let transaction = PlaidTransaction::new(
         Some(plaid_transaction_id),
         Some(name),
         plaid_transaction.merchant_entity_id.clone(),
@@ -156,7 +204,12 @@ fn plaid_transaction_to_new_transaction(
         plaid_transaction.account_id.clone(),
         None,
         None,
-    ))
+    );
+
+    Ok(match plaid_transaction.personal_finance_category {
+        Some(pfc) => transaction.with_plaid_category(pfc.primary, pfc.detailed),
+        None => transaction,
+    })
 }
 
 struct SyncedTransactions {
@@ -448,7 +501,10 @@ async fn fetch_item_and_upsert(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transactions::types::TransactionInput;
+    use chrono::NaiveDate;
     use serde_json::json;
+    use sqlx::{Pool, Sqlite};
 
     #[test]
     fn dev_build_omits_completion_redirect() {
@@ -506,4 +562,73 @@ mod tests {
         assert!(extract_public_token(None).is_err());
         assert!(extract_public_token(Some(vec![])).is_err());
     }
+
+    fn plaid_transaction(amount: f64) -> Transaction {
+        serde_json::from_value(json!({
+            "transaction_id": "txn-1",
+            "account_id": "plaid-acct-1",
+            "amount": amount,
+            "date": "2026-01-05",
+            "name": "NETFLIX.COM",
+            "pending": false,
+            "payment_channel": "online"
+        }))
+        .expect("valid transaction fixture")
+    }
+
+    #[test]
+    fn synced_amounts_use_the_ledger_sign() {
+        // Plaid reports purchases as positive and refunds as negative
+        let purchase = plaid_transaction_to_new_transaction(plaid_transaction(15.49)).unwrap();
+        assert_eq!(purchase.amount.to_cents(), -1549);
+        let refund = plaid_transaction_to_new_transaction(plaid_transaction(-20.0)).unwrap();
+        assert_eq!(refund.amount.to_cents(), 2000);
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn synced_purchases_count_as_spending(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let mut purchase = plaid_transaction(42.5);
+        purchase.transaction_base.date = Local::now().date_naive();
+        let mut refund = plaid_transaction(-10.0);
+        refund.transaction_base.transaction_id = "txn-2".to_owned();
+        refund.transaction_base.date = Local::now().date_naive();
+        let synced = vec![
+            plaid_transaction_to_new_transaction(purchase)?.update_account_id(1),
+            plaid_transaction_to_new_transaction(refund)?.update_account_id(1),
+        ];
+        transactions::queries::add_plaid_transactions(&mut conn, synced, &1).await?;
+
+        let overviews = categories::queries::get_category_overviews(&pool).await?;
+        let uncategorized = overviews.iter().find(|o| o.id == 1).unwrap();
+        assert_eq!(uncategorized.spent_cents, 3250);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn synced_payment_pairs_with_imported_credit(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        // $250 leaving the linked checking account, as Plaid reports it
+        let payment =
+            plaid_transaction_to_new_transaction(plaid_transaction(250.0))?.update_account_id(1);
+        transactions::queries::add_plaid_transactions(&mut conn, vec![payment], &1).await?;
+        let credit = TransactionInput {
+            account_id: 2,
+            name: "ONLINE TRANSFER FROM CHECKING".to_owned(),
+            amount_cents: 25000,
+            date: NaiveDate::from_ymd_opt(2026, 1, 6).unwrap(),
+        };
+        let credit_id =
+            transactions::queries::create_manual_transaction(&mut conn, &credit, None, &1).await?;
+
+        assert_eq!(transfers::queries::detect_transfers(&mut conn, true).await?, 1);
+        let transfers = transfers::queries::get_transfers(&pool, None).await?;
+        assert_eq!(transfers[0].outflow_account_name, "Linked Checking");
+        assert_eq!(transfers[0].inflow_transaction_id, credit_id);
+        Ok(())
+    }
 }
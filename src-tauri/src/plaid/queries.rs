@@ -1,5 +1,7 @@
+use crate::plaid::types::PlaidCategoryMapping;
 use crate::types::PlaidItem;
 use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::HashMap;
 
 pub async fn get_plaid_item(
     pool: &Pool<Sqlite>,
@@ -73,6 +75,63 @@ pub async fn insert_plaid_item_without_cursor(
     Ok(res.rows_affected())
 }
 
+pub async fn get_plaid_category_mappings(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<PlaidCategoryMapping>, sqlx::Error> {
+    let query = "SELECT pfc_code, category_id FROM plaid_category_mapping ORDER BY pfc_code";
+
+    let mappings: Vec<PlaidCategoryMapping> = sqlx::query_as(query).fetch_all(pool).await?;
+
+    Ok(mappings)
+}
+
+/// Category ids keyed by Plaid category code, for filing newly synced transactions
+pub async fn get_plaid_category_mapping(
+    conn: &mut SqliteConnection,
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let mappings: Vec<PlaidCategoryMapping> =
+        sqlx::query_as("SELECT pfc_code, category_id FROM plaid_category_mapping")
+            .fetch_all(conn)
+            .await?;
+
+    Ok(mappings
+        .into_iter()
+        .map(|m| (m.pfc_code, m.category_id))
+        .collect())
+}
+
+pub async fn set_plaid_category_mapping(
+    pool: &Pool<Sqlite>,
+    pfc_code: &str,
+    category_id: i64,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        INSERT INTO plaid_category_mapping (pfc_code, category_id)
+        VALUES ($1, $2)
+        ON CONFLICT(pfc_code) DO UPDATE SET category_id = excluded.category_id
+    "#;
+
+    sqlx::query(query)
+        .bind(pfc_code)
+        .bind(category_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_plaid_category_mapping(
+    pool: &Pool<Sqlite>,
+    pfc_code: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM plaid_category_mapping WHERE pfc_code=$1")
+        .bind(pfc_code)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,4 +166,27 @@ mod tests {
         assert_eq!(count.0, 2);
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn category_mapping_is_seeded_and_editable(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let mapping = get_plaid_category_mapping(&mut conn).await?;
+        assert_eq!(mapping.get("FOOD_AND_DRINK_GROCERIES"), Some(&4));
+        assert_eq!(mapping.get("FOOD_AND_DRINK"), Some(&5));
+
+        set_plaid_category_mapping(&pool, "FOOD_AND_DRINK_GROCERIES", 11).await?;
+        set_plaid_category_mapping(&pool, "TRAVEL_FLIGHTS", 6).await?;
+        delete_plaid_category_mapping(&pool, "FOOD_AND_DRINK").await?;
+
+        let mappings = get_plaid_category_mappings(&pool).await?;
+        assert!(mappings.contains(&PlaidCategoryMapping {
+            pfc_code: "FOOD_AND_DRINK_GROCERIES".to_owned(),
+            category_id: 11,
+        }));
+        assert!(mappings.iter().any(|m| m.pfc_code == "TRAVEL_FLIGHTS"));
+        assert!(!mappings.iter().any(|m| m.pfc_code == "FOOD_AND_DRINK"));
+        Ok(())
+    }
 }
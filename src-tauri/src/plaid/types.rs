@@ -1,4 +1,5 @@
 use chrono::NaiveDate;
+use std::collections::HashMap;
 
 use crate::types::Cents;
 
@@ -57,6 +58,8 @@ pub struct PlaidTransaction {
     plaid_account_id: String,
     account_id: Option<i64>,
     category_id: Option<i64>,
+    pub plaid_category_primary: Option<String>,
+    pub plaid_category_detailed: Option<String>,
 }
 
 impl PlaidTransaction {
@@ -93,6 +96,8 @@ impl PlaidTransaction {
             plaid_account_id,
             account_id,
             category_id,
+            plaid_category_primary: None,
+            plaid_category_detailed: None,
         }
     }
 
@@ -100,4 +105,24 @@ impl PlaidTransaction {
         self.account_id = Some(account_id);
         self
     }
+
+    pub fn with_plaid_category(mut self, primary: String, detailed: String) -> Self {
+        self.plaid_category_primary = Some(primary);
+        self.plaid_category_detailed = Some(detailed);
+        self
+    }
+
+    /// The category mapped to this transaction's Plaid category, detailed codes first
+    pub fn mapped_category(&self, mapping: &HashMap<String, i64>) -> Option<i64> {
+        [&self.plaid_category_detailed, &self.plaid_category_primary]
+            .into_iter()
+            .flatten()
+            .find_map(|code| mapping.get(code).copied())
+    }
+}
+
+#[derive(sqlx::FromRow, PartialEq, Eq, Debug, Clone, serde::Serialize)]
+pub struct PlaidCategoryMapping {
+    pub pfc_code: String,
+    pub category_id: i64,
 }
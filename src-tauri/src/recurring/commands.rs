@@ -0,0 +1,142 @@
+use crate::recurring::queries;
+use crate::recurring::types::{
+    RecurringAlert, RecurringReview, RecurringScan, RecurringSeries, RecurringSeriesEdit,
+};
+use crate::AppState;
+use chrono::Local;
+use tauri::Emitter;
+
+/// Emitted with the number of new alerts whenever saving transactions raises any
+pub const RECURRING_ALERTS_EVENT: &str = "recurring-alerts";
+
+// Called once the alerts are committed, so listeners fetching them see the new rows
+pub fn notify_new_alerts(app_handle: &tauri::AppHandle, scan: RecurringScan) {
+    if scan.new_alerts == 0 {
+        return;
+    }
+    if let Err(e) = app_handle.emit(RECURRING_ALERTS_EVENT, scan.new_alerts) {
+        eprintln!("Failed to emit {RECURRING_ALERTS_EVENT}: {e}");
+    }
+}
+
+#[tauri::command]
+pub async fn get_recurring_series(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<RecurringSeries>, String> {
+    queries::get_recurring_series(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Looks through the whole history for recurring charges
+
+New syncs and imports are checked as they are saved, this also brings statuses up to date
+when nothing new came in. Returns how many series were found
+*/
+#[tauri::command]
+pub async fn detect_recurring(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<u64, String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let scan = queries::detect_recurring(&mut tx, Local::now().date_naive())
+        .await
+        .map_err(|e| format!("Failed to detect recurring charges: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+    notify_new_alerts(&app_handle, scan);
+
+    Ok(scan.series_found)
+}
+
+fn review_error(e: sqlx::Error, id: i64) -> String {
+    match e {
+        sqlx::Error::RowNotFound => format!("Recurring series {id} doesn't exist"),
+        e => format!("Error updating recurring series {id}: {e}"),
+    }
+}
+
+#[tauri::command]
+pub async fn confirm_recurring_series(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    queries::set_recurring_review(&state.db.0, id, RecurringReview::Confirmed)
+        .await
+        .map_err(|e| review_error(e, id))
+}
+
+/// Marks a series as not recurring so detection stops suggesting it
+#[tauri::command]
+pub async fn dismiss_recurring_series(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    queries::set_recurring_review(&state.db.0, id, RecurringReview::Dismissed)
+        .await
+        .map_err(|e| review_error(e, id))
+}
+
+/// Changes a series' name, cadence or expected amount, confirming it
+#[tauri::command]
+pub async fn update_recurring_series(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    series: RecurringSeriesEdit,
+) -> Result<(), String> {
+    series.validate()?;
+    let mut conn = state
+        .db
+        .0
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {e}"))?;
+
+    queries::update_recurring_series(&mut conn, id, &series, Local::now().date_naive())
+        .await
+        .map_err(|e| review_error(e, id))
+}
+
+/// Price changes and missed payments the user hasn't acknowledged yet, newest first
+#[tauri::command]
+pub async fn get_recurring_alerts(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<RecurringAlert>, String> {
+    queries::get_recurring_alerts(&state.db.0)
+        .await
+        .map_err(|e| format!("Error fetching recurring alerts: {e}"))
+}
+
+/// Dismisses an alert. For a price change the series expects the new amount from then on
+#[tauri::command]
+pub async fn acknowledge_recurring_alert(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    queries::acknowledge_recurring_alert(&mut tx, id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!("Alert {id} doesn't exist"),
+            e => format!("Error acknowledging alert {id}: {e}"),
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
@@ -0,0 +1,600 @@
+use crate::recurring::types::{
+    detect_series, is_price_increase, AlertKind, Cadence, Charge, RecurringAlert, RecurringReview,
+    RecurringScan, RecurringSeries, RecurringSeriesEdit, RecurringStatus,
+};
+use chrono::NaiveDate;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+
+pub async fn get_recurring_series(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<RecurringSeries>, sqlx::Error> {
+    let query = r#"
+        SELECT id, payee, name, cadence, expected_amount_cents, last_date, next_expected_date,
+            status, review
+        FROM recurring_series
+        ORDER BY next_expected_date, name
+    "#;
+
+    let series: Vec<RecurringSeries> = sqlx::query_as(query).fetch_all(pool).await?;
+
+    Ok(series)
+}
+
+/*
+Looks through each payee's charges for a recurring series and saves what it finds
+
+Suggested series take the detected cadence and amount, reviewed ones only move their dates
+along and dismissed ones are left alone. Every series' next date and status are brought up to
+`today`. Monthly series are checked for price changes and missed payments along the way
+*/
+pub async fn detect_recurring(
+    conn: &mut SqliteConnection,
+    today: NaiveDate,
+) -> Result<RecurringScan, sqlx::Error> {
+    // Series take their new charges first, so each is compared with the price it expects
+    let mut scan = RecurringScan {
+        series_found: 0,
+        new_alerts: refresh_recurring_dates(&mut *conn, None, today).await?,
+    };
+
+    let query = r#"
+        SELECT payee, date, amount_cents
+        FROM 'transaction' t
+        WHERE t.deleted_at IS NULL
+            AND t.amount_cents < 0
+            AND t.payee IS NOT NULL
+            AND t.id NOT IN (SELECT transaction_id FROM transfer_transaction)
+        ORDER BY t.payee COLLATE NOCASE, t.date, t.id
+    "#;
+    let rows: Vec<(String, NaiveDate, i64)> = sqlx::query_as(query).fetch_all(&mut *conn).await?;
+
+    let expected: Vec<(String, i64)> =
+        sqlx::query_as("SELECT payee, expected_amount_cents FROM recurring_series")
+            .fetch_all(&mut *conn)
+            .await?;
+    let expected_cents: HashMap<String, i64> = expected
+        .into_iter()
+        .map(|(payee, cents)| (payee.to_lowercase(), cents))
+        .collect();
+
+    let upsert = r#"
+        INSERT INTO recurring_series
+            (payee, name, cadence, expected_amount_cents, last_date, next_expected_date, status)
+        VALUES ($1, $1, $2, $3, $4, $5, $6)
+        ON CONFLICT(payee) DO UPDATE SET
+            cadence = CASE WHEN review = 'SUGGESTED' THEN excluded.cadence ELSE cadence END,
+            expected_amount_cents = CASE
+                WHEN review = 'SUGGESTED' THEN excluded.expected_amount_cents
+                ELSE expected_amount_cents
+            END,
+            last_date = MAX(last_date, excluded.last_date)
+        WHERE review != 'DISMISSED'
+    "#;
+
+    for payee_rows in rows.chunk_by(|a, b| a.0.eq_ignore_ascii_case(&b.0)) {
+        let charges: Vec<Charge> = payee_rows
+            .iter()
+            .map(|(_, date, amount_cents)| Charge {
+                date: *date,
+                amount_cents: *amount_cents,
+            })
+            .collect();
+        let payee = &payee_rows[0].0;
+        let expected = expected_cents.get(&payee.to_lowercase()).copied();
+        let Some(series) = detect_series(&charges, expected) else {
+            continue;
+        };
+
+        // New series start out with their status, so one found already lapsed raises no alert
+        let next_expected_date = series.cadence.next_date(series.last_date);
+        let res = sqlx::query(upsert)
+            .bind(payee)
+            .bind(series.cadence)
+            .bind(series.expected_amount_cents)
+            .bind(series.last_date)
+            .bind(next_expected_date)
+            .bind(RecurringStatus::at(
+                series.cadence,
+                next_expected_date,
+                today,
+            ))
+            .execute(&mut *conn)
+            .await?;
+        // Dismissed series aren't updated, so they don't count
+        scan.series_found += res.rows_affected();
+    }
+    scan.new_alerts += refresh_recurring_dates(conn, None, today).await?;
+
+    Ok(scan)
+}
+
+const INSERT_ALERT_QUERY: &str = r#"
+    INSERT OR IGNORE INTO recurring_alert
+        (series_id, kind, transaction_id, expected_amount_cents, amount_cents, expected_date)
+    VALUES ($1, $2, $3, $4, $5, $6)
+"#;
+
+/*
+Moves one series, or all that aren't dismissed, along the charges that came in since their last
+one, then works out their next expected date and status
+
+A charge is the series' own when it lands within the grace period of a due date. One-off
+purchases from the same payee in between are passed over, while a charge at a very different
+price still counts. Monthly series are checked for price increases as each charge is taken, and
+going from active to lapsed means a charge didn't arrive, which raises an alert. Returns how
+many alerts were raised
+*/
+async fn refresh_recurring_dates(
+    conn: &mut SqliteConnection,
+    id: Option<i64>,
+    today: NaiveDate,
+) -> Result<u64, sqlx::Error> {
+    let series: Vec<(i64, String, Cadence, NaiveDate, RecurringStatus, i64)> = sqlx::query_as(
+        "SELECT id, payee, cadence, last_date, status, expected_amount_cents FROM recurring_series WHERE review != 'DISMISSED' AND ($1 IS NULL OR id = $1)",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let charges_query = r#"
+        SELECT id, date, amount_cents
+        FROM 'transaction'
+        WHERE payee = $1 COLLATE NOCASE
+            AND date > $2
+            AND deleted_at IS NULL
+            AND amount_cents < 0
+            AND id NOT IN (SELECT transaction_id FROM transfer_transaction)
+        ORDER BY date, id
+    "#;
+
+    let mut raised = 0;
+    for (id, payee, cadence, mut last_date, previous_status, expected_cents) in series {
+        let charges: Vec<(i64, NaiveDate, i64)> = sqlx::query_as(charges_query)
+            .bind(&payee)
+            .bind(last_date)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        loop {
+            let due_date = cadence.next_date(last_date);
+            let Some(&(transaction_id, date, amount_cents)) = charges
+                .iter()
+                .filter(|(_, date, _)| *date > last_date && cadence.is_due(*date, due_date))
+                .min_by_key(|(_, date, _)| (*date - due_date).num_days().abs())
+            else {
+                break;
+            };
+
+            if cadence == Cadence::Monthly && is_price_increase(expected_cents, amount_cents) {
+                let res = sqlx::query(INSERT_ALERT_QUERY)
+                    .bind(id)
+                    .bind(AlertKind::PriceChange)
+                    .bind(transaction_id)
+                    .bind(expected_cents)
+                    .bind(amount_cents)
+                    .bind(due_date)
+                    .execute(&mut *conn)
+                    .await?;
+                raised += res.rows_affected();
+            }
+            last_date = date;
+        }
+
+        let next_expected_date = cadence.next_date(last_date);
+        let status = RecurringStatus::at(cadence, next_expected_date, today);
+        sqlx::query(
+            "UPDATE recurring_series SET last_date=$1, next_expected_date=$2, status=$3 WHERE id=$4",
+        )
+        .bind(last_date)
+        .bind(next_expected_date)
+        .bind(status)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+        if cadence == Cadence::Monthly
+            && previous_status == RecurringStatus::Active
+            && status == RecurringStatus::Lapsed
+        {
+            let res = sqlx::query(INSERT_ALERT_QUERY)
+                .bind(id)
+                .bind(AlertKind::MissedPayment)
+                .bind(None::<i64>)
+                .bind(expected_cents)
+                .bind(None::<i64>)
+                .bind(next_expected_date)
+                .execute(&mut *conn)
+                .await?;
+            raised += res.rows_affected();
+        }
+    }
+    Ok(raised)
+}
+
+pub async fn set_recurring_review(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    review: RecurringReview,
+) -> Result<(), sqlx::Error> {
+    let res = sqlx::query("UPDATE recurring_series SET review=$1 WHERE id=$2")
+        .bind(review)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/// Saves the user's changes to a series, which confirms it so detection keeps them
+pub async fn update_recurring_series(
+    conn: &mut SqliteConnection,
+    id: i64,
+    edit: &RecurringSeriesEdit,
+    today: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE recurring_series
+        SET name=$1, cadence=$2, expected_amount_cents=$3, review='CONFIRMED'
+        WHERE id=$4
+    "#;
+
+    let res = sqlx::query(query)
+        .bind(edit.name.trim())
+        .bind(edit.cadence)
+        .bind(edit.expected_amount_cents)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    refresh_recurring_dates(conn, Some(id), today).await?;
+    Ok(())
+}
+
+pub async fn get_recurring_alerts(pool: &Pool<Sqlite>) -> Result<Vec<RecurringAlert>, sqlx::Error> {
+    let query = r#"
+        SELECT
+            a.id,
+            a.series_id,
+            s.name AS series_name,
+            a.kind,
+            a.transaction_id,
+            a.expected_amount_cents,
+            a.amount_cents,
+            a.expected_date,
+            a.created_at
+        FROM recurring_alert a
+        JOIN recurring_series s ON s.id = a.series_id
+        WHERE a.acknowledged_at IS NULL
+        ORDER BY a.created_at DESC, a.id DESC
+    "#;
+
+    let alerts: Vec<RecurringAlert> = sqlx::query_as(query).fetch_all(pool).await?;
+
+    Ok(alerts)
+}
+
+/*
+Marks an alert as seen
+
+Acknowledging a price change takes the new amount as what the series expects, so later charges
+at that price don't raise it again
+*/
+pub async fn acknowledge_recurring_alert(
+    conn: &mut SqliteConnection,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE recurring_series
+        SET expected_amount_cents = a.amount_cents
+        FROM recurring_alert a
+        WHERE a.id=$1
+            AND a.series_id = recurring_series.id
+            AND a.kind = 'PRICE_CHANGE'
+            AND a.acknowledged_at IS NULL
+    "#;
+    sqlx::query(query).bind(id).execute(&mut *conn).await?;
+
+    let res = sqlx::query(
+        "UPDATE recurring_alert SET acknowledged_at = COALESCE(acknowledged_at, datetime('now')) WHERE id=?",
+    )
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    async fn insert_charges(
+        pool: &Pool<Sqlite>,
+        payee: &str,
+        charges: &[(NaiveDate, i64)],
+    ) -> Result<(), sqlx::Error> {
+        for (date, amount_cents) in charges {
+            sqlx::query(
+                "INSERT INTO 'transaction' (name, payee, amount_cents, date, account_id, category_id) VALUES ($1, $1, $2, $3, 1, 1)",
+            )
+            .bind(payee)
+            .bind(amount_cents)
+            .bind(date)
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn detects_series_and_keeps_user_changes(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let today = date(2026, 10, 18);
+        insert_charges(
+            &pool,
+            "SimpleBills Prod",
+            &[
+                (date(2026, 7, 2), -8412),
+                (date(2026, 8, 3), -9120),
+                (date(2026, 9, 2), -7850),
+                (date(2026, 10, 2), -8833),
+            ],
+        )
+        .await?;
+        insert_charges(
+            &pool,
+            "BILTPYMTS DES:RENT PMT",
+            &[
+                (date(2026, 5, 1), -250000),
+                (date(2026, 6, 1), -250000),
+                (date(2026, 7, 1), -250000),
+            ],
+        )
+        .await?;
+
+        assert_eq!(detect_recurring(&mut conn, today).await?.series_found, 2);
+        let series = get_recurring_series(&pool).await?;
+        assert_eq!(series.len(), 2);
+        let (rent, bills) = (&series[0], &series[1]);
+        assert_eq!(
+            (rent.name.as_str(), rent.next_expected_date, rent.status),
+            (
+                "BILTPYMTS DES:RENT PMT",
+                date(2026, 8, 1),
+                RecurringStatus::Lapsed
+            )
+        );
+        assert_eq!(
+            (
+                bills.cadence,
+                bills.expected_amount_cents,
+                bills.next_expected_date
+            ),
+            (Cadence::Monthly, -8833, date(2026, 11, 2))
+        );
+        assert_eq!(
+            (bills.status, bills.review),
+            (RecurringStatus::Active, RecurringReview::Suggested)
+        );
+
+        let edit = RecurringSeriesEdit {
+            name: "Utilities".to_owned(),
+            cadence: Cadence::Monthly,
+            expected_amount_cents: -9000,
+        };
+        update_recurring_series(&mut conn, bills.id, &edit, today).await?;
+        set_recurring_review(&pool, rent.id, RecurringReview::Dismissed).await?;
+
+        // A later charge moves the dates along without undoing the edit or the dismissal
+        insert_charges(&pool, "SimpleBills Prod", &[(date(2026, 11, 3), -8100)]).await?;
+        insert_charges(
+            &pool,
+            "BILTPYMTS DES:RENT PMT",
+            &[(date(2026, 8, 1), -250000)],
+        )
+        .await?;
+        detect_recurring(&mut conn, date(2026, 11, 4)).await?;
+
+        let series = get_recurring_series(&pool).await?;
+        let bills = series.iter().find(|s| s.id == bills.id).unwrap();
+        assert_eq!(
+            (
+                bills.name.as_str(),
+                bills.expected_amount_cents,
+                bills.next_expected_date
+            ),
+            ("Utilities", -9000, date(2026, 12, 3))
+        );
+        assert_eq!(bills.review, RecurringReview::Confirmed);
+        let rent = series.iter().find(|s| s.id == rent.id).unwrap();
+        assert_eq!(
+            (rent.last_date, rent.review),
+            (date(2026, 7, 1), RecurringReview::Dismissed)
+        );
+
+        assert!(matches!(
+            set_recurring_review(&pool, 99, RecurringReview::Confirmed).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn alerts_on_price_increases_and_missed_payments(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let streaming = [
+            (date(2026, 7, 5), -1549),
+            (date(2026, 8, 5), -1549),
+            (date(2026, 9, 5), -1549),
+        ];
+        insert_charges(&pool, "Netflix", &streaming).await?;
+        let scan = detect_recurring(&mut conn, date(2026, 9, 10)).await?;
+        assert_eq!(scan.new_alerts, 0);
+        let series_id = get_recurring_series(&pool).await?[0].id;
+        set_recurring_review(&pool, series_id, RecurringReview::Confirmed).await?;
+
+        insert_charges(&pool, "Netflix", &[(date(2026, 10, 5), -1799)]).await?;
+        let scan = detect_recurring(&mut conn, date(2026, 10, 6)).await?;
+        assert_eq!(scan.new_alerts, 1);
+
+        // A one-off purchase between due dates isn't the subscription changing price, but the
+        // November charge never arriving is a missed payment
+        insert_charges(&pool, "Netflix", &[(date(2026, 10, 20), -5000)]).await?;
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 10))
+                .await?
+                .new_alerts,
+            0
+        );
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 20))
+                .await?
+                .new_alerts,
+            1
+        );
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 21))
+                .await?
+                .new_alerts,
+            0
+        );
+
+        let alerts = get_recurring_alerts(&pool).await?;
+        let summary: Vec<(AlertKind, Option<i64>, NaiveDate)> = alerts
+            .iter()
+            .map(|a| (a.kind, a.amount_cents, a.expected_date))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (AlertKind::MissedPayment, None, date(2026, 11, 5)),
+                (AlertKind::PriceChange, Some(-1799), date(2026, 10, 5)),
+            ]
+        );
+        assert_eq!(alerts[1].series_name, "Netflix");
+        assert_eq!(alerts[1].expected_amount_cents, -1549);
+
+        acknowledge_recurring_alert(&mut conn, alerts[1].id).await?;
+        assert_eq!(get_recurring_alerts(&pool).await?.len(), 1);
+        assert_eq!(
+            get_recurring_series(&pool).await?[0].expected_amount_cents,
+            -1799
+        );
+        assert!(matches!(
+            acknowledge_recurring_alert(&mut conn, 99).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn charges_after_a_one_off_purchase_keep_the_series_going(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let streaming = [
+            (date(2026, 7, 5), -1549),
+            (date(2026, 8, 5), -1549),
+            (date(2026, 9, 5), -1549),
+            (date(2026, 10, 5), -1549),
+        ];
+        insert_charges(&pool, "Netflix", &streaming).await?;
+        detect_recurring(&mut conn, date(2026, 10, 6)).await?;
+
+        // The one-off is 15 days after October's charge and November's comes 16 days later
+        insert_charges(&pool, "Netflix", &[(date(2026, 10, 20), -5000)]).await?;
+        insert_charges(&pool, "Netflix", &[(date(2026, 11, 5), -1549)]).await?;
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 6))
+                .await?
+                .new_alerts,
+            0
+        );
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 20))
+                .await?
+                .new_alerts,
+            0
+        );
+
+        let series = get_recurring_series(&pool).await?;
+        assert_eq!(
+            (
+                series[0].cadence,
+                series[0].last_date,
+                series[0].next_expected_date
+            ),
+            (Cadence::Monthly, date(2026, 11, 5), date(2026, 12, 5))
+        );
+        assert_eq!(series[0].status, RecurringStatus::Active);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn acknowledged_price_hikes_keep_the_series_going(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let gym = [
+            (date(2026, 7, 1), -1000),
+            (date(2026, 8, 1), -1000),
+            (date(2026, 9, 1), -1000),
+        ];
+        insert_charges(&pool, "Gym", &gym).await?;
+        detect_recurring(&mut conn, date(2026, 9, 2)).await?;
+
+        // Doubling the price is too far off to be detected as the same series
+        insert_charges(&pool, "Gym", &[(date(2026, 10, 1), -2000)]).await?;
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 10, 2))
+                .await?
+                .new_alerts,
+            1
+        );
+        let alerts = get_recurring_alerts(&pool).await?;
+        acknowledge_recurring_alert(&mut conn, alerts[0].id).await?;
+
+        insert_charges(&pool, "Gym", &[(date(2026, 11, 1), -2000)]).await?;
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 2))
+                .await?
+                .new_alerts,
+            0
+        );
+        assert_eq!(
+            detect_recurring(&mut conn, date(2026, 11, 20))
+                .await?
+                .new_alerts,
+            0
+        );
+        assert!(get_recurring_alerts(&pool).await?.is_empty());
+
+        let series = get_recurring_series(&pool).await?;
+        assert_eq!(
+            (
+                series[0].expected_amount_cents,
+                series[0].last_date,
+                series[0].next_expected_date
+            ),
+            (-2000, date(2026, 11, 1), date(2026, 12, 1))
+        );
+        assert_eq!(series[0].status, RecurringStatus::Active);
+        Ok(())
+    }
+}
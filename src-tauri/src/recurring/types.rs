@@ -0,0 +1,374 @@
+use chrono::{Days, Months, NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum Cadence {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Annual,
+}
+
+impl Cadence {
+    const ALL: [Cadence; 4] = [
+        Cadence::Weekly,
+        Cadence::Biweekly,
+        Cadence::Monthly,
+        Cadence::Annual,
+    ];
+
+    // Loose enough for charges that move around weekends and short months
+    fn interval_days(self) -> std::ops::RangeInclusive<i64> {
+        match self {
+            Cadence::Weekly => 6..=8,
+            Cadence::Biweekly => 12..=16,
+            Cadence::Monthly => 26..=35,
+            Cadence::Annual => 350..=380,
+        }
+    }
+
+    fn from_interval(days: i64) -> Option<Self> {
+        Cadence::ALL
+            .into_iter()
+            .find(|cadence| cadence.interval_days().contains(&days))
+    }
+
+    // Annual charges can't wait for a third year to be noticed
+    fn min_occurrences(self) -> usize {
+        match self {
+            Cadence::Annual => 2,
+            _ => 3,
+        }
+    }
+
+    /// How late a charge can be before the series counts as lapsed
+    pub fn grace_days(self) -> u64 {
+        match self {
+            Cadence::Weekly => 3,
+            Cadence::Biweekly => 5,
+            Cadence::Monthly => 7,
+            Cadence::Annual => 30,
+        }
+    }
+
+    /// Whether a charge on `date` is the one due on `expected_date`, give or take the grace period
+    pub fn is_due(self, date: NaiveDate, expected_date: NaiveDate) -> bool {
+        (date - expected_date).num_days().unsigned_abs() <= self.grace_days()
+    }
+
+    pub fn next_date(self, after: NaiveDate) -> NaiveDate {
+        match self {
+            Cadence::Weekly => after + Days::new(7),
+            Cadence::Biweekly => after + Days::new(14),
+            Cadence::Monthly => after + Months::new(1),
+            Cadence::Annual => after + Months::new(12),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum RecurringStatus {
+    Active,
+    /// The next charge is overdue past the cadence's grace period
+    Lapsed,
+}
+
+impl RecurringStatus {
+    pub fn at(cadence: Cadence, next_expected_date: NaiveDate, today: NaiveDate) -> Self {
+        if today > next_expected_date + Days::new(cadence.grace_days()) {
+            RecurringStatus::Lapsed
+        } else {
+            RecurringStatus::Active
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum RecurringReview {
+    /// Found by detection, which keeps its cadence and amount up to date
+    Suggested,
+    /// Confirmed or edited by the user, so detection only moves its dates along
+    Confirmed,
+    /// Not recurring after all. Detection leaves it alone
+    Dismissed,
+}
+
+/// One charge in a payee's history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Charge {
+    pub date: NaiveDate,
+    pub amount_cents: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedSeries {
+    pub cadence: Cadence,
+    /// The latest charge's amount, so a price change carries forward
+    pub expected_amount_cents: i64,
+    pub last_date: NaiveDate,
+    pub occurrences: usize,
+}
+
+// Bills like utilities vary from month to month, a different service usually costs more than this
+fn similar_amount(amount_cents: i64, expected_cents: i64) -> bool {
+    (amount_cents - expected_cents).abs() * 10 <= expected_cents.abs() * 3
+}
+
+// How many of the latest charges' amounts are tried when the payee has no series yet
+const AMOUNTS_TRIED: usize = 3;
+
+/*
+Finds the series a payee's charges end with, given in date order
+
+Charges more than 30% away from the series' amount, such as a one-off purchase from the same
+payee, are left out before the cadence is worked out. That amount is `expected_cents` for a
+payee that already has a series, otherwise each of the latest charges' is tried in turn
+*/
+pub fn detect_series(charges: &[Charge], expected_cents: Option<i64>) -> Option<DetectedSeries> {
+    let references: Vec<i64> = match expected_cents {
+        Some(expected_cents) => vec![expected_cents],
+        None => charges
+            .iter()
+            .rev()
+            .take(AMOUNTS_TRIED)
+            .map(|charge| charge.amount_cents)
+            .collect(),
+    };
+
+    references.into_iter().find_map(|reference| {
+        let matching: Vec<Charge> = charges
+            .iter()
+            .filter(|charge| similar_amount(charge.amount_cents, reference))
+            .copied()
+            .collect();
+        trailing_series(&matching)
+    })
+}
+
+/*
+Working back from the latest charge, earlier ones belong to the series while they are one
+cadence apart and within 30% of the latest amount. Older history that doesn't fit, such as a
+plan the user switched from, is ignored
+*/
+fn trailing_series(charges: &[Charge]) -> Option<DetectedSeries> {
+    let [.., previous, last] = charges else {
+        return None;
+    };
+    let cadence = Cadence::from_interval((last.date - previous.date).num_days())?;
+
+    let occurrences = 1 + charges
+        .windows(2)
+        .rev()
+        .take_while(|pair| {
+            cadence
+                .interval_days()
+                .contains(&(pair[1].date - pair[0].date).num_days())
+                && similar_amount(pair[0].amount_cents, last.amount_cents)
+        })
+        .count();
+
+    (occurrences >= cadence.min_occurrences()).then_some(DetectedSeries {
+        cadence,
+        expected_amount_cents: last.amount_cents,
+        last_date: last.date,
+        occurrences,
+    })
+}
+
+/// What a detection pass found, so callers can tell the user about new alerts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecurringScan {
+    pub series_found: u64,
+    pub new_alerts: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertKind {
+    /// A charge came in costing more than the series' expected amount
+    PriceChange,
+    /// The charge didn't arrive by the end of the grace period
+    MissedPayment,
+}
+
+/// Whether a charge costs enough more than expected to tell the user, ignoring a few cents of tax
+pub fn is_price_increase(expected_cents: i64, amount_cents: i64) -> bool {
+    // Both are negative, so a bigger charge is a smaller number
+    (expected_cents - amount_cents) * 20 > expected_cents.abs()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct RecurringAlert {
+    pub id: i64,
+    pub series_id: i64,
+    pub series_name: String,
+    pub kind: AlertKind,
+    pub transaction_id: Option<i64>,
+    pub expected_amount_cents: i64,
+    /// None for missed payments
+    pub amount_cents: Option<i64>,
+    pub expected_date: NaiveDate,
+    pub created_at: NaiveDateTime,
+}
+
+/// The parts of a series the user edits
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct RecurringSeriesEdit {
+    pub name: String,
+    pub cadence: Cadence,
+    pub expected_amount_cents: i64,
+}
+
+impl RecurringSeriesEdit {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Name is required".to_string());
+        }
+        if self.expected_amount_cents == 0 {
+            return Err("The expected amount can't be zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct RecurringSeries {
+    pub id: i64,
+    pub payee: String,
+    pub name: String,
+    pub cadence: Cadence,
+    pub expected_amount_cents: i64,
+    pub last_date: NaiveDate,
+    pub next_expected_date: NaiveDate,
+    pub status: RecurringStatus,
+    pub review: RecurringReview,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charges(dates: &[(i32, u32, u32)], amount_cents: i64) -> Vec<Charge> {
+        dates
+            .iter()
+            .map(|&(y, m, d)| Charge {
+                date: NaiveDate::from_ymd_opt(y, m, d).unwrap(),
+                amount_cents,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_cadence_from_trailing_run() {
+        let rent = charges(
+            &[(2026, 6, 1), (2026, 7, 1), (2026, 8, 3), (2026, 9, 1)],
+            -250000,
+        );
+        let series = detect_series(&rent, None).unwrap();
+        assert_eq!(series.cadence, Cadence::Monthly);
+        assert_eq!(series.occurrences, 4);
+        assert_eq!(
+            series.cadence.next_date(series.last_date),
+            NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()
+        );
+
+        let paycheck = charges(&[(2026, 9, 4), (2026, 9, 18), (2026, 10, 2)], -4000);
+        assert_eq!(
+            detect_series(&paycheck, None).unwrap().cadence,
+            Cadence::Biweekly
+        );
+
+        let annual = charges(&[(2025, 3, 10), (2026, 3, 12)], -9900);
+        assert_eq!(
+            detect_series(&annual, None).unwrap().cadence,
+            Cadence::Annual
+        );
+
+        // Two monthly charges aren't enough, nor are irregular ones
+        assert_eq!(
+            detect_series(&charges(&[(2026, 8, 1), (2026, 9, 1)], -999), None),
+            None
+        );
+        assert_eq!(
+            detect_series(
+                &charges(&[(2026, 7, 1), (2026, 7, 20), (2026, 9, 1)], -999),
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn amounts_must_stay_close_to_the_latest() {
+        let mut streaming = charges(&[(2026, 6, 5), (2026, 7, 5), (2026, 8, 5)], -1549);
+        streaming.push(Charge {
+            date: NaiveDate::from_ymd_opt(2026, 9, 5).unwrap(),
+            amount_cents: -1799,
+        });
+        let series = detect_series(&streaming, None).unwrap();
+        assert_eq!(series.expected_amount_cents, -1799);
+        assert_eq!(series.occurrences, 4);
+
+        let mut shop = charges(&[(2026, 7, 5), (2026, 8, 5)], -2000);
+        shop.push(Charge {
+            date: NaiveDate::from_ymd_opt(2026, 9, 5).unwrap(),
+            amount_cents: -12000,
+        });
+        assert_eq!(detect_series(&shop, None), None);
+    }
+
+    #[test]
+    fn one_off_charges_are_left_out() {
+        let mut music = charges(&[(2026, 6, 15), (2026, 7, 15), (2026, 8, 15)], -1099);
+        // An app bought from the same payee a few days after the latest charge
+        music.push(Charge {
+            date: NaiveDate::from_ymd_opt(2026, 8, 20).unwrap(),
+            amount_cents: -4999,
+        });
+        let series = detect_series(&music, None).unwrap();
+        assert_eq!(series.cadence, Cadence::Monthly);
+        assert_eq!(
+            (
+                series.expected_amount_cents,
+                series.last_date,
+                series.occurrences
+            ),
+            (-1099, NaiveDate::from_ymd_opt(2026, 8, 15).unwrap(), 3)
+        );
+
+        // A known series is matched on the amount it expects, whatever came in last
+        music.push(Charge {
+            date: NaiveDate::from_ymd_opt(2026, 8, 22).unwrap(),
+            amount_cents: -299,
+        });
+        assert_eq!(detect_series(&music, Some(-1099)), Some(series));
+        assert_eq!(detect_series(&music, Some(-8000)), None);
+    }
+
+    #[test]
+    fn only_increases_over_five_percent_alert() {
+        assert!(is_price_increase(-1549, -1799));
+        assert!(!is_price_increase(-1549, -1599));
+        assert!(!is_price_increase(-1549, -999));
+    }
+
+    #[test]
+    fn lapses_after_grace_period() {
+        let due = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let on = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        assert_eq!(
+            RecurringStatus::at(Cadence::Monthly, due, on(8)),
+            RecurringStatus::Active
+        );
+        assert_eq!(
+            RecurringStatus::at(Cadence::Monthly, due, on(9)),
+            RecurringStatus::Lapsed
+        );
+        assert_eq!(
+            RecurringStatus::at(Cadence::Weekly, due, on(5)),
+            RecurringStatus::Lapsed
+        );
+    }
+}
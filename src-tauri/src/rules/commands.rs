@@ -0,0 +1,79 @@
+use crate::rules::queries;
+use crate::rules::types::{CategorizationRule, CategoryChange, RuleDefinition};
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_categorization_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<CategorizationRule>, String> {
+    queries::get_categorization_rules(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_categorization_rule(
+    state: tauri::State<'_, AppState>,
+    rule: RuleDefinition,
+) -> Result<i64, String> {
+    rule.validate()?;
+    queries::create_categorization_rule(&state.db.0, &rule)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_categorization_rule(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    rule: RuleDefinition,
+) -> Result<(), String> {
+    rule.validate()?;
+    queries::update_categorization_rule(&state.db.0, id, &rule)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_categorization_rule(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    queries::delete_categorization_rule(&state.db.0, id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Runs a rule against existing transactions, usually right after it was created or edited
+
+All rows move in one transaction. A dry run returns the same list of changes, including the
+category each row would leave, without saving anything
+*/
+#[tauri::command]
+pub async fn apply_categorization_rule(
+    state: tauri::State<'_, AppState>,
+    rule_id: i64,
+    only_uncategorized: bool,
+    dry_run: bool,
+) -> Result<Vec<CategoryChange>, String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let changes = queries::apply_categorization_rule(&mut tx, rule_id, only_uncategorized, dry_run)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!("Rule {rule_id} does not exist"),
+            e => format!("Failed to apply rule {rule_id}: {e}"),
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(changes)
+}
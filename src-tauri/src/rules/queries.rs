@@ -0,0 +1,377 @@
+use crate::categories;
+use crate::rules::types::{
+    CategorizationRule, CategoryChange, RuleDefinition, RuleSet, RuleTarget,
+};
+use crate::transactions;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+const RULE_COLUMNS: &str = r#"
+    id,
+    name,
+    priority,
+    enabled,
+    description_pattern,
+    description_match,
+    merchant_entity_id,
+    account_id,
+    min_amount_cents,
+    max_amount_cents,
+    amount_sign,
+    category_id
+"#;
+
+pub async fn get_categorization_rules(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<CategorizationRule>, sqlx::Error> {
+    let query = format!("SELECT {RULE_COLUMNS} FROM categorization_rule ORDER BY priority DESC, id");
+
+    let rules: Vec<CategorizationRule> = sqlx::query_as(&query).fetch_all(pool).await?;
+
+    Ok(rules)
+}
+
+/// The enabled rules, ready to categorize transactions
+pub async fn get_rule_set(conn: &mut SqliteConnection) -> Result<RuleSet, sqlx::Error> {
+    let query = format!("SELECT {RULE_COLUMNS} FROM categorization_rule WHERE enabled = 1");
+
+    let rules: Vec<CategorizationRule> = sqlx::query_as(&query).fetch_all(conn).await?;
+
+    Ok(RuleSet::new(rules))
+}
+
+pub async fn create_categorization_rule(
+    pool: &Pool<Sqlite>,
+    rule: &RuleDefinition,
+) -> Result<i64, sqlx::Error> {
+    let query = r#"
+        INSERT INTO categorization_rule (
+            name,
+            priority,
+            enabled,
+            description_pattern,
+            description_match,
+            merchant_entity_id,
+            account_id,
+            min_amount_cents,
+            max_amount_cents,
+            amount_sign,
+            category_id
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+    "#;
+
+    sqlx::query_scalar(query)
+        .bind(&rule.name)
+        .bind(rule.priority)
+        .bind(rule.enabled)
+        .bind(&rule.description_pattern)
+        .bind(rule.description_match)
+        .bind(&rule.merchant_entity_id)
+        .bind(rule.account_id)
+        .bind(rule.min_amount_cents)
+        .bind(rule.max_amount_cents)
+        .bind(rule.amount_sign)
+        .bind(rule.category_id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn update_categorization_rule(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    rule: &RuleDefinition,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE categorization_rule
+        SET name=?,
+            priority=?,
+            enabled=?,
+            description_pattern=?,
+            description_match=?,
+            merchant_entity_id=?,
+            account_id=?,
+            min_amount_cents=?,
+            max_amount_cents=?,
+            amount_sign=?,
+            category_id=?
+        WHERE id=?
+    "#;
+
+    let res = sqlx::query(query)
+        .bind(&rule.name)
+        .bind(rule.priority)
+        .bind(rule.enabled)
+        .bind(&rule.description_pattern)
+        .bind(rule.description_match)
+        .bind(&rule.merchant_entity_id)
+        .bind(rule.account_id)
+        .bind(rule.min_amount_cents)
+        .bind(rule.max_amount_cents)
+        .bind(rule.amount_sign)
+        .bind(rule.category_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+pub async fn delete_categorization_rule(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM categorization_rule WHERE id=?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/*
+Runs a rule against transactions already in the ledger and returns the rows it moves
+
+A row only moves when this rule is the highest priority rule matching it, so applying a rule
+never overrides one that outranks it. The rule is tried even while disabled so it can be
+previewed before being switched on. With `only_uncategorized`, rows that were already filed
+are left alone, and a dry run reports the changes without writing them
+*/
+pub async fn apply_categorization_rule(
+    conn: &mut SqliteConnection,
+    rule_id: i64,
+    only_uncategorized: bool,
+    dry_run: bool,
+) -> Result<Vec<CategoryChange>, sqlx::Error> {
+    let query = format!("SELECT {RULE_COLUMNS} FROM categorization_rule");
+    let mut rules: Vec<CategorizationRule> = sqlx::query_as(&query).fetch_all(&mut *conn).await?;
+    let rule = rules
+        .iter_mut()
+        .find(|rule| rule.id == rule_id)
+        .ok_or(sqlx::Error::RowNotFound)?;
+    rule.definition.enabled = true;
+    let to_category_id = rule.definition.category_id;
+
+    let to_category_name: String = sqlx::query_scalar("SELECT name FROM category WHERE id=?")
+        .bind(to_category_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let rules = RuleSet::new(rules);
+    let uncategorized = categories::queries::get_uncategorized_category(&mut *conn).await?;
+
+    let target_query = r#"
+        SELECT
+            t.id,
+            t.name,
+            t.merchant_entity_id,
+            t.account_id,
+            t.amount_cents,
+            t.date,
+            t.category_id,
+            c.name AS category_name
+        FROM 'transaction' t
+        JOIN category c ON c.id = t.category_id
+        WHERE t.deleted_at IS NULL
+            AND t.category_id != ?
+            AND (? = 0 OR t.category_id = ?)
+        ORDER BY t.date, t.id
+    "#;
+    let targets: Vec<RuleTarget> = sqlx::query_as(target_query)
+        .bind(to_category_id)
+        .bind(only_uncategorized)
+        .bind(uncategorized.id())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let changes: Vec<CategoryChange> = targets
+        .into_iter()
+        .filter(|target| {
+            rules
+                .matching_rule(&target.candidate())
+                .is_some_and(|rule| rule.id == rule_id)
+        })
+        .map(|target| CategoryChange {
+            transaction_id: target.id,
+            name: target.name,
+            date: target.date,
+            from_category_id: target.category_id,
+            from_category_name: target.category_name,
+            to_category_id,
+            to_category_name: to_category_name.clone(),
+        })
+        .collect();
+
+    if !dry_run {
+        for change in &changes {
+            transactions::queries::update_transaction_category(
+                &mut *conn,
+                change.transaction_id,
+                change.to_category_id,
+            )
+            .await?;
+        }
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::types::{AmountSign, DescriptionMatch, RuleCandidate};
+    use rust_decimal::dec;
+
+    fn groceries_rule() -> RuleDefinition {
+        RuleDefinition {
+            name: "Groceries".to_owned(),
+            priority: 0,
+            enabled: true,
+            description_pattern: Some("market".to_owned()),
+            description_match: DescriptionMatch::Contains,
+            merchant_entity_id: None,
+            account_id: None,
+            min_amount_cents: None,
+            max_amount_cents: Some(20000),
+            amount_sign: Some(AmountSign::Debit),
+            category_id: 4,
+        }
+    }
+
+    fn transaction_ids(changes: &[CategoryChange]) -> Vec<i64> {
+        changes.iter().map(|c| c.transaction_id).collect()
+    }
+
+    async fn categories_by_name(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT t.name, c.name FROM 'transaction' t JOIN category c ON c.id = t.category_id ORDER BY t.id",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn dry_run_reports_changes_without_writing(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let everything_small = RuleDefinition {
+            description_pattern: Some("transaction".to_owned()),
+            max_amount_cents: Some(600),
+            amount_sign: None,
+            ..groceries_rule()
+        };
+        let id = create_categorization_rule(&pool, &everything_small).await?;
+        let before = categories_by_name(&pool).await?;
+
+        let mut conn = pool.acquire().await?;
+        let changes = apply_categorization_rule(&mut conn, id, false, true).await?;
+
+        // Oldest first: transaction 4 shares a date with 2, which is over the limit
+        assert_eq!(transaction_ids(&changes), vec![1, 4, 3]);
+        assert!(changes
+            .iter()
+            .all(|c| c.from_category_name == "Uncategorized" && c.to_category_name == "Groceries"));
+        assert_eq!(categories_by_name(&pool).await?, before);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn apply_can_leave_filed_transactions_alone(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let rule = RuleDefinition {
+            description_pattern: Some("transaction".to_owned()),
+            max_amount_cents: None,
+            ..groceries_rule()
+        };
+        let id = create_categorization_rule(&pool, &rule).await?;
+        transactions::queries::update_transaction_category(&pool, 1, 3).await?;
+
+        let mut conn = pool.acquire().await?;
+        let changes = apply_categorization_rule(&mut conn, id, true, false).await?;
+        assert_eq!(transaction_ids(&changes), vec![2, 4, 3]);
+        let categories = categories_by_name(&pool).await?;
+        assert_eq!(categories[0].1, "Housing");
+        assert!(categories[1..].iter().all(|(_, c)| c == "Groceries"));
+
+        let changes = apply_categorization_rule(&mut conn, id, false, false).await?;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].from_category_name, "Housing");
+        assert!(categories_by_name(&pool).await?.iter().all(|(_, c)| c == "Groceries"));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn apply_defers_to_higher_priority_rules(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let low = create_categorization_rule(
+            &pool,
+            &RuleDefinition {
+                description_pattern: Some("transaction".to_owned()),
+                max_amount_cents: None,
+                ..groceries_rule()
+            },
+        )
+        .await?;
+        create_categorization_rule(
+            &pool,
+            &RuleDefinition {
+                priority: 10,
+                description_pattern: Some("transaction 2".to_owned()),
+                category_id: 5,
+                ..groceries_rule()
+            },
+        )
+        .await?;
+
+        let mut conn = pool.acquire().await?;
+        let changes = apply_categorization_rule(&mut conn, low, false, true).await?;
+        assert_eq!(transaction_ids(&changes), vec![1, 4, 3]);
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn create_round_trips_rule(pool: Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+        let id = create_categorization_rule(&pool, &groceries_rule()).await?;
+
+        assert_eq!(
+            get_categorization_rules(&pool).await?,
+            vec![CategorizationRule {
+                id,
+                definition: groceries_rule(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn rule_set_skips_disabled_rules(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let id = create_categorization_rule(&pool, &groceries_rule()).await?;
+        let market = RuleCandidate {
+            name: "FOOD MARKET #111",
+            merchant_entity_id: None,
+            account_id: 1,
+            amount: dec!(-7.25),
+        };
+
+        let mut conn = pool.acquire().await?;
+        assert_eq!(get_rule_set(&mut conn).await?.categorize(&market), Some(4));
+
+        let disabled = RuleDefinition {
+            enabled: false,
+            ..groceries_rule()
+        };
+        update_categorization_rule(&pool, id, &disabled).await?;
+        assert_eq!(get_rule_set(&mut conn).await?.categorize(&market), None);
+
+        delete_categorization_rule(&pool, id).await?;
+        assert!(get_categorization_rules(&pool).await?.is_empty());
+        assert!(matches!(
+            update_categorization_rule(&pool, id, &disabled).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+use crate::types::Cents;
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use rust_decimal::Decimal;
+
+/// How `description_pattern` is compared against a transaction's name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum DescriptionMatch {
+    /// Case-insensitive substring
+    Contains,
+    /// Case-insensitive regular expression
+    Regex,
+}
+
+/// Direction of money, following the app's convention that spending is negative
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum AmountSign {
+    Debit,
+    Credit,
+}
+
+/// Everything about a rule that the user edits. Unset conditions match any transaction
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct RuleDefinition {
+    pub name: String,
+    /// Rules are tried from the highest priority down, the first match wins
+    pub priority: i64,
+    pub enabled: bool,
+    pub description_pattern: Option<String>,
+    pub description_match: DescriptionMatch,
+    pub merchant_entity_id: Option<String>,
+    pub account_id: Option<i64>,
+    /// Bounds on the absolute amount, inclusive
+    pub min_amount_cents: Option<i64>,
+    pub max_amount_cents: Option<i64>,
+    pub amount_sign: Option<AmountSign>,
+    pub category_id: i64,
+}
+
+impl RuleDefinition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Rule name is required".to_string());
+        }
+
+        let has_condition = self.description_pattern.is_some()
+            || self.merchant_entity_id.is_some()
+            || self.account_id.is_some()
+            || self.min_amount_cents.is_some()
+            || self.max_amount_cents.is_some()
+            || self.amount_sign.is_some();
+        if !has_condition {
+            return Err("A rule needs at least one condition".to_string());
+        }
+
+        if let Some(pattern) = &self.description_pattern {
+            if pattern.is_empty() {
+                return Err("Description pattern can't be empty".to_string());
+            }
+            if self.description_match == DescriptionMatch::Regex {
+                compile_pattern(pattern).map_err(|e| format!("Invalid regex: {e}"))?;
+            }
+        }
+
+        if self.min_amount_cents.is_some_and(|min| min < 0)
+            || self.max_amount_cents.is_some_and(|max| max < 0)
+        {
+            return Err("Amount bounds apply to the absolute amount and can't be negative".to_string());
+        }
+        if let (Some(min), Some(max)) = (self.min_amount_cents, self.max_amount_cents) {
+            if min > max {
+                return Err("Minimum amount is greater than the maximum".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct CategorizationRule {
+    pub id: i64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub definition: RuleDefinition,
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+/// The fields of a transaction that rules can match on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleCandidate<'a> {
+    pub name: &'a str,
+    pub merchant_entity_id: Option<&'a str>,
+    pub account_id: i64,
+    pub amount: Decimal,
+}
+
+enum DescriptionMatcher {
+    Contains(String),
+    Regex(Regex),
+}
+
+struct CompiledRule {
+    rule: CategorizationRule,
+    description: Option<DescriptionMatcher>,
+}
+
+impl CompiledRule {
+    fn matches(&self, candidate: &RuleCandidate) -> bool {
+        let rule = &self.rule.definition;
+
+        let description_matches = match &self.description {
+            Some(DescriptionMatcher::Contains(needle)) => {
+                candidate.name.to_lowercase().contains(needle)
+            }
+            Some(DescriptionMatcher::Regex(regex)) => regex.is_match(candidate.name),
+            None => true,
+        };
+        let merchant_matches = rule
+            .merchant_entity_id
+            .as_deref()
+            .is_none_or(|id| candidate.merchant_entity_id == Some(id));
+        let account_matches = rule.account_id.is_none_or(|id| id == candidate.account_id);
+
+        let cents = (candidate.amount.abs() * Decimal::ONE_HUNDRED).round();
+        let above_min = rule
+            .min_amount_cents
+            .is_none_or(|min| cents >= Decimal::from(min));
+        let below_max = rule
+            .max_amount_cents
+            .is_none_or(|max| cents <= Decimal::from(max));
+        let sign_matches = match rule.amount_sign {
+            Some(AmountSign::Debit) => candidate.amount.is_sign_negative(),
+            Some(AmountSign::Credit) => candidate.amount.is_sign_positive(),
+            None => true,
+        };
+
+        description_matches
+            && merchant_matches
+            && account_matches
+            && above_min
+            && below_max
+            && sign_matches
+    }
+}
+
+/// Enabled rules in the order they are tried
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleSet {
+    /*
+    Prepares rules for matching, ordered by priority with older rules first among equals
+
+    Disabled rules are dropped, as are rules whose regex no longer compiles so that one bad
+    rule doesn't stop every other rule from applying
+    */
+    pub fn new(mut rules: Vec<CategorizationRule>) -> Self {
+        rules.sort_by(|a, b| {
+            b.definition
+                .priority
+                .cmp(&a.definition.priority)
+                .then(a.id.cmp(&b.id))
+        });
+
+        let rules = rules
+            .into_iter()
+            .filter(|rule| rule.definition.enabled)
+            .filter_map(|rule| {
+                let description = match &rule.definition.description_pattern {
+                    None => None,
+                    Some(pattern) => match rule.definition.description_match {
+                        DescriptionMatch::Contains => {
+                            Some(DescriptionMatcher::Contains(pattern.to_lowercase()))
+                        }
+                        DescriptionMatch::Regex => match compile_pattern(pattern) {
+                            Ok(regex) => Some(DescriptionMatcher::Regex(regex)),
+                            Err(e) => {
+                                eprintln!("Skipping rule {}: invalid regex: {e}", rule.id);
+                                return None;
+                            }
+                        },
+                    },
+                };
+                Some(CompiledRule { rule, description })
+            })
+            .collect();
+
+        RuleSet { rules }
+    }
+
+    /// The highest priority rule matching the candidate
+    pub fn matching_rule(&self, candidate: &RuleCandidate) -> Option<&CategorizationRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(candidate))
+            .map(|rule| &rule.rule)
+    }
+
+    pub fn categorize(&self, candidate: &RuleCandidate) -> Option<i64> {
+        self.matching_rule(candidate)
+            .map(|rule| rule.definition.category_id)
+    }
+}
+
+/// A stored transaction as seen when applying rules to past rows
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct RuleTarget {
+    pub id: i64,
+    pub name: String,
+    pub merchant_entity_id: Option<String>,
+    pub account_id: i64,
+    #[sqlx(rename = "amount_cents")]
+    pub amount: Cents,
+    pub date: NaiveDate,
+    pub category_id: i64,
+    pub category_name: String,
+}
+
+impl RuleTarget {
+    pub fn candidate(&self) -> RuleCandidate<'_> {
+        RuleCandidate {
+            name: &self.name,
+            merchant_entity_id: self.merchant_entity_id.as_deref(),
+            account_id: self.account_id,
+            amount: self.amount.0,
+        }
+    }
+}
+
+/// A transaction a rule moves, or would move in a dry run, to another category
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CategoryChange {
+    pub transaction_id: i64,
+    pub name: String,
+    pub date: NaiveDate,
+    pub from_category_id: i64,
+    pub from_category_name: String,
+    pub to_category_id: i64,
+    pub to_category_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn rule(id: i64, priority: i64, category_id: i64) -> CategorizationRule {
+        CategorizationRule {
+            id,
+            definition: RuleDefinition {
+                name: format!("rule {id}"),
+                priority,
+                enabled: true,
+                description_pattern: None,
+                description_match: DescriptionMatch::Contains,
+                merchant_entity_id: None,
+                account_id: None,
+                min_amount_cents: None,
+                max_amount_cents: None,
+                amount_sign: None,
+                category_id,
+            },
+        }
+    }
+
+    fn candidate(name: &str, amount: Decimal) -> RuleCandidate<'_> {
+        RuleCandidate {
+            name,
+            merchant_entity_id: None,
+            account_id: 1,
+            amount,
+        }
+    }
+
+    #[test]
+    fn matches_description_substring_and_regex_ignoring_case() {
+        let mut contains = rule(1, 0, 4);
+        contains.definition.description_pattern = Some("whole foods".to_owned());
+        let mut regex = rule(2, 0, 5);
+        regex.definition.description_pattern = Some(r"^tst\*".to_owned());
+        regex.definition.description_match = DescriptionMatch::Regex;
+        let rules = RuleSet::new(vec![contains, regex]);
+
+        assert_eq!(rules.categorize(&candidate("WHOLE FOODS #123", dec!(-50))), Some(4));
+        assert_eq!(rules.categorize(&candidate("TST*PIZZA", dec!(-10.14))), Some(5));
+        assert_eq!(rules.categorize(&candidate("PIZZA TST*", dec!(-10.14))), None);
+    }
+
+    #[test]
+    fn matches_amount_range_sign_account_and_merchant() {
+        let mut rent = rule(1, 0, 3);
+        rent.definition.min_amount_cents = Some(100000);
+        rent.definition.max_amount_cents = Some(200000);
+        rent.definition.amount_sign = Some(AmountSign::Debit);
+        rent.definition.account_id = Some(1);
+        let mut coffee = rule(2, 0, 5);
+        coffee.definition.merchant_entity_id = Some("merchant-1".to_owned());
+        let rules = RuleSet::new(vec![rent, coffee]);
+
+        assert_eq!(rules.categorize(&candidate("CHECK 1042", dec!(-1500))), Some(3));
+        assert_eq!(rules.categorize(&candidate("CHECK 1042", dec!(-2000.01))), None);
+        assert_eq!(rules.categorize(&candidate("REFUND", dec!(1500))), None);
+        assert_eq!(
+            rules.categorize(&RuleCandidate {
+                account_id: 2,
+                ..candidate("CHECK 1042", dec!(-1500))
+            }),
+            None
+        );
+        assert_eq!(
+            rules.categorize(&RuleCandidate {
+                merchant_entity_id: Some("merchant-1"),
+                ..candidate("SQ *CAFE", dec!(-4.50))
+            }),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn highest_priority_enabled_rule_wins() {
+        let mut low = rule(1, 1, 4);
+        low.definition.description_pattern = Some("market".to_owned());
+        let mut high = rule(2, 10, 5);
+        high.definition.description_pattern = Some("market".to_owned());
+        let mut disabled = rule(3, 20, 6);
+        disabled.definition.description_pattern = Some("market".to_owned());
+        disabled.definition.enabled = false;
+        let mut tie = rule(4, 10, 7);
+        tie.definition.description_pattern = Some("market".to_owned());
+
+        let rules = RuleSet::new(vec![low, disabled, tie, high]);
+
+        assert_eq!(
+            rules
+                .matching_rule(&candidate("FOOD MARKET", dec!(-7.25)))
+                .map(|r| r.id),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn validate_rejects_rules_that_cannot_match_sensibly() {
+        let mut no_condition = rule(1, 0, 4).definition;
+        assert!(no_condition.validate().is_err());
+
+        no_condition.description_pattern = Some("(unclosed".to_owned());
+        no_condition.description_match = DescriptionMatch::Regex;
+        assert!(no_condition.validate().unwrap_err().contains("Invalid regex"));
+
+        let mut inverted = rule(1, 0, 4).definition;
+        inverted.min_amount_cents = Some(500);
+        inverted.max_amount_cents = Some(100);
+        assert!(inverted.validate().is_err());
+    }
+}
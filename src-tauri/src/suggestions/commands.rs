@@ -0,0 +1,25 @@
+use crate::suggestions::queries;
+use crate::suggestions::types::CategorySuggestion;
+use crate::AppState;
+
+/*
+Suggests a category for an existing transaction from the user's past corrections
+
+Resolves to None when no correction resembles the transaction
+*/
+#[tauri::command]
+pub async fn suggest_transaction_category(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+) -> Result<Option<CategorySuggestion>, String> {
+    let mut conn = state
+        .db
+        .0
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {e}"))?;
+
+    queries::suggest_transaction_category(&mut conn, transaction_id)
+        .await
+        .map_err(|e| format!("Error suggesting a category for transaction {transaction_id}: {e}"))
+}
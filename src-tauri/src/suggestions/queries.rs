@@ -0,0 +1,107 @@
+use crate::payees;
+use crate::suggestions::types::{CategoryCorrection, CategoryModel, CategorySuggestion};
+use sqlx::SqliteConnection;
+
+/*
+Records that the user moved a transaction to `category_id`, before the move is written
+
+The transaction's description, merchant and current category are copied into the example
+*/
+pub async fn record_category_correction(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+    category_id: i64,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        INSERT INTO category_correction (
+            transaction_id,
+            description,
+            merchant_entity_id,
+            from_category_id,
+            category_id
+        )
+        SELECT id, name, merchant_entity_id, category_id, ?
+        FROM 'transaction'
+        WHERE id=?
+    "#;
+
+    let res = sqlx::query(query)
+        .bind(category_id)
+        .bind(transaction_id)
+        .execute(conn)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/// A model trained on every transaction's latest correction, so changes of mind don't linger
+pub async fn get_category_model(conn: &mut SqliteConnection) -> Result<CategoryModel, sqlx::Error> {
+    let query = r#"
+        SELECT description, merchant_entity_id, category_id
+        FROM category_correction c
+        WHERE transaction_id IS NULL
+            OR id = (
+                SELECT MAX(id) FROM category_correction WHERE transaction_id = c.transaction_id
+            )
+        ORDER BY id
+    "#;
+
+    let corrections: Vec<CategoryCorrection> =
+        sqlx::query_as(query).fetch_all(&mut *conn).await?;
+    let payees = payees::queries::get_payee_normalizer(conn).await?;
+
+    Ok(CategoryModel::new(corrections, payees))
+}
+
+pub async fn suggest_transaction_category(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+) -> Result<Option<CategorySuggestion>, sqlx::Error> {
+    let (name, merchant_entity_id): (String, Option<String>) =
+        sqlx::query_as("SELECT name, merchant_entity_id FROM 'transaction' WHERE id=?")
+            .bind(transaction_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    let model = get_category_model(conn).await?;
+
+    Ok(model.suggest(&name, merchant_entity_id.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn model_learns_latest_correction_per_transaction(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        record_category_correction(&mut conn, 1, 3).await?;
+        record_category_correction(&mut conn, 1, 4).await?;
+
+        let from: Vec<Option<i64>> =
+            sqlx::query_scalar("SELECT from_category_id FROM category_correction ORDER BY id")
+                .fetch_all(&pool)
+                .await?;
+        assert_eq!(from, vec![Some(1), Some(1)]);
+
+        let model = get_category_model(&mut conn).await?;
+        let suggestion = model.suggest("TRANSACTION 99", None).unwrap();
+        assert_eq!(suggestion.category_id, 4);
+        assert_eq!(
+            suggest_transaction_category(&mut conn, 2).await?.map(|s| s.category_id),
+            Some(4)
+        );
+        assert!(suggestion.is_confident());
+
+        assert!(matches!(
+            record_category_correction(&mut conn, 999, 4).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+}
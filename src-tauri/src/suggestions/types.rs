@@ -0,0 +1,308 @@
+use crate::payees::types::PayeeNormalizer;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Suggestions at or above this confidence are applied to new rows without asking
+pub const AUTO_APPLY_CONFIDENCE: f64 = 0.8;
+
+// Words banks add to descriptions that say nothing about where the money went
+const NOISE_TOKENS: [&str; 10] = [
+    "ach", "card", "debit", "credit", "pos", "purchase", "payment", "recurring", "online", "the",
+];
+
+/*
+Splits a payee name into the words that identify a merchant
+
+Names are lowercased and split on anything that isn't a letter or digit. Tokens holding
+digits are dropped since they are usually store numbers, dates or reference codes that differ
+between otherwise identical rows
+*/
+pub fn normalize_description(name: &str) -> BTreeSet<String> {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.len() > 1)
+        .filter(|token| !token.chars().any(|c| c.is_ascii_digit()))
+        .filter(|token| !NOISE_TOKENS.contains(token))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// A category the user picked by hand for a transaction
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct CategoryCorrection {
+    pub description: String,
+    pub merchant_entity_id: Option<String>,
+    pub category_id: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum SuggestionSource {
+    /// Earlier corrections of rows from the same Plaid merchant
+    Merchant,
+    /// Earlier corrections of rows with similar descriptions
+    Description,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct CategorySuggestion {
+    pub category_id: i64,
+    /// Between 0 and 1
+    pub confidence: f64,
+    pub source: SuggestionSource,
+}
+
+impl CategorySuggestion {
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= AUTO_APPLY_CONFIDENCE
+    }
+}
+
+#[derive(Default)]
+struct CategoryTokens {
+    examples: u32,
+    tokens: BTreeMap<String, u32>,
+    total_tokens: u32,
+}
+
+/*
+Naive Bayes model of past corrections, keyed by description tokens and merchant ids
+
+Descriptions are read through the payee normalizer first, so rows the user's payee rules name
+alike share their tokens. Everything is counted in ordered maps so the same corrections always
+give the same suggestions, with ties going to the lower category id
+*/
+pub struct CategoryModel {
+    payees: PayeeNormalizer,
+    examples: u32,
+    categories: BTreeMap<i64, CategoryTokens>,
+    vocabulary: BTreeSet<String>,
+    merchants: BTreeMap<String, BTreeMap<i64, u32>>,
+}
+
+impl CategoryModel {
+    pub fn new(corrections: Vec<CategoryCorrection>, payees: PayeeNormalizer) -> Self {
+        let mut model = CategoryModel {
+            payees,
+            examples: 0,
+            categories: BTreeMap::new(),
+            vocabulary: BTreeSet::new(),
+            merchants: BTreeMap::new(),
+        };
+        for correction in corrections {
+            model.learn(correction);
+        }
+        model
+    }
+
+    fn tokens(&self, name: &str) -> BTreeSet<String> {
+        normalize_description(&self.payees.normalize(name))
+    }
+
+    fn learn(&mut self, correction: CategoryCorrection) {
+        if let Some(merchant) = correction.merchant_entity_id {
+            *self
+                .merchants
+                .entry(merchant)
+                .or_default()
+                .entry(correction.category_id)
+                .or_default() += 1;
+        }
+
+        let tokens = self.tokens(&correction.description);
+        if tokens.is_empty() {
+            return;
+        }
+        self.examples += 1;
+        let category = self.categories.entry(correction.category_id).or_default();
+        category.examples += 1;
+        for token in tokens {
+            category.total_tokens += 1;
+            *category.tokens.entry(token.clone()).or_default() += 1;
+            self.vocabulary.insert(token);
+        }
+    }
+
+    /*
+    The most likely category for a row, if any correction resembles it
+
+    A merchant the user has corrected before decides on its own. Its confidence is the share of
+    its corrections that went to the winning category, counted with one extra correction
+    against it so a single example can't be applied without asking. Otherwise the description is
+    scored with Laplace smoothing, and the winning posterior is scaled by how many of the
+    row's tokens the model has seen so one shared word can't carry a confident match
+    */
+    pub fn suggest(
+        &self,
+        name: &str,
+        merchant_entity_id: Option<&str>,
+    ) -> Option<CategorySuggestion> {
+        if let Some(counts) = merchant_entity_id.and_then(|id| self.merchants.get(id)) {
+            let total: u32 = counts.values().sum();
+            let (&category_id, &count) = counts
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))?;
+            return Some(CategorySuggestion {
+                category_id,
+                confidence: f64::from(count) / f64::from(total + 1),
+                source: SuggestionSource::Merchant,
+            });
+        }
+
+        let tokens = self.tokens(name);
+        let known: Vec<&String> = tokens
+            .iter()
+            .filter(|token| self.vocabulary.contains(*token))
+            .collect();
+        if known.is_empty() {
+            return None;
+        }
+
+        let vocabulary_size = self.vocabulary.len() as f64;
+        let scores: Vec<(i64, f64)> = self
+            .categories
+            .iter()
+            .map(|(&category_id, category)| {
+                let prior = (f64::from(category.examples) / f64::from(self.examples)).ln();
+                let likelihood: f64 = known
+                    .iter()
+                    .map(|token| {
+                        let count = category.tokens.get(*token).copied().unwrap_or(0);
+                        ((f64::from(count) + 1.0)
+                            / (f64::from(category.total_tokens) + vocabulary_size))
+                            .ln()
+                    })
+                    .sum();
+                (category_id, prior + likelihood)
+            })
+            .collect();
+
+        let best = scores
+            .iter()
+            .copied()
+            .fold(None, |best: Option<(i64, f64)>, score| match best {
+                Some(best) if best.1 >= score.1 => Some(best),
+                _ => Some(score),
+            })?;
+        let evidence: f64 = scores.iter().map(|(_, score)| (score - best.1).exp()).sum();
+        let coverage = known.len() as f64 / tokens.len() as f64;
+
+        Some(CategorySuggestion {
+            category_id: best.0,
+            confidence: coverage / evidence,
+            source: SuggestionSource::Description,
+        })
+    }
+
+    /// The category to file a new row under without asking, when the model is confident
+    pub fn auto_category(&self, name: &str, merchant_entity_id: Option<&str>) -> Option<i64> {
+        self.suggest(name, merchant_entity_id)
+            .filter(CategorySuggestion::is_confident)
+            .map(|suggestion| suggestion.category_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payees::types::{PayeeRule, PayeeRuleDefinition};
+    use crate::rules::types::DescriptionMatch;
+
+    fn correction(description: &str, merchant: Option<&str>, category_id: i64) -> CategoryCorrection {
+        CategoryCorrection {
+            description: description.to_owned(),
+            merchant_entity_id: merchant.map(str::to_owned),
+            category_id,
+        }
+    }
+
+    fn train(corrections: Vec<CategoryCorrection>) -> CategoryModel {
+        CategoryModel::new(corrections, PayeeNormalizer::new(vec![]))
+    }
+
+    #[test]
+    fn normalize_drops_store_numbers_and_noise() {
+        assert_eq!(
+            normalize_description("POS PURCHASE STARBUCKS #1234 SEATTLE 09/14"),
+            BTreeSet::from(["starbucks".to_owned(), "seattle".to_owned()])
+        );
+    }
+
+    #[test]
+    fn suggests_from_similar_descriptions() {
+        let model = train(vec![
+            correction("STARBUCKS #1234 SEATTLE", None, 5),
+            correction("STARBUCKS #88 PORTLAND", None, 5),
+            correction("SAFEWAY #12 SEATTLE", None, 4),
+        ]);
+
+        let suggestion = model.suggest("STARBUCKS #999 PORTLAND", None).unwrap();
+        assert_eq!(suggestion.category_id, 5);
+        assert_eq!(suggestion.source, SuggestionSource::Description);
+        assert!(suggestion.is_confident());
+
+        // Only one of the two words has been seen, so the match is not trusted
+        let partial = model.suggest("SEATTLE PARKING", None).unwrap();
+        assert!(!partial.is_confident());
+        assert_eq!(model.auto_category("SEATTLE PARKING", None), None);
+
+        assert_eq!(model.suggest("UNSEEN MERCHANT", None), None);
+    }
+
+    #[test]
+    fn merchant_id_outweighs_description() {
+        let model = train(vec![
+            correction("SQ *BLUE BOTTLE", Some("merchant-1"), 5),
+            correction("SQ *BLUE BOTTLE BEANS", Some("merchant-1"), 5),
+            correction("SQ *BLUE BOTTLE GIFT", Some("merchant-1"), 11),
+        ]);
+
+        let suggestion = model.suggest("SOMETHING ELSE", Some("merchant-1")).unwrap();
+        assert_eq!(suggestion.category_id, 5);
+        assert_eq!(suggestion.source, SuggestionSource::Merchant);
+        assert!((suggestion.confidence - 2.0 / 4.0).abs() < 1e-9);
+        assert!(!suggestion.is_confident());
+    }
+
+    #[test]
+    fn merchants_need_several_corrections_to_be_trusted() {
+        let once = train(vec![correction("BLUE BOTTLE", Some("merchant-1"), 5)]);
+        let suggestion = once.suggest("BLUE BOTTLE", Some("merchant-1")).unwrap();
+        assert_eq!(suggestion.confidence, 0.5);
+        assert_eq!(once.auto_category("BLUE BOTTLE", Some("merchant-1")), None);
+
+        let often = train(vec![correction("BLUE BOTTLE", Some("merchant-1"), 5); 4]);
+        assert_eq!(often.auto_category("BLUE BOTTLE", Some("merchant-1")), Some(5));
+    }
+
+    #[test]
+    fn payee_rules_feed_suggestions() {
+        let corrections = vec![correction("AMZN MKTP US*2K3AB1", None, 5)];
+        let amazon = PayeeRule {
+            id: 1,
+            definition: PayeeRuleDefinition {
+                pattern: "amzn".to_owned(),
+                description_match: DescriptionMatch::Contains,
+                payee: "Amazon".to_owned(),
+            },
+        };
+
+        let without_rule = train(corrections.clone());
+        assert_eq!(without_rule.auto_category("AMZN DIGITAL", None), None);
+
+        let with_rule = CategoryModel::new(corrections, PayeeNormalizer::new(vec![amazon]));
+        assert_eq!(with_rule.auto_category("AMZN DIGITAL", None), Some(5));
+    }
+
+    #[test]
+    fn suggestions_are_deterministic() {
+        let corrections = vec![
+            correction("CORNER STORE", None, 4),
+            correction("CORNER STORE", None, 11),
+        ];
+        let first = train(corrections.clone()).suggest("CORNER STORE", None);
+        let second = train(corrections).suggest("CORNER STORE", None);
+
+        assert_eq!(first, second);
+        assert_eq!(first.map(|s| s.category_id), Some(4));
+        assert_eq!(first.map(|s| s.confidence), Some(0.5));
+    }
+}
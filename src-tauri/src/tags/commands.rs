@@ -0,0 +1,120 @@
+use crate::tags::queries;
+use crate::tags::types::{validate_tag_name, Tag, TagSpending};
+use crate::AppState;
+use chrono::NaiveDate;
+
+// Tag names are unique, so a clash gets a message the user can act on
+fn tag_error(e: sqlx::Error, name: &str) -> String {
+    match e {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            format!("A tag named {name} already exists")
+        }
+        e => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_tags(state: tauri::State<'_, AppState>) -> Result<Vec<Tag>, String> {
+    queries::get_tags(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_tag(state: tauri::State<'_, AppState>, name: String) -> Result<i64, String> {
+    let name = validate_tag_name(&name)?;
+    queries::create_tag(&state.db.0, name)
+        .await
+        .map_err(|e| tag_error(e, name))
+}
+
+#[tauri::command]
+pub async fn rename_tag(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    name: String,
+) -> Result<(), String> {
+    let name = validate_tag_name(&name)?;
+    queries::rename_tag(&state.db.0, id, name)
+        .await
+        .map_err(|e| tag_error(e, name))
+}
+
+#[tauri::command]
+pub async fn delete_tag(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    queries::delete_tag(&state.db.0, id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Folds one tag into another, keeping the target's name
+#[tauri::command]
+pub async fn merge_tags(
+    state: tauri::State<'_, AppState>,
+    source_id: i64,
+    target_id: i64,
+) -> Result<(), String> {
+    if source_id == target_id {
+        return Err("Can't merge a tag into itself".to_string());
+    }
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    queries::merge_tags(&mut tx, source_id, target_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => "Both tags must exist to merge them".to_string(),
+            e => format!("Failed to merge tags: {e}"),
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_transaction_tags(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+) -> Result<Vec<Tag>, String> {
+    queries::get_transaction_tags(&state.db.0, transaction_id)
+        .await
+        .map_err(|e| format!("Error fetching tags of transaction {transaction_id}: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_transaction_tags(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+    tag_ids: Vec<i64>,
+) -> Result<(), String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    queries::set_transaction_tags(&mut tx, transaction_id, &tag_ids)
+        .await
+        .map_err(|e| format!("Error tagging transaction {transaction_id}: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_tag_spending(
+    state: tauri::State<'_, AppState>,
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+) -> Result<Vec<TagSpending>, String> {
+    queries::get_tag_spending(&state.db.0, date_from, date_to)
+        .await
+        .map_err(|e| format!("Error totalling spending per tag: {e}"))
+}
@@ -0,0 +1,297 @@
+use crate::bulk::types::BulkAction;
+use crate::tags::types::{Tag, TagSpending};
+use chrono::NaiveDate;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+pub async fn get_tags(pool: &Pool<Sqlite>) -> Result<Vec<Tag>, sqlx::Error> {
+    let tags: Vec<Tag> = sqlx::query_as("SELECT id, name FROM tag ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(tags)
+}
+
+pub async fn create_tag(pool: &Pool<Sqlite>, name: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("INSERT INTO tag (name) VALUES (?) RETURNING id")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn rename_tag(pool: &Pool<Sqlite>, id: i64, name: &str) -> Result<(), sqlx::Error> {
+    let res = sqlx::query("UPDATE tag SET name=? WHERE id=?")
+        .bind(name)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/// Deleting a tag removes it from every transaction
+pub async fn delete_tag(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM tag WHERE id=?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/*
+Moves every transaction tagged with `source_id` over to `target_id` and deletes the source
+
+Bulk operations on the source now refer to the target so they can still be undone. Rows that
+held both tags are dropped from operations that added either one, as undoing those would take
+the merged tag off rows that still had the other
+*/
+pub async fn merge_tags(
+    conn: &mut SqliteConnection,
+    source_id: i64,
+    target_id: i64,
+) -> Result<(), sqlx::Error> {
+    let target_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tag WHERE id=?)")
+        .bind(target_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !target_exists {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    let query = r#"
+        DELETE FROM bulk_operation_change
+        WHERE operation_id IN (
+                SELECT id FROM bulk_operation WHERE action=$3 AND tag_id IN ($1, $2)
+            )
+            AND transaction_id IN (
+                SELECT transaction_id FROM transaction_tag WHERE tag_id=$1
+                INTERSECT
+                SELECT transaction_id FROM transaction_tag WHERE tag_id=$2
+            )
+    "#;
+    sqlx::query(query)
+        .bind(target_id)
+        .bind(source_id)
+        .bind(BulkAction::AddTag)
+        .execute(&mut *conn)
+        .await?;
+
+    let query = r#"
+        INSERT OR IGNORE INTO transaction_tag (transaction_id, tag_id)
+        SELECT transaction_id, $1 FROM transaction_tag WHERE tag_id=$2
+    "#;
+    sqlx::query(query)
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE bulk_operation SET tag_id=$1 WHERE tag_id=$2")
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let res = sqlx::query("DELETE FROM tag WHERE id=?")
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+pub async fn get_transaction_tags(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+) -> Result<Vec<Tag>, sqlx::Error> {
+    let query = r#"
+        SELECT tg.id, tg.name
+        FROM transaction_tag tt
+        JOIN tag tg ON tg.id = tt.tag_id
+        WHERE tt.transaction_id=?
+        ORDER BY tg.name
+    "#;
+
+    let tags: Vec<Tag> = sqlx::query_as(query)
+        .bind(transaction_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(tags)
+}
+
+/// Replaces the tags on a transaction
+pub async fn set_transaction_tags(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+    tag_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_tag WHERE transaction_id=?")
+        .bind(transaction_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for tag_id in tag_ids {
+        sqlx::query("INSERT OR IGNORE INTO transaction_tag (transaction_id, tag_id) VALUES (?, ?)")
+            .bind(transaction_id)
+            .bind(tag_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/*
+Spending per tag between two dates, inclusive, with every tag listed even if unused
+
+Transfers between the user's accounts aren't spending and are left out
+*/
+pub async fn get_tag_spending(
+    pool: &Pool<Sqlite>,
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+) -> Result<Vec<TagSpending>, sqlx::Error> {
+    let query = r#"
+        SELECT
+            tg.id AS tag_id,
+            tg.name AS name,
+            COALESCE(-SUM(t.amount_cents), 0) AS spent_cents,
+            COUNT(t.id) AS transaction_count
+        FROM tag tg
+        LEFT JOIN transaction_tag tt ON tt.tag_id = tg.id
+        LEFT JOIN "transaction" t ON t.id = tt.transaction_id
+            AND t.deleted_at IS NULL
+            AND t.date >= $1
+            AND t.date <= $2
+            AND t.id NOT IN (SELECT transaction_id FROM transfer_transaction)
+        GROUP BY tg.id
+        ORDER BY tg.name
+    "#;
+
+    let spending: Vec<TagSpending> = sqlx::query_as(query)
+        .bind(date_from)
+        .bind(date_to)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(spending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk;
+    use crate::bulk::types::{BulkChange, BulkSelection};
+    use crate::transactions;
+    use crate::transactions::types::TransactionFilter;
+
+    fn tag_names(tags: &[Tag]) -> Vec<&str> {
+        tags.iter().map(|tag| tag.name.as_str()).collect()
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn tags_are_renamed_merged_and_deleted(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let vacation = create_tag(&pool, "vacation").await?;
+        let trip = create_tag(&pool, "trip-2026").await?;
+        let work = create_tag(&pool, "reimbursable").await?;
+        assert!(create_tag(&pool, "Vacation").await.is_err(), "names ignore case");
+
+        set_transaction_tags(&mut conn, 1, &[vacation, work]).await?;
+        set_transaction_tags(&mut conn, 2, &[trip]).await?;
+        set_transaction_tags(&mut conn, 3, &[vacation, trip]).await?;
+
+        rename_tag(&pool, vacation, "vacation-2026").await?;
+        merge_tags(&mut conn, trip, vacation).await?;
+
+        assert_eq!(tag_names(&get_tags(&pool).await?), vec!["reimbursable", "vacation-2026"]);
+        for id in [1, 2, 3] {
+            assert!(get_transaction_tags(&pool, id).await?.iter().any(|tag| tag.id == vacation));
+        }
+
+        let filter = TransactionFilter {
+            tag_ids: vec![work],
+            ..Default::default()
+        };
+        assert_eq!(transactions::queries::get_num_transactions(&pool, &filter).await?, 1);
+
+        delete_tag(&pool, work).await?;
+        assert_eq!(tag_names(&get_transaction_tags(&pool, 1).await?), vec!["vacation-2026"]);
+        assert!(matches!(
+            merge_tags(&mut conn, vacation, work).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn undoing_after_a_merge_keeps_tags_rows_already_had(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let vacation = create_tag(&pool, "vacation").await?;
+        let trip = create_tag(&pool, "trip-2026").await?;
+        set_transaction_tags(&mut conn, 1, &[vacation]).await?;
+
+        let add_trip = BulkChange {
+            action: BulkAction::AddTag,
+            category_id: None,
+            tag_id: Some(trip),
+        };
+        let selection = BulkSelection::Ids(vec![1, 2]);
+        let operation = bulk::queries::apply_bulk_operation(&mut conn, &selection, &add_trip).await?;
+        merge_tags(&mut conn, trip, vacation).await?;
+
+        // Row 1 was tagged vacation before the merge, only row 2 got it from the operation
+        assert_eq!(bulk::queries::undo_bulk_operation(&mut conn, operation.id).await?, 1);
+        assert_eq!(tag_names(&get_transaction_tags(&pool, 1).await?), vec!["vacation"]);
+        assert!(get_transaction_tags(&pool, 2).await?.is_empty());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn spending_per_tag_covers_the_date_range(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let vacation = create_tag(&pool, "vacation").await?;
+        create_tag(&pool, "unused").await?;
+        // -5.77 on the 15th, -10.90 and -0.70 on the 16th, -1.90 on the 17th
+        for id in [1, 2, 3, 4] {
+            set_transaction_tags(&mut conn, id, &[vacation]).await?;
+        }
+        transactions::queries::set_transaction_deleted(&pool, 4, true).await?;
+
+        let spending = get_tag_spending(
+            &pool,
+            NaiveDate::from_ymd_opt(2025, 12, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 16).unwrap(),
+        )
+        .await?;
+        assert_eq!(
+            spending,
+            vec![
+                TagSpending {
+                    tag_id: 2,
+                    name: "unused".to_owned(),
+                    spent_cents: 0,
+                    transaction_count: 0,
+                },
+                TagSpending {
+                    tag_id: vacation,
+                    name: "vacation".to_owned(),
+                    spent_cents: 1667,
+                    transaction_count: 2,
+                },
+            ]
+        );
+        Ok(())
+    }
+}
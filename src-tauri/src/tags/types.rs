@@ -0,0 +1,23 @@
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Tags are compared ignoring case, so only surrounding whitespace is trimmed
+pub fn validate_tag_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name is required".to_string());
+    }
+    Ok(name)
+}
+
+/// Net spending on a tag's transactions, positive when more went out than came in
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct TagSpending {
+    pub tag_id: i64,
+    pub name: String,
+    pub spent_cents: i64,
+    pub transaction_count: i64,
+}
@@ -1,5 +1,9 @@
+use crate::transactions::types::{
+    validate_splits, PageCursor, PageDirection, SortColumn, SplitLine, TransactionFilter,
+    TransactionInput, TransactionPage, TransactionSplit,
+};
 use crate::types::{SortDir, TransactionWithAccount};
-use crate::{AppState, transactions};
+use crate::{AppState, categories, suggestions, transactions};
 
 #[derive(serde::Serialize)]
 pub struct PaginatedSortedTransactionsResponse {
@@ -17,15 +21,17 @@ pub async fn get_paginated_sorted_transactions(
     page: i64,
     page_size: i64,
     sort_col: Option<String>,
-    sort_dir: Option<SortDir>
+    sort_dir: Option<SortDir>,
+    filter: Option<TransactionFilter>,
 ) -> Result<PaginatedSortedTransactionsResponse, String> {
     let db = &state.db;
+    let filter = filter.unwrap_or_default();
 
-    let res = transactions::queries::get_paginated_sorted_transactions(&db.0, &page, &page_size, &sort_col, &sort_dir)
+    let res = transactions::queries::get_paginated_sorted_transactions(&db.0, &page, &page_size, &sort_col, &sort_dir, &filter)
          .await
          .map_err(|e| format!("Error getting paginated transactions {e}"))?;
 
-    let num_transactions = transactions::queries::get_num_transactions(&db.0)
+    let num_transactions = transactions::queries::get_num_transactions(&db.0, &filter)
         .await
         .map_err(|e| format!("Error getting num transactions: {e}"))?;
     // Division with ceiling, doesn't handle negatives properly but we shouldn't see negative
@@ -45,13 +51,208 @@ pub async fn get_paginated_sorted_transactions(
     Ok(out)
 }
 
+/*
+Cursor-based alternative to `get_paginated_sorted_transactions`
+
+Pass the `next_cursor` or `prev_cursor` of the page being shown to move forwards or back, or
+no cursor for the first page. Pages stay consistent while new transactions are synced in
+*/
+#[tauri::command]
+pub async fn get_transactions_page(
+    state: tauri::State<'_, AppState>,
+    cursor: Option<String>,
+    direction: Option<PageDirection>,
+    page_size: i64,
+    sort_col: Option<String>,
+    sort_dir: Option<SortDir>,
+    filter: Option<TransactionFilter>,
+) -> Result<TransactionPage, String> {
+    let db = &state.db;
+    let filter = filter.unwrap_or_default();
+    let sort_col = sort_col.as_deref().map(SortColumn::parse).transpose()?;
+    let sort_dir = sort_dir.unwrap_or(SortDir::Asc);
+    let page_size = page_size.max(1);
+
+    let cursor = cursor.as_deref().map(PageCursor::decode).transpose()?;
+    if let Some(cursor) = &cursor {
+        if cursor.sort_col != sort_col || cursor.sort_dir != sort_dir {
+            return Err("Page cursor was made for a different sort order".to_string());
+        }
+    }
+    // Without a cursor there is nothing to go back from, so it's the first page
+    let direction = match cursor {
+        Some(_) => direction.unwrap_or_default(),
+        None => PageDirection::Next,
+    };
+
+    transactions::queries::get_transactions_page(
+        &db.0,
+        sort_col,
+        sort_dir,
+        cursor.as_ref(),
+        direction,
+        page_size,
+        &filter,
+    )
+    .await
+    .map_err(|e| format!("Error getting transactions page: {e}"))
+}
+
+/*
+Moves a transaction to another category by hand
+
+The change is also recorded as an example for category suggestions
+*/
 #[tauri::command]
 pub async fn update_transaction_category(
     state: tauri::State<'_, AppState>,
     transaction_id: i64,
     category_id: i64,
 ) -> Result<(), String> {
-    transactions::queries::update_transaction_category(&state.db.0, transaction_id, category_id)
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    suggestions::queries::record_category_correction(&mut tx, transaction_id, category_id)
+        .await
+        .map_err(|e| format!("Error recording category correction: {e}"))?;
+    transactions::queries::update_transaction_category(&mut *tx, transaction_id, category_id)
+        .await
+        .map_err(|e| format!("Error updating transaction category: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_transaction_splits(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+) -> Result<Vec<TransactionSplit>, String> {
+    transactions::queries::get_transaction_splits(&state.db.0, transaction_id)
+        .await
+        .map_err(|e| format!("Error fetching splits of transaction {transaction_id}: {e}"))
+}
+
+/*
+Divides a transaction across categories, replacing any split it already had
+
+The lines must add up to the transaction's amount. An empty list removes the split
+*/
+#[tauri::command]
+pub async fn set_transaction_splits(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+    splits: Vec<SplitLine>,
+) -> Result<(), String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let amount_cents = transactions::queries::get_transaction_amount_cents(&mut tx, transaction_id)
+        .await
+        .map_err(|e| format!("Error fetching transaction {transaction_id}: {e}"))?;
+    validate_splits(amount_cents, &splits)?;
+
+    transactions::queries::set_transaction_splits(&mut tx, transaction_id, &splits)
+        .await
+        .map_err(|e| format!("Error saving splits: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
+
+/*
+Adds a transaction by hand, such as a cash purchase, and returns its id
+
+Without a category the transaction is filed the same way synced ones are, by rules and past
+corrections, falling back to Uncategorized
+*/
+#[tauri::command]
+pub async fn create_transaction(
+    state: tauri::State<'_, AppState>,
+    transaction: TransactionInput,
+    category_id: Option<i64>,
+) -> Result<i64, String> {
+    transaction.validate()?;
+    let db = &state.db;
+
+    let uncategorized = categories::queries::get_uncategorized_category(&db.0)
+        .await
+        .map_err(|e| format!("Error fetching uncategorized category: {e}"))?;
+
+    let mut conn =
+        db.0.acquire()
+            .await
+            .map_err(|e| format!("Failed to acquire connection: {e}"))?;
+    transactions::queries::create_manual_transaction(
+        &mut conn,
+        &transaction,
+        category_id,
+        uncategorized.id(),
+    )
+    .await
+    .map_err(|e| format!("Error creating transaction: {e}"))
+}
+
+/*
+Changes a transaction's name, amount, date and account
+
+Synced transactions can't change account, and their other edits are kept as overrides that
+later syncs leave alone. Changing the amount removes any split
+*/
+#[tauri::command]
+pub async fn update_transaction(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+    transaction: TransactionInput,
+) -> Result<(), String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let existing = transactions::queries::get_transaction(&mut tx, transaction_id)
+        .await
+        .map_err(|e| format!("Error fetching transaction {transaction_id}: {e}"))?;
+    transaction.validate_edit(&existing)?;
+
+    transactions::queries::update_transaction(&mut tx, transaction_id, &transaction)
+        .await
+        .map_err(|e| format!("Error updating transaction {transaction_id}: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))
+}
+
+/// Soft-deletes a transaction so it can be restored later
+#[tauri::command]
+pub async fn delete_transaction(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+) -> Result<(), String> {
+    transactions::queries::set_transaction_deleted(&state.db.0, transaction_id, true)
+        .await
+        .map_err(|e| format!("Error deleting transaction {transaction_id}: {e}"))
+}
+
+#[tauri::command]
+pub async fn restore_transaction(
+    state: tauri::State<'_, AppState>,
+    transaction_id: i64,
+) -> Result<(), String> {
+    transactions::queries::set_transaction_deleted(&state.db.0, transaction_id, false)
         .await
-        .map_err(|e| format!("Error updating transaction category: {e}"))
+        .map_err(|e| format!("Error restoring transaction {transaction_id}: {e}"))
 }
@@ -1,13 +1,27 @@
+use crate::categories;
+use crate::importers;
+use crate::payees;
+use crate::payees::types::PayeeScope;
+use crate::rules;
+use crate::rules::types::RuleCandidate;
+use crate::suggestions;
+use crate::transfers;
+use crate::transactions::types::{
+    primary_split_category, validate_splits, PageCursor, PageDirection, SortColumn, SortKey,
+    SplitLine, TransactionFilter, TransactionInput, TransactionPage, TransactionSplit,
+};
+use crate::importers::types::{import_fingerprints, ImportSummary, ImportedRow, TransactionImport};
+use crate::plaid;
 use crate::{plaid::types::PlaidTransaction, types::SortDir};
-use crate::types::{Transaction, TransactionWithAccount};
+use crate::types::{Cents, Transaction, TransactionWithAccount};
 use ::plaid::model::RemovedTransaction;
-use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection, SqliteExecutor};
 
 pub async fn get_transactions(
     pool: &Pool<Sqlite>,
     limit: Option<i64>,
 ) -> Result<Vec<Transaction>, sqlx::Error> {
-    let query = "SELECT id, plaid_transaction_id, name, merchant_entity_id, amount_cents, date, pending, deleted_at, account_id, category_id FROM 'transaction' WHERE deleted_at IS NULL ORDER BY date, id LIMIT $1";
+    let query = "SELECT id, plaid_transaction_id, name, merchant_entity_id, amount_cents, date, pending, deleted_at, account_id, category_id, plaid_category_primary, plaid_category_detailed, payee, merchant_id FROM 'transaction' WHERE deleted_at IS NULL ORDER BY date, id LIMIT $1";
 
     // Negative value returns all rows
     let lim = limit.unwrap_or(-1);
@@ -16,57 +30,133 @@ pub async fn get_transactions(
     Ok(res)
 }
 
+// Appends the filter's conditions to a query over `'transaction' t` that already has a WHERE
+pub fn push_transaction_filter(
+    query_builder: &mut QueryBuilder<'_, Sqlite>,
+    filter: &TransactionFilter,
+) {
+    if let Some(date_from) = filter.date_from {
+        query_builder.push(" AND t.date >= ").push_bind(date_from);
+    }
+    if let Some(date_to) = filter.date_to {
+        query_builder.push(" AND t.date <= ").push_bind(date_to);
+    }
+    if !filter.account_ids.is_empty() {
+        query_builder.push(" AND t.account_id IN (");
+        let mut ids = query_builder.separated(", ");
+        for id in &filter.account_ids {
+            ids.push_bind(*id);
+        }
+        query_builder.push(")");
+    }
+    if !filter.category_ids.is_empty() {
+        query_builder.push(" AND (t.category_id IN (");
+        let mut ids = query_builder.separated(", ");
+        for id in &filter.category_ids {
+            ids.push_bind(*id);
+        }
+        query_builder.push(
+            ") OR EXISTS (SELECT 1 FROM transaction_split s \
+            WHERE s.transaction_id = t.id AND s.category_id IN (",
+        );
+        let mut ids = query_builder.separated(", ");
+        for id in &filter.category_ids {
+            ids.push_bind(*id);
+        }
+        query_builder.push(")))");
+    }
+    if !filter.tag_ids.is_empty() {
+        query_builder.push(
+            " AND EXISTS (SELECT 1 FROM transaction_tag tt \
+            WHERE tt.transaction_id = t.id AND tt.tag_id IN (",
+        );
+        let mut ids = query_builder.separated(", ");
+        for id in &filter.tag_ids {
+            ids.push_bind(*id);
+        }
+        query_builder.push("))");
+    }
+    if !filter.merchant_ids.is_empty() {
+        query_builder.push(" AND t.merchant_id IN (");
+        let mut ids = query_builder.separated(", ");
+        for id in &filter.merchant_ids {
+            ids.push_bind(*id);
+        }
+        query_builder.push(")");
+    }
+    if let Some(min) = filter.min_amount_cents {
+        query_builder.push(" AND t.amount_cents >= ").push_bind(min);
+    }
+    if let Some(max) = filter.max_amount_cents {
+        query_builder.push(" AND t.amount_cents <= ").push_bind(max);
+    }
+    if let Some(pending) = filter.pending {
+        query_builder.push(" AND t.pending = ").push_bind(pending);
+    }
+    if let Some(search) = filter.search_query() {
+        query_builder
+            .push(" AND t.id IN (SELECT rowid FROM transaction_fts WHERE transaction_fts MATCH ")
+            .push_bind(search)
+            .push(")");
+    }
+}
+
 pub async fn get_num_transactions(
-    pool: &Pool<Sqlite>
+    pool: &Pool<Sqlite>,
+    filter: &TransactionFilter,
 ) -> Result<i64, sqlx::Error> {
-    let res: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM 'transaction' WHERE deleted_at IS NULL")
-        .fetch_one(pool)
-        .await?;
+    let mut query_builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT COUNT(*) FROM 'transaction' t WHERE t.deleted_at IS NULL");
+    push_transaction_filter(&mut query_builder, filter);
+
+    let res: i64 = query_builder.build_query_scalar().fetch_one(pool).await?;
 
     Ok(res)
 }
 
 
+const TRANSACTION_WITH_ACCOUNT_QUERY: &str = r#"
+    SELECT
+        t.id,
+        t.plaid_transaction_id,
+        t.name,
+        t.merchant_entity_id,
+        t.amount_cents,
+        t.date,
+        t.pending,
+        t.deleted_at,
+        t.account_id,
+        t.category_id,
+        t.plaid_category_primary,
+        t.plaid_category_detailed,
+        t.payee,
+        t.merchant_id,
+        a.name AS account_name,
+        c.name AS category_name,
+        c.color AS category_color,
+        c.icon AS category_icon
+    FROM 'transaction' t
+    JOIN account a ON t.account_id=a.id
+    JOIN category c ON t.category_id=c.id
+    WHERE t.deleted_at IS NULL
+"#;
+
 pub async fn get_paginated_sorted_transactions(
     pool: &Pool<Sqlite>,
     page: &i64,
     page_size: &i64,
     sort_col: &Option<String>,
-    sort_dir: &Option<SortDir>
+    sort_dir: &Option<SortDir>,
+    filter: &TransactionFilter,
 ) -> Result<Vec<TransactionWithAccount>, sqlx::Error> {
     let offset = std::cmp::max(page - 1, 0) * page_size;
 
-    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(r#"
-        SELECT
-            t.id,
-            t.plaid_transaction_id,
-            t.name,
-            t.merchant_entity_id,
-            t.amount_cents,
-            t.date,
-            t.pending,
-            t.deleted_at,
-            t.account_id,
-            t.category_id,
-            a.name AS account_name,
-            c.name AS category_name,
-            c.color AS category_color,
-            c.icon AS category_icon
-        FROM 'transaction' t
-        JOIN account a ON t.account_id=a.id
-        JOIN category c ON t.category_id=c.id
-        WHERE t.deleted_at IS NULL
-    "#);
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(TRANSACTION_WITH_ACCOUNT_QUERY);
+    push_transaction_filter(&mut query_builder, filter);
 
     query_builder.push(" ORDER BY ");
     if let Some(col) = sort_col.as_deref() {
-        let sort_col_final = match col {
-            "date" => "t.date",
-            "account" => "a.name",
-            "name" => "t.name",
-            "amount" => "t.amount_cents",
-            _ => return Err(sqlx::Error::Protocol("Invalid sort column".into()))
-        };
+        let sort_col_final = SortColumn::parse(col).map_err(sqlx::Error::Protocol)?.sql();
 
         let sort_dir_final = match sort_dir.unwrap_or(SortDir::Asc) {
             SortDir::Asc => "ASC",
@@ -93,6 +183,128 @@ pub async fn get_paginated_sorted_transactions(
     Ok(transactions)
 }
 
+/*
+Fetches up to `limit` transactions on one side of `cursor`, in display order
+
+Rows are ordered by the sort column and then id, both in the same direction, and compared
+with the cursor as a pair so paging stays fast on deep pages and rows inserted mid-way don't
+shift what the next page returns. Without a cursor this is the first page
+*/
+async fn get_transactions_beyond(
+    pool: &Pool<Sqlite>,
+    sort_col: Option<SortColumn>,
+    sort_dir: SortDir,
+    cursor: Option<&PageCursor>,
+    direction: PageDirection,
+    limit: i64,
+    filter: &TransactionFilter,
+) -> Result<Vec<TransactionWithAccount>, sqlx::Error> {
+    // Walking backwards flips the order, and the rows are put back the right way round below
+    let ascending = match direction {
+        PageDirection::Next => sort_dir == SortDir::Asc,
+        PageDirection::Prev => sort_dir == SortDir::Desc,
+    };
+    let (comparison, order) = if ascending { (" > ", " ASC") } else { (" < ", " DESC") };
+
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(TRANSACTION_WITH_ACCOUNT_QUERY);
+    push_transaction_filter(&mut query_builder, filter);
+
+    if let Some(cursor) = cursor {
+        query_builder.push(" AND (");
+        if let Some(col) = sort_col {
+            query_builder.push(col.sql()).push(", ");
+        }
+        query_builder.push("t.id)").push(comparison).push("(");
+        match &cursor.key {
+            Some(SortKey::Integer(key)) => {
+                query_builder.push_bind(*key).push(", ");
+            }
+            Some(SortKey::Text(key)) => {
+                query_builder.push_bind(key.clone()).push(", ");
+            }
+            None => {}
+        }
+        query_builder.push_bind(cursor.id).push(")");
+    }
+
+    query_builder.push(" ORDER BY ");
+    if let Some(col) = sort_col {
+        query_builder.push(col.sql()).push(order).push(", ");
+    }
+    query_builder.push("t.id").push(order);
+    query_builder.push(" LIMIT ").push_bind(limit);
+
+    let mut transactions: Vec<TransactionWithAccount> = query_builder
+        .build_query_as::<TransactionWithAccount>()
+        .fetch_all(pool)
+        .await?;
+
+    if direction == PageDirection::Prev {
+        transactions.reverse();
+    }
+    Ok(transactions)
+}
+
+/*
+One page of the transactions list, with cursors to the pages either side of it
+
+Rows are only counted for the first page, later pages keep the count the client already has
+*/
+pub async fn get_transactions_page(
+    pool: &Pool<Sqlite>,
+    sort_col: Option<SortColumn>,
+    sort_dir: SortDir,
+    cursor: Option<&PageCursor>,
+    direction: PageDirection,
+    page_size: i64,
+    filter: &TransactionFilter,
+) -> Result<TransactionPage, sqlx::Error> {
+    // One extra row tells whether there is another page in this direction
+    let mut rows =
+        get_transactions_beyond(pool, sort_col, sort_dir, cursor, direction, page_size + 1, filter)
+            .await?;
+    let has_more = rows.len() as i64 > page_size;
+    if has_more {
+        match direction {
+            PageDirection::Next => rows.truncate(page_size as usize),
+            PageDirection::Prev => {
+                rows.remove(0);
+            }
+        }
+    }
+
+    let cursor_at = |row: Option<&TransactionWithAccount>| {
+        row.map(|row| PageCursor::at(row, sort_col, sort_dir).encode())
+    };
+    let (next_cursor, prev_cursor) = match direction {
+        PageDirection::Next => (
+            has_more.then(|| cursor_at(rows.last())).flatten(),
+            cursor.and_then(|_| cursor_at(rows.first())),
+        ),
+        PageDirection::Prev => (
+            cursor_at(rows.last()),
+            has_more.then(|| cursor_at(rows.first())).flatten(),
+        ),
+    };
+
+    Ok(TransactionPage {
+        transactions: rows,
+        next_cursor,
+        prev_cursor,
+        num_transactions: match cursor {
+            Some(_) => None,
+            None => Some(get_num_transactions(pool, filter).await?),
+        },
+    })
+}
+
+/*
+Inserts newly synced transactions, skipping any that were already synced
+
+Transactions without a category are filed by the first categorization rule that matches
+them, then by a confident suggestion from past corrections, then by the category mapped to
+their Plaid category, otherwise under `default_category`
+*/
 pub async fn add_plaid_transactions(
     conn: &mut SqliteConnection,
     new_transactions: Vec<PlaidTransaction>,
@@ -102,13 +314,31 @@ pub async fn add_plaid_transactions(
         return Ok(0);
     }
     let num_transactions = new_transactions.len() as u64;
+    let rules = rules::queries::get_rule_set(&mut *conn).await?;
+    let model = suggestions::queries::get_category_model(&mut *conn).await?;
+    let plaid_categories = plaid::queries::get_plaid_category_mapping(&mut *conn).await?;
 
     let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
-        "INSERT INTO 'transaction' (plaid_transaction_id, name, merchant_entity_id, amount_cents, date, pending, account_id, category_id) "
+        "INSERT INTO 'transaction' (plaid_transaction_id, name, merchant_entity_id, amount_cents, date, pending, account_id, category_id, plaid_category_primary, plaid_category_detailed) "
     );
 
     query_builder.push_values(new_transactions, |mut b, t| {
         let account_id = *t.account_id();
+        let name = t.name.as_deref().unwrap_or("");
+        let merchant_entity_id = t.merchant_entity_id.as_deref();
+        let category_id = t
+            .category_id()
+            .or_else(|| {
+                rules.categorize(&RuleCandidate {
+                    name,
+                    merchant_entity_id,
+                    account_id: account_id.unwrap_or_default(),
+                    amount: t.amount.0,
+                })
+            })
+            .or_else(|| model.auto_category(name, merchant_entity_id))
+            .or_else(|| t.mapped_category(&plaid_categories))
+            .unwrap_or(*default_category);
 
         b.push_bind(t.plaid_transaction_id)
             .push_bind(t.name.unwrap_or("".to_string()))
@@ -117,29 +347,360 @@ pub async fn add_plaid_transactions(
             .push_bind(t.date)
             .push_bind(t.pending)
             .push_bind(account_id)
-            .push_bind(default_category);
+            .push_bind(category_id)
+            .push_bind(t.plaid_category_primary)
+            .push_bind(t.plaid_category_detailed);
     });
     query_builder.push(
-        " ON CONFLICT(plaid_transaction_id) WHERE plaid_transaction_id IS NOT NULL DO NOTHING",
+        " ON CONFLICT(plaid_transaction_id) WHERE plaid_transaction_id IS NOT NULL DO NOTHING RETURNING id",
     );
 
-    let query = query_builder.build();
-    let res = query.execute(&mut *conn).await?;
+    let ids: Vec<i64> = query_builder
+        .build_query_scalar()
+        .fetch_all(&mut *conn)
+        .await?;
+    payees::queries::normalize_payees(&mut *conn, PayeeScope::Ids(&ids)).await?;
 
-    Ok(num_transactions - res.rows_affected())
+    Ok(num_transactions - ids.len() as u64)
 }
 
-pub async fn update_transaction_category(
-    pool: &Pool<Sqlite>,
+/*
+Adds statement rows to an account, filing each under the category its source file names
+when one matches, then under the first categorization rule that matches, then under a
+confident suggestion from past corrections, otherwise under `default_category`
+
+Rows are linked to `import_batch_id` when given so the import can be rolled back
+*/
+pub async fn add_imported_transactions(
+    conn: &mut SqliteConnection,
+    account_id: i64,
+    new_transactions: Vec<TransactionImport>,
+    default_category: &i64,
+    import_batch_id: Option<i64>,
+) -> Result<ImportSummary, sqlx::Error> {
+    let resolver = importers::queries::category_resolver(&mut *conn).await?;
+    let fingerprints = import_fingerprints(&new_transactions);
+
+    let rows = new_transactions
+        .into_iter()
+        .zip(fingerprints)
+        .map(|(transaction, fingerprint)| ImportedRow {
+            category_id: resolver
+                .resolve(&transaction, account_id)
+                .unwrap_or(*default_category),
+            fingerprint,
+            transaction,
+        })
+        .collect();
+
+    insert_imported_rows(conn, account_id, rows, import_batch_id).await
+}
+
+// Rows are inserted one at a time so a single bad row is counted as failed instead of
+// aborting the rest of the statement
+pub async fn insert_imported_rows(
+    conn: &mut SqliteConnection,
+    account_id: i64,
+    rows: Vec<ImportedRow>,
+    import_batch_id: Option<i64>,
+) -> Result<ImportSummary, sqlx::Error> {
+    let account_exists: Option<i64> = sqlx::query_scalar("SELECT id FROM account WHERE id=?")
+        .bind(account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if account_exists.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    // Rows already imported into this account (same fingerprint) are skipped
+    let query = r#"
+        INSERT INTO 'transaction' (
+            name,
+            amount_cents,
+            date,
+            account_id,
+            category_id,
+            import_fingerprint,
+            import_batch_id
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(account_id, import_fingerprint) WHERE import_fingerprint IS NOT NULL DO NOTHING
+    "#;
+
+    let category_ids = categories::queries::get_category_ids_by_name(&mut *conn).await?;
+    let mut summary = ImportSummary::default();
+    let mut ids = vec![];
+    for row in rows {
+        let t = &row.transaction;
+        let res = sqlx::query(query)
+            .bind(&t.name)
+            .bind(Cents(t.amount))
+            .bind(t.date)
+            .bind(account_id)
+            .bind(row.category_id)
+            .bind(&row.fingerprint)
+            .bind(import_batch_id)
+            .execute(&mut *conn)
+            .await;
+
+        match res {
+            Ok(res) if res.rows_affected() == 0 => summary.skipped += 1,
+            Ok(res) => {
+                summary.inserted += 1;
+                ids.push(res.last_insert_rowid());
+                let lines = t.split_lines(row.category_id, &category_ids);
+                if validate_splits(Cents(t.amount).to_cents(), &lines).is_ok() {
+                    set_transaction_splits(&mut *conn, res.last_insert_rowid(), &lines).await?;
+                } else {
+                    summary.unbalanced_splits += 1;
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to insert imported transaction {t}: {e}");
+                summary.failed += 1;
+            }
+        }
+    }
+    payees::queries::normalize_payees(&mut *conn, PayeeScope::Ids(&ids)).await?;
+
+    Ok(summary)
+}
+
+pub async fn update_transaction_category<'e>(
+    executor: impl SqliteExecutor<'e>,
     transaction_id: i64,
     category_id: i64,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(r#"UPDATE 'transaction' SET category_id=? WHERE id=?"#)
         .bind(category_id)
         .bind(transaction_id)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// A transaction by id, including soft-deleted ones
+pub async fn get_transaction(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+) -> Result<Transaction, sqlx::Error> {
+    let query = "SELECT id, plaid_transaction_id, name, merchant_entity_id, amount_cents, date, pending, deleted_at, account_id, category_id, plaid_category_primary, plaid_category_detailed, payee, merchant_id FROM 'transaction' WHERE id=?";
+
+    sqlx::query_as(query)
+        .bind(transaction_id)
+        .fetch_one(conn)
+        .await
+}
+
+/*
+Adds a transaction entered by hand, such as a cash purchase, and returns its id
+
+Without a category it is filed by the first categorization rule that matches it, then by a
+confident suggestion from past corrections, otherwise under `default_category`
+*/
+pub async fn create_manual_transaction(
+    conn: &mut SqliteConnection,
+    input: &TransactionInput,
+    category_id: Option<i64>,
+    default_category: &i64,
+) -> Result<i64, sqlx::Error> {
+    let category_id = match category_id {
+        Some(category_id) => category_id,
+        None => {
+            let rules = rules::queries::get_rule_set(&mut *conn).await?;
+            let model = suggestions::queries::get_category_model(&mut *conn).await?;
+            rules
+                .categorize(&RuleCandidate {
+                    name: &input.name,
+                    merchant_entity_id: None,
+                    account_id: input.account_id,
+                    amount: Cents::from_cents(input.amount_cents).0,
+                })
+                .or_else(|| model.auto_category(&input.name, None))
+                .unwrap_or(*default_category)
+        }
+    };
+
+    let query = r#"
+        INSERT INTO 'transaction' (name, amount_cents, date, pending, account_id, category_id)
+        VALUES (?, ?, ?, FALSE, ?, ?)
+        RETURNING id
+    "#;
+
+    let id = sqlx::query_scalar(query)
+        .bind(input.name.trim())
+        .bind(input.amount_cents)
+        .bind(input.date)
+        .bind(input.account_id)
+        .bind(category_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    payees::queries::normalize_payees(conn, PayeeScope::Ids(&[id])).await?;
+
+    Ok(id)
+}
+
+/*
+Changes a transaction's name, amount, date and account, which callers validate first
+
+On synced transactions each changed field is also kept as an override so later syncs don't
+undo the edit. A split is dropped when the amount changes since its lines no longer add up
+*/
+pub async fn update_transaction(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+    input: &TransactionInput,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM transaction_split
+        WHERE transaction_id IN (SELECT id FROM 'transaction' WHERE id=? AND amount_cents != ?)
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(input.amount_cents)
+    .execute(&mut *conn)
+    .await?;
+
+    let moved: Option<bool> = sqlx::query_scalar(
+        "SELECT amount_cents != ? OR date != ? OR account_id != ? FROM 'transaction' WHERE id=?",
+    )
+    .bind(input.amount_cents)
+    .bind(input.date)
+    .bind(input.account_id)
+    .bind(transaction_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    // SET expressions all see the row as it was before the update
+    let query = r#"
+        UPDATE 'transaction'
+        SET name_override = CASE
+                WHEN plaid_transaction_id IS NOT NULL AND name != $1 THEN $1
+                ELSE name_override
+            END,
+            amount_override_cents = CASE
+                WHEN plaid_transaction_id IS NOT NULL AND amount_cents != $2 THEN $2
+                ELSE amount_override_cents
+            END,
+            date_override = CASE
+                WHEN plaid_transaction_id IS NOT NULL AND date != $3 THEN $3
+                ELSE date_override
+            END,
+            payee = CASE WHEN name != $1 THEN NULL ELSE payee END,
+            name=$1,
+            amount_cents=$2,
+            date=$3,
+            account_id=$4
+        WHERE id=$5 AND deleted_at IS NULL
+    "#;
+
+    let res = sqlx::query(query)
+        .bind(input.name.trim())
+        .bind(input.amount_cents)
+        .bind(input.date)
+        .bind(input.account_id)
+        .bind(transaction_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    if moved == Some(true) {
+        transfers::queries::rescan_transfers_from(&mut *conn, transaction_id).await?;
+    }
+    payees::queries::normalize_payees(conn, PayeeScope::Ids(&[transaction_id])).await?;
+    Ok(())
+}
+
+/// Soft-deletes a transaction, or restores one when `deleted` is false
+pub async fn set_transaction_deleted(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+    deleted: bool,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        UPDATE 'transaction'
+        SET deleted_at = CASE WHEN $1 THEN COALESCE(deleted_at, date('now')) ELSE NULL END
+        WHERE id=$2
+    "#;
+
+    let res = sqlx::query(query)
+        .bind(deleted)
+        .bind(transaction_id)
         .execute(pool)
         .await?;
 
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+pub async fn get_transaction_amount_cents(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT amount_cents FROM 'transaction' WHERE id=? AND deleted_at IS NULL")
+        .bind(transaction_id)
+        .fetch_one(conn)
+        .await
+}
+
+pub async fn get_transaction_splits(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+) -> Result<Vec<TransactionSplit>, sqlx::Error> {
+    let query = r#"
+        SELECT id, transaction_id, category_id, amount_cents, memo
+        FROM transaction_split
+        WHERE transaction_id=?
+        ORDER BY id
+    "#;
+
+    let splits: Vec<TransactionSplit> = sqlx::query_as(query)
+        .bind(transaction_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(splits)
+}
+
+/*
+Replaces a transaction's split lines, which callers validate against its amount first
+
+The transaction is listed under the category holding the largest share. Passing no lines
+removes the split and leaves the transaction's category as it is
+*/
+pub async fn set_transaction_splits(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+    lines: &[SplitLine],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_split WHERE transaction_id=?")
+        .bind(transaction_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let query = r#"
+        INSERT INTO transaction_split (transaction_id, category_id, amount_cents, memo)
+        VALUES (?, ?, ?, ?)
+    "#;
+    for line in lines {
+        sqlx::query(query)
+            .bind(transaction_id)
+            .bind(line.category_id)
+            .bind(line.amount_cents)
+            .bind(&line.memo)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    if let Some(category_id) = primary_split_category(lines) {
+        update_transaction_category(&mut *conn, transaction_id, category_id).await?;
+    }
     Ok(())
 }
 
@@ -147,27 +708,71 @@ pub async fn modify_plaid_transactions(
     conn: &mut SqliteConnection,
     modified_transactions: Vec<PlaidTransaction>,
 ) -> Result<(), sqlx::Error> {
+    // Fields the user has edited keep their override
     let query = r#"
         UPDATE 'transaction'
-        SET amount_cents=?,
-            date=?,
-            name=?,
-            merchant_entity_id=?,
-            pending=?
+        SET amount_cents=COALESCE(amount_override_cents, $1),
+            date=COALESCE(date_override, $2),
+            payee = CASE WHEN name != COALESCE(name_override, $3) THEN NULL ELSE payee END,
+            name=COALESCE(name_override, $3),
+            merchant_id = CASE WHEN merchant_entity_id IS NOT $4 THEN NULL ELSE merchant_id END,
+            merchant_entity_id=$4,
+            pending=$5,
+            plaid_category_primary=$6,
+            plaid_category_detailed=$7
+        WHERE plaid_transaction_id=$8
+        RETURNING id
+    "#;
+
+    // Splits no longer add up once Plaid revises an amount, so they are dropped
+    let drop_splits = r#"
+        DELETE FROM transaction_split
+        WHERE transaction_id IN (
+            SELECT id FROM 'transaction'
+            WHERE plaid_transaction_id=? AND amount_cents != COALESCE(amount_override_cents, ?)
+        )
+    "#;
+
+    // Rows whose amount or date moves may now pair as a transfer
+    let moved = r#"
+        SELECT id FROM 'transaction'
         WHERE plaid_transaction_id=?
+            AND (
+                amount_cents != COALESCE(amount_override_cents, ?)
+                OR date != COALESCE(date_override, ?)
+            )
     "#;
 
+    let mut ids = vec![];
     for t in modified_transactions {
-        sqlx::query(query)
+        sqlx::query(drop_splits)
+            .bind(&t.plaid_transaction_id)
+            .bind(t.amount)
+            .execute(&mut *conn)
+            .await?;
+        let moved_id: Option<i64> = sqlx::query_scalar(moved)
+            .bind(&t.plaid_transaction_id)
+            .bind(t.amount)
+            .bind(t.date)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if let Some(moved_id) = moved_id {
+            transfers::queries::rescan_transfers_from(&mut *conn, moved_id).await?;
+        }
+        let id: Option<i64> = sqlx::query_scalar(query)
             .bind(t.amount)
             .bind(t.date)
             .bind(t.name.unwrap_or_default())
             .bind(t.merchant_entity_id)
             .bind(t.pending)
+            .bind(t.plaid_category_primary)
+            .bind(t.plaid_category_detailed)
             .bind(t.plaid_transaction_id)
-            .execute(&mut *conn)
+            .fetch_optional(&mut *conn)
             .await?;
+        ids.extend(id);
     }
+    payees::queries::normalize_payees(conn, PayeeScope::Ids(&ids)).await?;
 
     Ok(())
 }
@@ -195,6 +800,8 @@ pub async fn remove_plaid_transactions(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::importers::types::ImportSplit;
+    use crate::rules::types::{DescriptionMatch, RuleDefinition};
     use crate::types::Cents;
     use chrono::NaiveDate;
     use rust_decimal::dec;
@@ -411,6 +1018,644 @@ mod tests {
         Ok(())
     }
 
+    fn manual_input(account_id: i64, name: &str, amount_cents: i64, day: u32) -> TransactionInput {
+        TransactionInput {
+            account_id,
+            name: name.to_owned(),
+            amount_cents,
+            date: NaiveDate::from_ymd_opt(2026, 1, day).unwrap(),
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn manual_transactions_are_created_and_edited(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        create_rule(&pool, "market", 4).await?;
+
+        let id = create_manual_transaction(&mut conn, &manual_input(2, "Farmers Market", -1250, 3), None, &1)
+            .await?;
+        let pinned = create_manual_transaction(&mut conn, &manual_input(2, "Market day", -300, 3), Some(10), &1)
+            .await?;
+
+        let created = get_transaction(&mut conn, id).await?;
+        assert_eq!(created.plaid_transaction_id(), &None);
+        assert_eq!(created.amount, Cents::from_cents(-1250));
+        assert_eq!(*created.category_id(), 4, "rules file manual rows too");
+        assert_eq!(created.payee.as_deref(), Some("Farmers Market"));
+        assert_eq!(*get_transaction(&mut conn, pinned).await?.category_id(), 10);
+
+        set_transaction_splits(&mut conn, id, &[split(4, -1000), split(5, -250)]).await?;
+        update_transaction(&mut conn, id, &manual_input(1, "Farmers Market", -1250, 4)).await?;
+        assert_eq!(get_transaction_splits(&pool, id).await?.len(), 2, "same amount keeps the split");
+
+        update_transaction(&mut conn, id, &manual_input(1, "Cash", -1500, 5)).await?;
+        let edited = get_transaction(&mut conn, id).await?;
+        assert_eq!(edited.name, "Cash");
+        assert_eq!(edited.payee.as_deref(), Some("Cash"));
+        assert_eq!(edited.amount, Cents::from_cents(-1500));
+        assert_eq!(edited.date, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        assert_eq!(*edited.account_id(), 1);
+        assert!(get_transaction_splits(&pool, id).await?.is_empty());
+
+        let overrides: (Option<String>, Option<i64>) =
+            sqlx::query_as("SELECT name_override, amount_override_cents FROM 'transaction' WHERE id=?")
+                .bind(id)
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(overrides, (None, None), "only synced rows keep overrides");
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn edits_to_synced_transactions_survive_later_syncs(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        add_plaid_transactions(&mut conn, vec![plaid_txn("txn-1", "SQ *CAFE 1234", -4.50, true)], &1)
+            .await?;
+        let id = *get_transactions(&pool, None).await?[0].id();
+        let date = get_transaction(&mut conn, id).await?.date;
+
+        // Only the name is changed, so the amount and date still follow Plaid
+        let input = TransactionInput {
+            name: "Corner Cafe".to_owned(),
+            date,
+            ..manual_input(1, "", -450, 1)
+        };
+        update_transaction(&mut conn, id, &input).await?;
+
+        let mut posted = plaid_txn("txn-1", "SQ *CAFE 1234 POSTED", -5.25, false);
+        posted.date = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+        modify_plaid_transactions(&mut conn, vec![posted]).await?;
+
+        let synced = get_transaction(&mut conn, id).await?;
+        assert_eq!(synced.name, "Corner Cafe");
+        assert_eq!(synced.payee.as_deref(), Some("Corner Cafe"));
+        assert_eq!(synced.amount, Cents::from_cents(-525));
+        assert_eq!(synced.date, NaiveDate::from_ymd_opt(2026, 1, 20).unwrap());
+        assert!(!synced.pending);
+
+        let input = TransactionInput {
+            amount_cents: -600,
+            date: synced.date,
+            ..input
+        };
+        update_transaction(&mut conn, id, &input).await?;
+        set_transaction_splits(&mut conn, id, &[split(4, -400), split(5, -200)]).await?;
+        modify_plaid_transactions(&mut conn, vec![plaid_txn("txn-1", "SQ *CAFE", -5.30, false)])
+            .await?;
+
+        let synced = get_transaction(&mut conn, id).await?;
+        assert_eq!(synced.amount, Cents::from_cents(-600));
+        assert_eq!(synced.date, NaiveDate::from_ymd_opt(2026, 1, 15).unwrap());
+        assert_eq!(
+            get_transaction_splits(&pool, id).await?.len(),
+            2,
+            "the split still adds up to the overridden amount"
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn deleted_transactions_can_be_restored(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        set_transaction_deleted(&pool, 2, true).await?;
+        let ids: Vec<i64> = get_transactions(&pool, None).await?.iter().map(|t| *t.id()).collect();
+        assert_eq!(ids, vec![1, 4, 3]);
+
+        let mut conn = pool.acquire().await?;
+        assert!(get_transaction(&mut conn, 2).await?.deleted_at.is_some());
+        assert!(matches!(
+            update_transaction(&mut conn, 2, &manual_input(1, "Edited", -100, 1)).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+
+        set_transaction_deleted(&pool, 2, false).await?;
+        assert_eq!(get_transactions(&pool, None).await?.len(), 4);
+        assert!(matches!(
+            set_transaction_deleted(&pool, 99, true).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        Ok(())
+    }
+
+    fn imported_txn(name: &str, amount: rust_decimal::Decimal, day: u32) -> TransactionImport {
+        TransactionImport {
+            date: NaiveDate::from_ymd_opt(2025, 12, day).unwrap(),
+            name: name.to_owned(),
+            amount,
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_persists_rows_to_account(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let summary = add_imported_transactions(
+            &mut conn,
+            2,
+            vec![
+                imported_txn("SQ *ESPRESSO HOUSE", dec!(-5.77), 15),
+                imported_txn("PAYROLL", dec!(1200.00), 16),
+            ],
+            &1,
+            None,
+        )
+        .await?;
+        assert_eq!(
+            summary,
+            ImportSummary {
+                inserted: 2,
+                skipped: 0,
+                failed: 0,
+                unbalanced_splits: 0,
+                rejected: vec![],
+                reconciliation: None,
+            }
+        );
+
+        let transactions = get_transactions(&pool, None).await?;
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions
+            .iter()
+            .all(|t| *t.account_id() == 2 && *t.category_id() == 1));
+        let espresso = transactions
+            .iter()
+            .find(|t| t.name == "SQ *ESPRESSO HOUSE")
+            .expect("imported row should be queryable");
+        assert_eq!(espresso.amount, Cents(dec!(-5.77)));
+        assert!(espresso.plaid_transaction_id().is_none());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_rejects_unknown_account(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let result = add_imported_transactions(
+            &mut conn,
+            999,
+            vec![imported_txn("Coffee", dec!(-4.50), 15)],
+            &1,
+            None,
+        )
+        .await;
+        assert!(result.is_err(), "importing into a missing account should fail");
+
+        assert!(get_transactions(&pool, None).await?.is_empty());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_skips_rows_from_overlapping_statements(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let first_statement = vec![
+            imported_txn("FOOD MARKET #111", dec!(-7.25), 11),
+            imported_txn("FOOD MARKET #111", dec!(-7.25), 11),
+            imported_txn("TST*PIZZA", dec!(-10.14), 12),
+        ];
+        add_imported_transactions(&mut conn, 2, first_statement.clone(), &1, None).await?;
+
+        // Re-importing the same file changes nothing.
+        let again = add_imported_transactions(&mut conn, 2, first_statement, &1, None).await?;
+        assert_eq!((again.inserted, again.skipped), (0, 3));
+
+        // A later statement repeating the last days only adds the new row.
+        let overlapping = add_imported_transactions(
+            &mut conn,
+            2,
+            vec![
+                imported_txn("FOOD MARKET #111", dec!(-7.25), 11),
+                imported_txn("FOOD MARKET #111", dec!(-7.25), 11),
+                imported_txn("TST*PIZZA", dec!(-10.14), 12),
+                imported_txn("SQ *ESPRESSO HOUSE", dec!(-5.77), 15),
+            ],
+            &1,
+            None,
+        )
+        .await?;
+        assert_eq!((overlapping.inserted, overlapping.skipped), (1, 3));
+
+        assert_eq!(get_num_transactions(&pool, &no_filter()).await?, 4);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_fingerprints_are_per_account(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let rows = vec![imported_txn("TST*PIZZA", dec!(-10.14), 12)];
+        add_imported_transactions(&mut conn, 1, rows.clone(), &1, None).await?;
+        let other_account = add_imported_transactions(&mut conn, 2, rows, &1, None).await?;
+
+        assert_eq!(other_account.inserted, 1);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_matches_category_hints_by_name(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let hinted = |name: &str, category: Option<&str>| TransactionImport {
+            category: category.map(str::to_owned),
+            ..imported_txn(name, dec!(-10.00), 12)
+        };
+        let split = TransactionImport {
+            splits: vec![
+                ImportSplit {
+                    category: Some("Shopping".to_owned()),
+                    memo: None,
+                    amount: dec!(-2.00),
+                },
+                ImportSplit {
+                    category: Some("Restaurants".to_owned()),
+                    memo: None,
+                    amount: dec!(-8.00),
+                },
+            ],
+            ..imported_txn("SPLIT", dec!(-10.00), 12)
+        };
+
+        let mut conn = pool.acquire().await?;
+        add_imported_transactions(
+            &mut conn,
+            2,
+            vec![
+                hinted("RENT", Some("housing")),
+                hinted("MARKET", Some("Food:Groceries")),
+                hinted("UNKNOWN", Some("Pets")),
+                hinted("NO HINT", None),
+                split,
+            ],
+            &1,
+            None,
+        )
+        .await?;
+
+        let categories: Vec<(String, String)> = sqlx::query_as(
+            "SELECT t.name, c.name FROM 'transaction' t JOIN category c ON c.id = t.category_id ORDER BY t.id",
+        )
+        .fetch_all(&pool)
+        .await?;
+        let categories: Vec<(&str, &str)> = categories
+            .iter()
+            .map(|(t, c)| (t.as_str(), c.as_str()))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                ("RENT", "Housing"),
+                ("MARKET", "Groceries"),
+                ("UNKNOWN", "Uncategorized"),
+                ("NO HINT", "Uncategorized"),
+                ("SPLIT", "Restaurants"),
+            ]
+        );
+        Ok(())
+    }
+
+    async fn create_rule(
+        pool: &Pool<Sqlite>,
+        pattern: &str,
+        category_id: i64,
+    ) -> Result<i64, sqlx::Error> {
+        rules::queries::create_categorization_rule(
+            pool,
+            &RuleDefinition {
+                name: pattern.to_owned(),
+                priority: 0,
+                enabled: true,
+                description_pattern: Some(pattern.to_owned()),
+                description_match: DescriptionMatch::Contains,
+                merchant_entity_id: None,
+                account_id: None,
+                min_amount_cents: None,
+                max_amount_cents: None,
+                amount_sign: None,
+                category_id,
+            },
+        )
+        .await
+    }
+
+    async fn category_names(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT t.name, c.name FROM 'transaction' t JOIN category c ON c.id = t.category_id ORDER BY t.id",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_plaid_files_transactions_by_rules(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        create_rule(&pool, "coffee", 5).await?;
+
+        let mut conn = pool.acquire().await?;
+        add_plaid_transactions(
+            &mut conn,
+            vec![
+                plaid_txn("txn-1", "Blue Bottle Coffee", -4.50, false),
+                plaid_txn("txn-2", "Books", -20.00, false),
+            ],
+            &1,
+        )
+        .await?;
+
+        assert_eq!(
+            category_names(&pool).await?,
+            vec![
+                ("Blue Bottle Coffee".to_owned(), "Restaurants".to_owned()),
+                ("Books".to_owned(), "Uncategorized".to_owned()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_plaid_learns_from_corrections(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let coffee = plaid_txn("txn-1", "Blue Bottle Coffee", -4.50, false);
+        add_plaid_transactions(&mut conn, vec![coffee], &1).await?;
+        suggestions::queries::record_category_correction(&mut conn, 1, 5).await?;
+        update_transaction_category(&mut *conn, 1, 5).await?;
+
+        add_plaid_transactions(
+            &mut conn,
+            vec![
+                plaid_txn("txn-2", "BLUE BOTTLE COFFEE #12", -6.00, false),
+                plaid_txn("txn-3", "Books", -20.00, false),
+            ],
+            &1,
+        )
+        .await?;
+
+        let categories = category_names(&pool).await?;
+        assert_eq!(categories[1].1, "Restaurants");
+        assert_eq!(categories[2].1, "Uncategorized");
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_plaid_files_by_mapped_plaid_category(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pfc = |plaid_id: &str, name: &str, primary: &str, detailed: &str| {
+            plaid_txn(plaid_id, name, -10.00, false)
+                .with_plaid_category(primary.to_owned(), detailed.to_owned())
+        };
+        let mut conn = pool.acquire().await?;
+        add_plaid_transactions(
+            &mut conn,
+            vec![
+                pfc("txn-1", "Safeway", "FOOD_AND_DRINK", "FOOD_AND_DRINK_GROCERIES"),
+                pfc("txn-2", "Diner", "FOOD_AND_DRINK", "FOOD_AND_DRINK_RESTAURANT"),
+                pfc("txn-3", "Bank Fee", "BANK_FEES", "BANK_FEES_OTHER_BANK_FEES"),
+                plaid_txn("txn-4", "Books", -20.00, false),
+            ],
+            &1,
+        )
+        .await?;
+
+        let categories: Vec<String> =
+            category_names(&pool).await?.into_iter().map(|(_, c)| c).collect();
+        assert_eq!(
+            categories,
+            vec!["Groceries", "Restaurants", "Uncategorized", "Uncategorized"]
+        );
+
+        let stored = get_transactions(&pool, None).await?;
+        assert_eq!(stored[0].plaid_category_primary.as_deref(), Some("FOOD_AND_DRINK"));
+        assert_eq!(
+            stored[0].plaid_category_detailed.as_deref(),
+            Some("FOOD_AND_DRINK_GROCERIES")
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_applies_rules_after_file_categories(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        create_rule(&pool, "market", 4).await?;
+        let market = TransactionImport {
+            category: Some("Housing".to_owned()),
+            ..imported_txn("MARKET RENT", dec!(-10.00), 12)
+        };
+
+        let mut conn = pool.acquire().await?;
+        add_imported_transactions(
+            &mut conn,
+            2,
+            vec![market, imported_txn("FOOD MARKET", dec!(-10.00), 13)],
+            &1,
+            None,
+        )
+        .await?;
+
+        assert_eq!(
+            category_names(&pool).await?,
+            vec![
+                ("MARKET RENT".to_owned(), "Housing".to_owned()),
+                ("FOOD MARKET".to_owned(), "Groceries".to_owned()),
+            ]
+        );
+        Ok(())
+    }
+
+    fn split(category_id: i64, amount_cents: i64) -> SplitLine {
+        SplitLine {
+            category_id,
+            amount_cents,
+            memo: None,
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn set_splits_replaces_lines_and_lists_largest_category(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        // Transaction 2 is -10.90
+        assert_eq!(get_transaction_amount_cents(&mut conn, 2).await?, -1090);
+
+        set_transaction_splits(&mut conn, 2, &[split(4, -290), split(11, -800)]).await?;
+        let splits = get_transaction_splits(&pool, 2).await?;
+        assert_eq!(
+            splits
+                .iter()
+                .map(|s| (s.category_id, s.amount))
+                .collect::<Vec<_>>(),
+            vec![(4, Cents(dec!(-2.90))), (11, Cents(dec!(-8.00)))]
+        );
+        assert_eq!(category_of(&all_transactions(&pool).await?, 2), "Shopping");
+
+        set_transaction_splits(&mut conn, 2, &[split(4, -545), split(5, -545)]).await?;
+        assert_eq!(get_transaction_splits(&pool, 2).await?.len(), 2);
+        assert_eq!(category_of(&all_transactions(&pool).await?, 2), "Groceries");
+
+        set_transaction_splits(&mut conn, 2, &[]).await?;
+        assert!(get_transaction_splits(&pool, 2).await?.is_empty());
+        assert_eq!(category_of(&all_transactions(&pool).await?, 2), "Groceries");
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn modify_drops_splits_when_amount_changes(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        add_plaid_transactions(
+            &mut conn,
+            vec![
+                plaid_txn("txn-1", "Costco", -100.00, false),
+                plaid_txn("txn-2", "Target", -50.00, false),
+            ],
+            &1,
+        )
+        .await?;
+        set_transaction_splits(&mut conn, 1, &[split(4, -6000), split(11, -4000)]).await?;
+        set_transaction_splits(&mut conn, 2, &[split(4, -2500), split(11, -2500)]).await?;
+
+        modify_plaid_transactions(
+            &mut conn,
+            vec![
+                plaid_txn("txn-1", "Costco", -120.00, false),
+                plaid_txn("txn-2", "Target", -50.00, true),
+            ],
+        )
+        .await?;
+
+        assert!(get_transaction_splits(&pool, 1).await?.is_empty());
+        assert_eq!(get_transaction_splits(&pool, 2).await?.len(), 2);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn add_imported_keeps_file_splits(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let split_line = |category: &str, amount| ImportSplit {
+            category: Some(category.to_owned()),
+            memo: None,
+            amount,
+        };
+        let costco = TransactionImport {
+            splits: vec![
+                split_line("Groceries", dec!(-60.00)),
+                split_line("Shopping", dec!(-40.00)),
+            ],
+            ..imported_txn("COSTCO", dec!(-100.00), 12)
+        };
+        let unbalanced = TransactionImport {
+            splits: vec![
+                split_line("Groceries", dec!(-60.00)),
+                split_line("Shopping", dec!(-30.00)),
+            ],
+            ..imported_txn("COSTCO", dec!(-100.00), 13)
+        };
+
+        let mut conn = pool.acquire().await?;
+        let summary =
+            add_imported_transactions(&mut conn, 2, vec![costco, unbalanced], &1, None).await?;
+        assert_eq!((summary.inserted, summary.unbalanced_splits), (2, 1));
+
+        let splits = get_transaction_splits(&pool, 1).await?;
+        assert_eq!(
+            splits.iter().map(|s| s.category_id).collect::<Vec<_>>(),
+            vec![4, 11]
+        );
+        assert!(get_transaction_splits(&pool, 2).await?.is_empty());
+        Ok(())
+    }
+
+    fn no_filter() -> TransactionFilter {
+        TransactionFilter::default()
+    }
+
+    async fn filtered(
+        pool: &Pool<Sqlite>,
+        filter: TransactionFilter,
+    ) -> Result<(Vec<i64>, i64), sqlx::Error> {
+        let page = get_paginated_sorted_transactions(
+            pool,
+            &1,
+            &10,
+            &Some("date".to_owned()),
+            &Some(SortDir::Asc),
+            &filter,
+        )
+        .await?;
+        Ok((ids(&page), get_num_transactions(pool, &filter).await?))
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn filter_applies_to_page_and_count(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let dates = TransactionFilter {
+            date_from: NaiveDate::from_ymd_opt(2025, 12, 16),
+            date_to: NaiveDate::from_ymd_opt(2025, 12, 16),
+            ..no_filter()
+        };
+        assert_eq!(filtered(&pool, dates).await?, (vec![2, 4], 2));
+
+        let amounts = TransactionFilter {
+            min_amount_cents: Some(-600),
+            max_amount_cents: Some(-100),
+            ..no_filter()
+        };
+        assert_eq!(filtered(&pool, amounts).await?, (vec![1, 3], 2));
+
+        let pending = TransactionFilter {
+            pending: Some(true),
+            account_ids: vec![1],
+            ..no_filter()
+        };
+        assert_eq!(filtered(&pool, pending).await?, (vec![], 0));
+
+        update_transaction_category(&pool, 3, 4).await?;
+        let mut conn = pool.acquire().await?;
+        set_transaction_splits(&mut conn, 2, &[split(5, -590), split(4, -500)]).await?;
+        let groceries = TransactionFilter {
+            category_ids: vec![4],
+            ..no_filter()
+        };
+        assert_eq!(filtered(&pool, groceries).await?, (vec![2, 3], 2));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn search_matches_name_words_by_prefix(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let search = |text: &str| TransactionFilter {
+            search: Some(text.to_owned()),
+            ..no_filter()
+        };
+        sqlx::query("UPDATE 'transaction' SET name='Whole Foods Market' WHERE id=3")
+            .execute(&pool)
+            .await?;
+
+        assert_eq!(filtered(&pool, search("whole mark")).await?, (vec![3], 1));
+        assert_eq!(filtered(&pool, search("transaction 4")).await?, (vec![4], 1));
+        assert_eq!(filtered(&pool, search("TRANS")).await?.1, 3);
+        assert_eq!(filtered(&pool, search("\"foods OR")).await?, (vec![], 0));
+        Ok(())
+    }
+
     fn ids(transactions: &[TransactionWithAccount]) -> Vec<i64> {
         transactions.iter().map(|t| *t.id()).collect()
     }
@@ -419,17 +1664,20 @@ mod tests {
     async fn paginated_walks_pages_without_overlap(
         pool: Pool<Sqlite>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        assert_eq!(get_num_transactions(&pool).await?, 4);
+        assert_eq!(get_num_transactions(&pool, &no_filter()).await?, 4);
 
         // Sort by amount (unique values) so paging is deterministic.
         // Ascending amounts: id2=-10.90, id1=-5.77, id3=-1.90, id4=-0.70
         let sort_col = Some("amount".to_owned());
         let page1 =
-            get_paginated_sorted_transactions(&pool, &1, &2, &sort_col, &Some(SortDir::Asc)).await?;
+            get_paginated_sorted_transactions(&pool, &1, &2, &sort_col, &Some(SortDir::Asc), &no_filter())
+                .await?;
         let page2 =
-            get_paginated_sorted_transactions(&pool, &2, &2, &sort_col, &Some(SortDir::Asc)).await?;
+            get_paginated_sorted_transactions(&pool, &2, &2, &sort_col, &Some(SortDir::Asc), &no_filter())
+                .await?;
         let page3 =
-            get_paginated_sorted_transactions(&pool, &3, &2, &sort_col, &Some(SortDir::Asc)).await?;
+            get_paginated_sorted_transactions(&pool, &3, &2, &sort_col, &Some(SortDir::Asc), &no_filter())
+                .await?;
 
         assert_eq!(ids(&page1), vec![2, 1]);
         assert_eq!(ids(&page2), vec![3, 4]);
@@ -447,6 +1695,7 @@ mod tests {
             &10,
             &Some("amount".to_owned()),
             &Some(SortDir::Asc),
+            &no_filter(),
         )
         .await?;
         let desc = get_paginated_sorted_transactions(
@@ -455,6 +1704,7 @@ mod tests {
             &10,
             &Some("amount".to_owned()),
             &Some(SortDir::Desc),
+            &no_filter(),
         )
         .await?;
 
@@ -473,6 +1723,7 @@ mod tests {
             &1,
             &Some("name".to_owned()),
             &Some(SortDir::Asc),
+            &no_filter(),
         )
         .await?;
 
@@ -486,6 +1737,109 @@ mod tests {
         Ok(())
     }
 
+    async fn walk_pages(
+        pool: &Pool<Sqlite>,
+        sort_col: SortColumn,
+        sort_dir: SortDir,
+    ) -> Result<Vec<Vec<i64>>, Box<dyn std::error::Error>> {
+        let mut pages = vec![];
+        let mut cursor = None;
+        loop {
+            let page = get_transactions_page(
+                pool,
+                Some(sort_col),
+                sort_dir,
+                cursor.as_ref(),
+                PageDirection::Next,
+                2,
+                &no_filter(),
+            )
+            .await?;
+            pages.push(ids(&page.transactions));
+            match page.next_cursor {
+                Some(next) => cursor = Some(PageCursor::decode(&next)?),
+                None => return Ok(pages),
+            }
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn keyset_pages_cover_every_sort_column(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Dates: id1=12-15, id2=12-16, id4=12-16, id3=12-17
+        assert_eq!(
+            walk_pages(&pool, SortColumn::Date, SortDir::Asc).await?,
+            vec![vec![1, 2], vec![4, 3]]
+        );
+        assert_eq!(
+            walk_pages(&pool, SortColumn::Date, SortDir::Desc).await?,
+            vec![vec![3, 4], vec![2, 1]]
+        );
+        assert_eq!(
+            walk_pages(&pool, SortColumn::Amount, SortDir::Asc).await?,
+            vec![vec![2, 1], vec![3, 4]]
+        );
+        assert_eq!(
+            walk_pages(&pool, SortColumn::Name, SortDir::Desc).await?,
+            vec![vec![4, 3], vec![2, 1]]
+        );
+        // Every row shares one account, so the id alone orders them
+        assert_eq!(
+            walk_pages(&pool, SortColumn::Account, SortDir::Asc).await?,
+            vec![vec![1, 2], vec![3, 4]]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
+    async fn keyset_pages_hold_still_while_rows_are_added(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let page = |cursor: Option<String>, direction| {
+            let pool = pool.clone();
+            async move {
+                let cursor = cursor.map(|c| PageCursor::decode(&c)).transpose()?;
+                let page = get_transactions_page(
+                    &pool,
+                    Some(SortColumn::Date),
+                    SortDir::Desc,
+                    cursor.as_ref(),
+                    direction,
+                    2,
+                    &no_filter(),
+                )
+                .await?;
+                Ok::<_, Box<dyn std::error::Error>>(page)
+            }
+        };
+
+        let first = page(None, PageDirection::Next).await?;
+        assert_eq!(ids(&first.transactions), vec![3, 4]);
+        assert_eq!(first.prev_cursor, None);
+        assert_eq!(first.num_transactions, Some(4));
+
+        // A newer transaction synced in mid-way would push rows down an OFFSET page
+        sqlx::query(
+            "INSERT INTO 'transaction' (id, date, amount_cents, name, account_id, category_id) \
+            VALUES (5, '2025-12-20', -100, 'TRANSACTION 5', 1, 1)",
+        )
+        .execute(&pool)
+        .await?;
+
+        let second = page(first.next_cursor, PageDirection::Next).await?;
+        assert_eq!(ids(&second.transactions), vec![2, 1]);
+        assert_eq!(second.next_cursor, None);
+        assert_eq!(second.num_transactions, None, "only the first page is counted");
+
+        let back = page(second.prev_cursor, PageDirection::Prev).await?;
+        assert_eq!(ids(&back.transactions), vec![3, 4]);
+        let newest = page(back.prev_cursor, PageDirection::Prev).await?;
+        assert_eq!(ids(&newest.transactions), vec![5]);
+        assert_eq!(newest.prev_cursor, None);
+        Ok(())
+    }
+
     fn category_of(transactions: &[TransactionWithAccount], id: i64) -> &str {
         transactions
             .iter()
@@ -497,8 +1851,15 @@ mod tests {
     async fn all_transactions(
         pool: &Pool<Sqlite>,
     ) -> Result<Vec<TransactionWithAccount>, sqlx::Error> {
-        get_paginated_sorted_transactions(pool, &1, &10, &Some("name".to_owned()), &Some(SortDir::Asc))
-            .await
+        get_paginated_sorted_transactions(
+            pool,
+            &1,
+            &10,
+            &Some("name".to_owned()),
+            &Some(SortDir::Asc),
+            &no_filter(),
+        )
+        .await
     }
 
     #[sqlx::test(fixtures(path = "../fixtures", scripts("transactions")))]
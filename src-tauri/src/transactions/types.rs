@@ -0,0 +1,311 @@
+use crate::types::{Cents, SortDir, Transaction, TransactionWithAccount};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::NaiveDate;
+
+/// One part of a transaction divided across several categories
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow, serde::Serialize)]
+pub struct TransactionSplit {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub category_id: i64,
+    #[sqlx(rename = "amount_cents")]
+    pub amount: Cents,
+    pub memo: Option<String>,
+}
+
+/// A split line as entered by the user, in cents with the parent's sign
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct SplitLine {
+    pub category_id: i64,
+    pub amount_cents: i64,
+    pub memo: Option<String>,
+}
+
+/*
+Checks that split lines divide a transaction of `parent_cents` exactly
+
+No lines at all is valid and means the transaction isn't split. Otherwise there must be at
+least two lines, none of them zero, adding up to the parent amount
+*/
+pub fn validate_splits(parent_cents: i64, lines: &[SplitLine]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+    if lines.len() < 2 {
+        return Err("A split needs at least two lines".to_string());
+    }
+    if lines.iter().any(|line| line.amount_cents == 0) {
+        return Err("Split lines can't be zero".to_string());
+    }
+
+    let total: i64 = lines.iter().map(|line| line.amount_cents).sum();
+    if total != parent_cents {
+        return Err(format!(
+            "Split lines add up to {} but the transaction is {}",
+            Cents::from_cents(total),
+            Cents::from_cents(parent_cents)
+        ));
+    }
+    Ok(())
+}
+
+/// The category a split transaction is listed under, the one holding the largest share
+pub fn primary_split_category(lines: &[SplitLine]) -> Option<i64> {
+    // Reversed so the earliest line wins a tie
+    lines
+        .iter()
+        .rev()
+        .max_by_key(|line| line.amount_cents.abs())
+        .map(|line| line.category_id)
+}
+
+/// A transaction's own fields as entered by the user, in cents with spending negative
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct TransactionInput {
+    pub account_id: i64,
+    pub name: String,
+    pub amount_cents: i64,
+    pub date: NaiveDate,
+}
+
+impl TransactionInput {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Transaction name is required".to_string());
+        }
+        Ok(())
+    }
+
+    /*
+    Checks that `existing` can be changed to these fields
+
+    Deleted transactions have to be restored first. Synced transactions stay on the account
+    Plaid reports them under, since the next sync would look for them there
+    */
+    pub fn validate_edit(&self, existing: &Transaction) -> Result<(), String> {
+        self.validate()?;
+        if existing.deleted_at.is_some() {
+            return Err("Deleted transactions can't be edited".to_string());
+        }
+        if existing.plaid_transaction_id().is_some() && *existing.account_id() != self.account_id
+        {
+            return Err("Synced transactions can't be moved to another account".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Narrows the transactions list. Unset fields and empty id lists don't filter anything
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default)]
+pub struct TransactionFilter {
+    /// Inclusive on both ends
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub account_ids: Vec<i64>,
+    /// Matches split lines as well as the category a transaction is listed under
+    pub category_ids: Vec<i64>,
+    /// Matches transactions with any of these tags
+    pub tag_ids: Vec<i64>,
+    pub merchant_ids: Vec<i64>,
+    /// Signed like the amounts themselves, so spending is negative
+    pub min_amount_cents: Option<i64>,
+    pub max_amount_cents: Option<i64>,
+    pub pending: Option<bool>,
+    pub search: Option<String>,
+}
+
+impl TransactionFilter {
+    /*
+    The search text as an FTS5 query matching names containing every word, each as a prefix
+
+    Words are quoted so characters FTS5 treats as syntax are searched for literally
+    */
+    pub fn search_query(&self) -> Option<String> {
+        let words: Vec<String> = self
+            .search
+            .as_deref()?
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect();
+
+        if words.is_empty() {
+            None
+        } else {
+            Some(words.join(" "))
+        }
+    }
+}
+
+/// The columns the transactions list can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortColumn {
+    Date,
+    Account,
+    Name,
+    Amount,
+}
+
+impl SortColumn {
+    pub fn parse(column: &str) -> Result<Self, String> {
+        match column {
+            "date" => Ok(SortColumn::Date),
+            "account" => Ok(SortColumn::Account),
+            "name" => Ok(SortColumn::Name),
+            "amount" => Ok(SortColumn::Amount),
+            _ => Err(format!("Invalid sort column {column}")),
+        }
+    }
+
+    pub fn sql(&self) -> &'static str {
+        match self {
+            SortColumn::Date => "t.date",
+            SortColumn::Account => "a.name",
+            SortColumn::Name => "t.name",
+            SortColumn::Amount => "t.amount_cents",
+        }
+    }
+
+    fn key(&self, transaction: &TransactionWithAccount) -> SortKey {
+        match self {
+            SortColumn::Date => SortKey::Text(transaction.date.to_string()),
+            SortColumn::Account => SortKey::Text(transaction.account_name().clone()),
+            SortColumn::Name => SortKey::Text(transaction.name.clone()),
+            SortColumn::Amount => SortKey::Integer(transaction.amount.to_cents()),
+        }
+    }
+}
+
+/// A row's value in the sort column, as stored in the database
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum SortKey {
+    Integer(i64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+pub enum PageDirection {
+    #[default]
+    Next,
+    Prev,
+}
+
+/*
+Position of a row in the sorted transactions list, handed to the frontend as an opaque string
+
+It carries the sort it was made for so a cursor can't be replayed against another ordering
+*/
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PageCursor {
+    pub sort_col: Option<SortColumn>,
+    pub sort_dir: SortDir,
+    pub key: Option<SortKey>,
+    pub id: i64,
+}
+
+impl PageCursor {
+    pub fn at(
+        transaction: &TransactionWithAccount,
+        sort_col: Option<SortColumn>,
+        sort_dir: SortDir,
+    ) -> Self {
+        PageCursor {
+            sort_col,
+            sort_dir,
+            key: sort_col.map(|col| col.key(transaction)),
+            id: *transaction.id(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor always serializes");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, String> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or("Invalid page cursor".to_string())
+    }
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct TransactionPage {
+    pub transactions: Vec<TransactionWithAccount>,
+    /// None on the last page
+    pub next_cursor: Option<String>,
+    /// None on the first page
+    pub prev_cursor: Option<String>,
+    /// Only counted for the first page
+    pub num_transactions: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(category_id: i64, amount_cents: i64) -> SplitLine {
+        SplitLine {
+            category_id,
+            amount_cents,
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn splits_must_add_up_to_parent() {
+        assert!(validate_splits(-10000, &[line(4, -6000), line(11, -4000)]).is_ok());
+        assert!(validate_splits(-10000, &[]).is_ok());
+
+        let err = validate_splits(-10000, &[line(4, -6000), line(11, -3000)]).unwrap_err();
+        assert_eq!(err, "Split lines add up to -90.00 but the transaction is -100.00");
+        assert!(validate_splits(-10000, &[line(4, -10000)]).is_err());
+        assert!(validate_splits(-10000, &[line(4, -10000), line(11, 0)]).is_err());
+    }
+
+    #[test]
+    fn primary_category_holds_largest_share() {
+        assert_eq!(primary_split_category(&[line(4, -6000), line(11, -4000)]), Some(4));
+        assert_eq!(primary_split_category(&[line(5, -5000), line(4, -5000)]), Some(5));
+        assert_eq!(primary_split_category(&[]), None);
+    }
+
+    #[test]
+    fn search_query_quotes_each_word_as_a_prefix() {
+        let filter = |search: &str| TransactionFilter {
+            search: Some(search.to_owned()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filter("  whole  foo ").search_query(),
+            Some(r#""whole"* "foo"*"#.to_owned())
+        );
+        assert_eq!(
+            filter(r#"AT&T "wireless" OR"#).search_query(),
+            Some(r#""AT&T"* """wireless"""* "OR"*"#.to_owned())
+        );
+        assert_eq!(filter("   ").search_query(), None);
+        assert_eq!(TransactionFilter::default().search_query(), None);
+    }
+
+    #[test]
+    fn cursor_round_trips_through_opaque_string() {
+        let cursor = PageCursor {
+            sort_col: Some(SortColumn::Amount),
+            sort_dir: SortDir::Desc,
+            key: Some(SortKey::Integer(-1090)),
+            id: 2,
+        };
+        let encoded = cursor.encode();
+
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(PageCursor::decode(&encoded), Ok(cursor));
+        assert!(PageCursor::decode("not a cursor").is_err());
+    }
+}
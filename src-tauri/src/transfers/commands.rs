@@ -0,0 +1,113 @@
+use crate::transfers::queries;
+use crate::transfers::types::{validate_transfer_link, Transfer, TransferStatus};
+use crate::{transactions, AppState};
+
+#[tauri::command]
+pub async fn get_transfers(
+    state: tauri::State<'_, AppState>,
+    status: Option<TransferStatus>,
+) -> Result<Vec<Transfer>, String> {
+    queries::get_transfers(&state.db.0, status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/*
+Looks through all transactions for transfers between the user's accounts
+
+New syncs and imports are checked as they are saved, this covers history from before and
+changes to the window. Returns how many transfers were found
+*/
+#[tauri::command]
+pub async fn detect_transfers(state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let found = queries::detect_transfers(&mut tx, false)
+        .await
+        .map_err(|e| format!("Failed to detect transfers: {e}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(found)
+}
+
+#[tauri::command]
+pub async fn get_transfer_window_days(state: tauri::State<'_, AppState>) -> Result<i64, String> {
+    queries::get_transfer_window_days(&state.db.0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// How many days apart the two sides of a transfer may post to still be detected
+#[tauri::command]
+pub async fn set_transfer_window_days(
+    state: tauri::State<'_, AppState>,
+    window_days: i64,
+) -> Result<(), String> {
+    if window_days < 0 {
+        return Err("The transfer window can't be negative".to_string());
+    }
+    queries::set_transfer_window_days(&state.db.0, window_days)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn confirm_transfer(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    queries::set_transfer_status(&state.db.0, id, TransferStatus::Confirmed)
+        .await
+        .map_err(|e| format!("Error confirming transfer {id}: {e}"))
+}
+
+/// Unlinks a pair so both sides count as spending and income again
+#[tauri::command]
+pub async fn reject_transfer(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    queries::set_transfer_status(&state.db.0, id, TransferStatus::Rejected)
+        .await
+        .map_err(|e| format!("Error rejecting transfer {id}: {e}"))
+}
+
+/// Links two transactions detection missed, such as a transfer that carried a fee
+#[tauri::command]
+pub async fn link_transfer(
+    state: tauri::State<'_, AppState>,
+    outflow_transaction_id: i64,
+    inflow_transaction_id: i64,
+) -> Result<i64, String> {
+    let mut tx = state
+        .db
+        .0
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {e}"))?;
+
+    let outflow = transactions::queries::get_transaction(&mut tx, outflow_transaction_id)
+        .await
+        .map_err(|e| format!("Error fetching transaction {outflow_transaction_id}: {e}"))?;
+    let inflow = transactions::queries::get_transaction(&mut tx, inflow_transaction_id)
+        .await
+        .map_err(|e| format!("Error fetching transaction {inflow_transaction_id}: {e}"))?;
+    validate_transfer_link(&outflow, &inflow)?;
+
+    let id = queries::link_transfer(&mut tx, outflow_transaction_id, inflow_transaction_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                "One of these transactions is already part of a confirmed transfer".to_string()
+            }
+            e => format!("Failed to link transfer: {e}"),
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(id)
+}
@@ -0,0 +1,370 @@
+use crate::transfers::types::{pick_transfer_pairs, Transfer, TransferCandidate, TransferStatus};
+use sqlx::{Pool, Sqlite, SqliteConnection, SqliteExecutor};
+
+pub async fn get_transfer_window_days<'e>(
+    executor: impl SqliteExecutor<'e>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT window_days FROM transfer_setting WHERE id = 1")
+        .fetch_one(executor)
+        .await
+}
+
+pub async fn set_transfer_window_days(
+    pool: &Pool<Sqlite>,
+    window_days: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE transfer_setting SET window_days=? WHERE id = 1")
+        .bind(window_days)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/*
+Pairs outflows with inflows of the same size on another account, posted within the
+configured number of days of each other, and stores them as suggested transfers
+
+With `only_new` just the transactions written or moved since the last scan are paired, against
+any other row, which is what saves need. Without it the whole history is checked again, as
+needed after the window changes. Transactions already in a transfer, split transactions and
+pairs the user rejected before are skipped. Returns how many transfers were found
+*/
+pub async fn detect_transfers(
+    conn: &mut SqliteConnection,
+    only_new: bool,
+) -> Result<u64, sqlx::Error> {
+    let window_days = get_transfer_window_days(&mut *conn).await?;
+    let scanned_through_id: i64 = if only_new {
+        sqlx::query_scalar("SELECT scanned_through_id FROM transfer_setting WHERE id = 1")
+            .fetch_one(&mut *conn)
+            .await?
+    } else {
+        0
+    };
+
+    // `n` is a row not scanned yet and `m` its counterpart. When both are new the pair is only
+    // taken from the outflow's side
+    let query = r#"
+        SELECT
+            CASE WHEN n.amount_cents < 0 THEN n.id ELSE m.id END AS outflow_transaction_id,
+            CASE WHEN n.amount_cents < 0 THEN m.id ELSE n.id END AS inflow_transaction_id,
+            CAST(ABS(julianday(m.date) - julianday(n.date)) AS INTEGER) AS days_apart
+        FROM 'transaction' n
+        JOIN 'transaction' m ON m.amount_cents = -n.amount_cents
+            AND m.date BETWEEN date(n.date, printf('-%d days', $1))
+                AND date(n.date, printf('+%d days', $1))
+            AND m.account_id != n.account_id
+        WHERE n.id > $2
+            AND n.amount_cents != 0
+            AND (n.amount_cents < 0 OR m.id <= $2)
+            AND n.deleted_at IS NULL
+            AND m.deleted_at IS NULL
+            AND n.id NOT IN (SELECT transaction_id FROM transfer_transaction)
+            AND m.id NOT IN (SELECT transaction_id FROM transfer_transaction)
+            AND NOT EXISTS (
+                SELECT 1 FROM transfer tr
+                WHERE (tr.outflow_transaction_id = n.id AND tr.inflow_transaction_id = m.id)
+                    OR (tr.outflow_transaction_id = m.id AND tr.inflow_transaction_id = n.id)
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM transaction_split s WHERE s.transaction_id IN (n.id, m.id)
+            )
+    "#;
+    let candidates: Vec<TransferCandidate> = sqlx::query_as(query)
+        .bind(window_days)
+        .bind(scanned_through_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let pairs = pick_transfer_pairs(candidates);
+    for pair in &pairs {
+        sqlx::query(
+            "INSERT INTO transfer (outflow_transaction_id, inflow_transaction_id, status) VALUES (?, ?, ?)",
+        )
+        .bind(pair.outflow_transaction_id)
+        .bind(pair.inflow_transaction_id)
+        .bind(TransferStatus::Suggested)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE transfer_setting SET scanned_through_id = (SELECT COALESCE(MAX(id), 0) FROM 'transaction') WHERE id = 1",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(pairs.len() as u64)
+}
+
+/// Has the next scan of new rows look at `transaction_id` again, after its amount, date or account changed
+pub async fn rescan_transfers_from(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE transfer_setting SET scanned_through_id = MIN(scanned_through_id, ? - 1) WHERE id = 1",
+    )
+    .bind(transaction_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Transfers between live transactions, newest first, optionally only those with `status`
+pub async fn get_transfers(
+    pool: &Pool<Sqlite>,
+    status: Option<TransferStatus>,
+) -> Result<Vec<Transfer>, sqlx::Error> {
+    let query = r#"
+        SELECT
+            tr.id,
+            tr.status,
+            o.id AS outflow_transaction_id,
+            o.name AS outflow_name,
+            oa.name AS outflow_account_name,
+            o.date AS outflow_date,
+            o.amount_cents AS outflow_amount_cents,
+            i.id AS inflow_transaction_id,
+            i.name AS inflow_name,
+            ia.name AS inflow_account_name,
+            i.date AS inflow_date,
+            i.amount_cents AS inflow_amount_cents
+        FROM transfer tr
+        JOIN 'transaction' o ON o.id = tr.outflow_transaction_id
+        JOIN account oa ON oa.id = o.account_id
+        JOIN 'transaction' i ON i.id = tr.inflow_transaction_id
+        JOIN account ia ON ia.id = i.account_id
+        WHERE o.deleted_at IS NULL
+            AND i.deleted_at IS NULL
+            AND ($1 IS NULL OR tr.status = $1)
+        ORDER BY o.date DESC, tr.id DESC
+    "#;
+
+    let transfers: Vec<Transfer> = sqlx::query_as(query)
+        .bind(status)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(transfers)
+}
+
+pub async fn set_transfer_status(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    status: TransferStatus,
+) -> Result<(), sqlx::Error> {
+    let res = sqlx::query("UPDATE transfer SET status=? WHERE id=?")
+        .bind(status)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/*
+Links two transactions as a confirmed transfer, which callers validate first, and returns its id
+
+Suggestions involving either transaction are rejected in favour of the user's choice. A
+transaction already in a confirmed transfer can't be linked again until that one is rejected
+*/
+pub async fn link_transfer(
+    conn: &mut SqliteConnection,
+    outflow_transaction_id: i64,
+    inflow_transaction_id: i64,
+) -> Result<i64, sqlx::Error> {
+    let query = r#"
+        UPDATE transfer
+        SET status=$1
+        WHERE status=$2
+            AND (outflow_transaction_id=$3 OR inflow_transaction_id=$4)
+            AND NOT (outflow_transaction_id=$3 AND inflow_transaction_id=$4)
+    "#;
+    sqlx::query(query)
+        .bind(TransferStatus::Rejected)
+        .bind(TransferStatus::Suggested)
+        .bind(outflow_transaction_id)
+        .bind(inflow_transaction_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let query = r#"
+        INSERT INTO transfer (outflow_transaction_id, inflow_transaction_id, status)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(outflow_transaction_id, inflow_transaction_id) DO UPDATE SET status = excluded.status
+        RETURNING id
+    "#;
+    sqlx::query_scalar(query)
+        .bind(outflow_transaction_id)
+        .bind(inflow_transaction_id)
+        .bind(TransferStatus::Confirmed)
+        .fetch_one(conn)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categories;
+    use crate::transactions;
+    use crate::transactions::types::TransactionInput;
+    use chrono::{Local, NaiveDate};
+
+    async fn add(
+        conn: &mut SqliteConnection,
+        account_id: i64,
+        amount_cents: i64,
+        date: NaiveDate,
+        category_id: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let input = TransactionInput {
+            account_id,
+            name: "WELLS FARGO CARD DES:CRD".to_owned(),
+            amount_cents,
+            date,
+        };
+        transactions::queries::create_manual_transaction(conn, &input, Some(category_id), &1).await
+    }
+
+    async fn housing_spent(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+        let overviews = categories::queries::get_category_overviews(pool).await?;
+        Ok(overviews.into_iter().find(|o| o.id == 3).unwrap().spent_cents)
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn detected_transfers_leave_spending_totals(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let today = Local::now().date_naive();
+        // The card payment is filed under Housing (3) and its credit under Uncategorized (1)
+        let payment = add(&mut conn, 1, -25000, today, 3).await?;
+        let credit = add(&mut conn, 2, 25000, today, 1).await?;
+        add(&mut conn, 1, -100000, today, 3).await?;
+        // Same amount but nine days apart
+        let date = |day| NaiveDate::from_ymd_opt(2020, 1, day).unwrap();
+        add(&mut conn, 1, -777, date(1), 1).await?;
+        add(&mut conn, 2, 777, date(10), 1).await?;
+        assert_eq!(housing_spent(&pool).await?, 125000);
+
+        assert_eq!(detect_transfers(&mut conn, true).await?, 1);
+        assert_eq!(detect_transfers(&mut conn, false).await?, 0, "pairs are only found once");
+        assert_eq!(housing_spent(&pool).await?, 100000);
+
+        let transfers = get_transfers(&pool, Some(TransferStatus::Suggested)).await?;
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(
+            (transfers[0].outflow_transaction_id, transfers[0].inflow_transaction_id),
+            (payment, credit)
+        );
+        assert_eq!(transfers[0].inflow_account_name, "Manual Savings");
+
+        // Rejected pairs count again and aren't suggested a second time
+        set_transfer_status(&pool, transfers[0].id, TransferStatus::Rejected).await?;
+        assert_eq!(housing_spent(&pool).await?, 125000);
+        assert_eq!(detect_transfers(&mut conn, false).await?, 0);
+
+        // Rows already scanned are only paired again by a full scan
+        set_transfer_window_days(&pool, 10).await?;
+        assert_eq!(detect_transfers(&mut conn, true).await?, 0);
+        assert_eq!(detect_transfers(&mut conn, false).await?, 1);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn new_rows_pair_with_either_side_already_scanned(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let date = |day| NaiveDate::from_ymd_opt(2026, 2, day).unwrap();
+        let payment = add(&mut conn, 1, -4000, date(2), 1).await?;
+        let refund = add(&mut conn, 2, 1500, date(2), 1).await?;
+        assert_eq!(detect_transfers(&mut conn, true).await?, 0);
+
+        // A new inflow pairs with the scanned outflow and a new outflow with the scanned inflow
+        let credit = add(&mut conn, 2, 4000, date(4), 1).await?;
+        let transfer_out = add(&mut conn, 1, -1500, date(1), 1).await?;
+        assert_eq!(detect_transfers(&mut conn, true).await?, 2);
+
+        let mut pairs: Vec<(i64, i64)> = get_transfers(&pool, None)
+            .await?
+            .iter()
+            .map(|t| (t.outflow_transaction_id, t.inflow_transaction_id))
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(payment, credit), (transfer_out, refund)]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn edited_rows_are_scanned_again(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let payment = add(&mut conn, 1, -4000, date, 1).await?;
+        let credit = add(&mut conn, 2, 3900, date, 1).await?;
+        add(&mut conn, 1, -60, date, 1).await?;
+        assert_eq!(detect_transfers(&mut conn, true).await?, 0);
+
+        // Renaming leaves the row scanned, fixing the amount has it paired
+        let mut input = TransactionInput {
+            account_id: 2,
+            name: "CARD PAYMENT".to_owned(),
+            amount_cents: 3900,
+            date,
+        };
+        transactions::queries::update_transaction(&mut conn, credit, &input).await?;
+        let scanned: i64 =
+            sqlx::query_scalar("SELECT scanned_through_id FROM transfer_setting WHERE id = 1")
+                .fetch_one(&pool)
+                .await?;
+        assert!(scanned >= credit);
+
+        input.amount_cents = 4000;
+        transactions::queries::update_transaction(&mut conn, credit, &input).await?;
+        assert_eq!(detect_transfers(&mut conn, true).await?, 1);
+
+        let transfers = get_transfers(&pool, None).await?;
+        assert_eq!(
+            (transfers[0].outflow_transaction_id, transfers[0].inflow_transaction_id),
+            (payment, credit)
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("plaid_sync")))]
+    async fn manual_links_replace_suggestions(
+        pool: Pool<Sqlite>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = pool.acquire().await?;
+        let date = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let payment = add(&mut conn, 1, -5000, date, 1).await?;
+        let wrong_credit = add(&mut conn, 2, 5000, date, 1).await?;
+        let right_credit = add(&mut conn, 2, 4950, date, 1).await?;
+        detect_transfers(&mut conn, true).await?;
+
+        let id = link_transfer(&mut conn, payment, right_credit).await?;
+
+        let transfers = get_transfers(&pool, None).await?;
+        let status_of = |inflow| {
+            transfers
+                .iter()
+                .find(|t| t.inflow_transaction_id == inflow)
+                .map(|t| t.status)
+        };
+        assert_eq!(status_of(right_credit), Some(TransferStatus::Confirmed));
+        assert_eq!(status_of(wrong_credit), Some(TransferStatus::Rejected));
+        assert_eq!(transfers.iter().find(|t| t.id == id).unwrap().inflow_transaction_id, right_credit);
+
+        // Linking the same pair again keeps the one transfer
+        assert_eq!(link_transfer(&mut conn, payment, right_credit).await?, id);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::types::{Cents, Transaction};
+use chrono::NaiveDate;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum TransferStatus {
+    /// Found by detection and not yet reviewed. Already left out of totals
+    Suggested,
+    Confirmed,
+    /// Not a transfer after all, so both sides count as spending and income again
+    Rejected,
+}
+
+/// Two transactions that could be the sides of one transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::FromRow)]
+pub struct TransferCandidate {
+    pub outflow_transaction_id: i64,
+    pub inflow_transaction_id: i64,
+    pub days_apart: i64,
+}
+
+/*
+Picks pairs so that no transaction is used twice
+
+Closer dates win, then older transactions, so the same candidates always give the same
+pairs. A payment that could match either of two identical credits takes the nearer one
+*/
+pub fn pick_transfer_pairs(mut candidates: Vec<TransferCandidate>) -> Vec<TransferCandidate> {
+    candidates.sort_by_key(|c| (c.days_apart, c.outflow_transaction_id, c.inflow_transaction_id));
+
+    let mut used = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|c| {
+            if used.contains(&c.outflow_transaction_id) || used.contains(&c.inflow_transaction_id) {
+                return false;
+            }
+            used.insert(c.outflow_transaction_id);
+            used.insert(c.inflow_transaction_id);
+            true
+        })
+        .collect()
+}
+
+/*
+Checks that two transactions can be linked by hand as one transfer
+
+The amounts don't have to match since a transfer can carry a fee, but money has to leave one
+account and arrive in another
+*/
+pub fn validate_transfer_link(outflow: &Transaction, inflow: &Transaction) -> Result<(), String> {
+    if outflow.deleted_at.is_some() || inflow.deleted_at.is_some() {
+        return Err("Deleted transactions can't be linked".to_string());
+    }
+    if outflow.account_id() == inflow.account_id() {
+        return Err("A transfer has to move money between two different accounts".to_string());
+    }
+    if !outflow.amount.0.is_sign_negative() || inflow.amount.0.is_sign_negative() {
+        return Err("A transfer needs an outflow and an inflow".to_string());
+    }
+    Ok(())
+}
+
+/// A linked pair with enough of each side to show it
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow, serde::Serialize)]
+pub struct Transfer {
+    pub id: i64,
+    pub status: TransferStatus,
+    pub outflow_transaction_id: i64,
+    pub outflow_name: String,
+    pub outflow_account_name: String,
+    pub outflow_date: NaiveDate,
+    #[sqlx(rename = "outflow_amount_cents")]
+    pub outflow_amount: Cents,
+    pub inflow_transaction_id: i64,
+    pub inflow_name: String,
+    pub inflow_account_name: String,
+    pub inflow_date: NaiveDate,
+    #[sqlx(rename = "inflow_amount_cents")]
+    pub inflow_amount: Cents,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::dec;
+
+    fn candidate(outflow: i64, inflow: i64, days_apart: i64) -> TransferCandidate {
+        TransferCandidate {
+            outflow_transaction_id: outflow,
+            inflow_transaction_id: inflow,
+            days_apart,
+        }
+    }
+
+    #[test]
+    fn picks_nearest_pairs_without_reusing_transactions() {
+        // Three equal card payments competing for two credits on the card
+        let pairs = pick_transfer_pairs(vec![
+            candidate(1, 10, 2),
+            candidate(1, 11, 30),
+            candidate(2, 10, 1),
+            candidate(2, 11, 1),
+            candidate(3, 11, 0),
+        ]);
+
+        assert_eq!(pairs, vec![candidate(3, 11, 0), candidate(2, 10, 1)]);
+    }
+
+    #[test]
+    fn links_need_an_outflow_and_an_inflow_on_different_accounts() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 2).unwrap();
+        let payment = Transaction::new(1, "CARD PAYMENT".to_owned(), Cents(dec!(-250)), date, 1, 1);
+        let credit = Transaction::new(2, "PAYMENT THANK YOU".to_owned(), Cents(dec!(249)), date, 2, 1);
+        let same_account = Transaction::new(3, "REFUND".to_owned(), Cents(dec!(250)), date, 1, 1);
+
+        assert_eq!(validate_transfer_link(&payment, &credit), Ok(()));
+        assert!(validate_transfer_link(&credit, &payment).is_err());
+        assert!(validate_transfer_link(&payment, &same_account).is_err());
+    }
+}
@@ -31,12 +31,7 @@ impl<'q> Encode<'q, Sqlite> for Cents {
         &self,
         args: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'q>>,
     ) -> Result<IsNull, Box<dyn std::error::Error + Send + Sync>> {
-        // Multiply by 100 and convert to i64
-        let cents = (self.0 * Decimal::from(100))
-            .to_i64()
-            .expect("Decimal overflow when converting to cents");
-
-        <i64 as Encode<Sqlite>>::encode(cents, args)
+        <i64 as Encode<Sqlite>>::encode(self.to_cents(), args)
     }
 }
 
@@ -56,6 +51,17 @@ impl Cents {
     pub fn from_dollars_f64(dollars: f64) -> Option<Self> {
         Decimal::from_f64(dollars).map(|d| Cents(d.round_dp(2)))
     }
+
+    pub fn from_cents(cents: i64) -> Self {
+        Cents(Decimal::new(cents, 2))
+    }
+
+    pub fn to_cents(self) -> i64 {
+        // Multiply by 100 and convert to i64
+        (self.0 * Decimal::from(100))
+            .to_i64()
+            .expect("Decimal overflow when converting to cents")
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
@@ -78,6 +84,13 @@ pub struct Transaction {
     pub deleted_at: Option<NaiveDate>,
     account_id: i64,
     category_id: i64,
+    pub plaid_category_primary: Option<String>,
+    pub plaid_category_detailed: Option<String>,
+    /// Readable merchant name cleaned up from `name`
+    #[sqlx(default)]
+    pub payee: Option<String>,
+    #[sqlx(default)]
+    pub merchant_id: Option<i64>,
 }
 
 impl Transaction {
@@ -116,6 +129,10 @@ impl Transaction {
             deleted_at: None,
             account_id,
             category_id,
+            plaid_category_primary: None,
+            plaid_category_detailed: None,
+            payee: None,
+            merchant_id: None,
         }
     }
 }
@@ -139,6 +156,12 @@ pub struct TransactionWithAccount {
     account_name: String
 }
 
+impl TransactionWithAccount {
+    pub fn account_name(&self) -> &String {
+        &self.account_name
+    }
+}
+
 impl Deref for TransactionWithAccount {
     type Target = Transaction;
 
@@ -0,0 +1,22 @@
+import type { BulkChange, BulkOperation, BulkSelection } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+const getBulkOperations = async (): Promise<BulkOperation[]> => {
+    return await invoke("get_bulk_operations") as BulkOperation[];
+};
+
+// The returned operation's id can be passed to undoBulkOperation
+const bulkUpdateTransactions = async (selection: BulkSelection, change: BulkChange): Promise<BulkOperation> => {
+    return await invoke("bulk_update_transactions", { selection, change }) as BulkOperation;
+};
+
+// Resolves to the number of transactions put back
+const undoBulkOperation = async (operationId: number): Promise<number> => {
+    return await invoke("undo_bulk_operation", { operationId }) as number;
+};
+
+export const bulkApi = {
+    getBulkOperations,
+    bulkUpdateTransactions,
+    undoBulkOperation,
+};
@@ -1,22 +1,85 @@
-import type { TransactionImport } from "$lib/types";
+import type { AcceptedRow, ColumnMapping, ImportBatch, Detection, ImportedStatement, ImportPreview, ImportProfile, ImporterInfo, ImportSummary, ParseReport } from "$lib/types";
 import { invoke } from "@tauri-apps/api/core";
 import { categoriesApi } from "./categories";
 
-const importTransactions = async (filePath: string, bankName: string, accountId: number): Promise<TransactionImport[]> => {
+const getImporters = async (): Promise<ImporterInfo[]> => {
+    return await invoke("get_importers") as ImporterInfo[];
+};
+
+// Rejects with a DetectionError when the format is unknown or ambiguous
+const detectStatementFormat = async (filePath: string): Promise<Detection> => {
+    return await invoke("detect_statement_format", { filePath: filePath }) as Detection;
+};
+
+// Lines that couldn't be parsed come back as rejected so they can be shown to the user
+const importTransactions = async (filePath: string, importerId: string, accountId: number): Promise<ImportedStatement> => {
     const uncategorizedCategory = await categoriesApi.getCategoryByName("Uncategorized");
-    const transactions =  await invoke("import_transactions", {
+    const report = await invoke("import_transactions", {
         filePath: filePath,
-        bankName: bankName,
-    }) as Omit<TransactionImport, "account_id" | "category_id">[];
+        importerId: importerId,
+    }) as ParseReport;
 
-    const populatedTransactions = transactions.map(transaction => ({ 
+    const populatedTransactions = report.transactions.map(transaction => ({ 
         ...transaction, 
         date: new Date(transaction.date), 
         account_id: accountId,
         category_id: uncategorizedCategory?.id ?? 0
     }));
 
-    return populatedTransactions;
+    return { transactions: populatedTransactions, rejected: report.rejected };
+};
+
+const saveImportedTransactions = async (filePath: string, importerId: string, accountId: number): Promise<ImportSummary> => {
+    return await invoke("save_imported_transactions", {
+        filePath: filePath,
+        importerId: importerId,
+        accountId: accountId,
+    }) as ImportSummary;
+};
+
+// Stages the file server-side, nothing is saved until commitImport
+const previewImport = async (filePath: string, importerId: string, accountId: number): Promise<ImportPreview> => {
+    return await invoke("preview_import", {
+        filePath: filePath,
+        importerId: importerId,
+        accountId: accountId,
+    }) as ImportPreview;
+};
+
+const commitImport = async (sessionId: number, acceptedRows: AcceptedRow[]): Promise<ImportSummary> => {
+    return await invoke("commit_import", {
+        sessionId: sessionId,
+        acceptedRows: acceptedRows,
+    }) as ImportSummary;
+};
+
+const discardImport = async (sessionId: number): Promise<void> => {
+    await invoke("discard_import", { sessionId });
+};
+
+const getImportBatches = async (accountId?: number): Promise<ImportBatch[]> => {
+    return await invoke("get_import_batches", { accountId: accountId ?? null }) as ImportBatch[];
+};
+
+// Resolves to the number of transactions removed
+const rollbackImportBatch = async (batchId: number): Promise<number> => {
+    return await invoke("rollback_import_batch", { batchId }) as number;
+};
+
+const getImportProfiles = async (): Promise<ImportProfile[]> => {
+    return await invoke("get_import_profiles") as ImportProfile[];
+};
+
+const createImportProfile = async (name: string, mapping: ColumnMapping): Promise<number> => {
+    return await invoke("create_import_profile", { name, mapping }) as number;
+};
+
+const updateImportProfile = async (id: number, name: string, mapping: ColumnMapping): Promise<void> => {
+    await invoke("update_import_profile", { id, name, mapping });
+};
+
+const deleteImportProfile = async (id: number): Promise<void> => {
+    await invoke("delete_import_profile", { id });
 };
 
-export { importTransactions };
\ No newline at end of file
+export { commitImport, discardImport, previewImport, getImportBatches, rollbackImportBatch, createImportProfile, deleteImportProfile, getImportProfiles, updateImportProfile, detectStatementFormat, getImporters, importTransactions, saveImportedTransactions };
\ No newline at end of file
@@ -0,0 +1,23 @@
+import type { MerchantDetails, MerchantSummary } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+// Period dates are YYYY-MM-DD and inclusive
+const getMerchants = async (periodFrom: string, periodTo: string): Promise<MerchantSummary[]> => {
+    return await invoke("get_merchants", { periodFrom, periodTo }) as MerchantSummary[];
+};
+
+// Renames the merchant and sets its default category, logo and notes
+const updateMerchant = async (id: number, details: MerchantDetails): Promise<void> => {
+    await invoke("update_merchant", { id, details });
+};
+
+// Moves the source merchant's transactions to the target and deletes the source
+const mergeMerchants = async (sourceId: number, targetId: number): Promise<void> => {
+    await invoke("merge_merchants", { sourceId, targetId });
+};
+
+export const merchantsApi = {
+    getMerchants,
+    updateMerchant,
+    mergeMerchants,
+};
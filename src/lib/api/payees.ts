@@ -0,0 +1,31 @@
+import type { PayeeRule, PayeeRuleDefinition } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+const getPayeeRules = async (): Promise<PayeeRule[]> => {
+    return await invoke("get_payee_rules") as PayeeRule[];
+};
+
+const createPayeeRule = async (rule: PayeeRuleDefinition): Promise<number> => {
+    return await invoke("create_payee_rule", { rule }) as number;
+};
+
+const updatePayeeRule = async (id: number, rule: PayeeRuleDefinition): Promise<void> => {
+    await invoke("update_payee_rule", { id, rule });
+};
+
+const deletePayeeRule = async (id: number): Promise<void> => {
+    await invoke("delete_payee_rule", { id });
+};
+
+// Rule changes only apply to existing transactions after this. Resolves to the number of payees changed
+const renormalizePayees = async (): Promise<number> => {
+    return await invoke("renormalize_payees") as number;
+};
+
+export const payeesApi = {
+    getPayeeRules,
+    createPayeeRule,
+    updatePayeeRule,
+    deletePayeeRule,
+    renormalizePayees,
+};
@@ -1,4 +1,4 @@
-import type { Account, AccountsGetResponse, LinkedInstitution, PlaidAccount } from "$lib/types";
+import type { Account, AccountsGetResponse, LinkedInstitution, PlaidAccount, PlaidCategoryMapping } from "$lib/types";
 import { invoke } from "@tauri-apps/api/core";
 
 const savePlaidCredentials = async (client_id: string, secret: string): Promise<string> => {
@@ -58,6 +58,19 @@ const syncTransactions = async (item_id: string): Promise<number> => {
   })) as number;
 }
 
+const getPlaidCategoryMappings = async (): Promise<PlaidCategoryMapping[]> => {
+  return (await invoke("get_plaid_category_mappings")) as PlaidCategoryMapping[];
+}
+
+// pfcCode is a primary (FOOD_AND_DRINK) or detailed (FOOD_AND_DRINK_GROCERIES) Plaid category
+const setPlaidCategoryMapping = async (pfcCode: string, categoryId: number): Promise<void> => {
+  await invoke("set_plaid_category_mapping", { pfcCode, categoryId });
+}
+
+const deletePlaidCategoryMapping = async (pfcCode: string): Promise<void> => {
+  await invoke("delete_plaid_category_mapping", { pfcCode });
+}
+
 export const plaidApi = {
     savePlaidCredentials,
     savePlaidClientId,
@@ -68,5 +81,8 @@ export const plaidApi = {
     getAccountsOfItem,
     getAccountsOfItemFromPlaid,
     getLinkedInstitutions,
-    addNewPlaidAccounts
+    addNewPlaidAccounts,
+    getPlaidCategoryMappings,
+    setPlaidCategoryMapping,
+    deletePlaidCategoryMapping
 };
@@ -0,0 +1,52 @@
+import type { RecurringAlert, RecurringSeries, RecurringSeriesEdit } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+const getRecurringSeries = async (): Promise<RecurringSeries[]> => {
+    return await invoke("get_recurring_series") as RecurringSeries[];
+};
+
+// Syncs and imports are checked as they are saved. Resolves to the number of series found
+const detectRecurring = async (): Promise<number> => {
+    return await invoke("detect_recurring") as number;
+};
+
+const confirmRecurringSeries = async (id: number): Promise<void> => {
+    await invoke("confirm_recurring_series", { id });
+};
+
+// Detection stops suggesting a dismissed series
+const dismissRecurringSeries = async (id: number): Promise<void> => {
+    await invoke("dismiss_recurring_series", { id });
+};
+
+// Editing a series confirms it
+const updateRecurringSeries = async (id: number, series: RecurringSeriesEdit): Promise<void> => {
+    await invoke("update_recurring_series", { id, series });
+};
+
+// Unacknowledged price changes and missed payments, newest first
+const getRecurringAlerts = async (): Promise<RecurringAlert[]> => {
+    return await invoke("get_recurring_alerts") as RecurringAlert[];
+};
+
+// Acknowledging a price change makes the new amount the expected one
+const acknowledgeRecurringAlert = async (id: number): Promise<void> => {
+    await invoke("acknowledge_recurring_alert", { id });
+};
+
+// Called with the number of new alerts after a sync or import raises any
+const onNewRecurringAlerts = async (handler: (count: number) => void): Promise<UnlistenFn> => {
+    return await listen<number>("recurring-alerts", (event) => handler(event.payload));
+};
+
+export const recurringApi = {
+    getRecurringSeries,
+    detectRecurring,
+    confirmRecurringSeries,
+    dismissRecurringSeries,
+    updateRecurringSeries,
+    getRecurringAlerts,
+    acknowledgeRecurringAlert,
+    onNewRecurringAlerts,
+};
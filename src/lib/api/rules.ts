@@ -0,0 +1,31 @@
+import type { CategorizationRule, CategoryChange, RuleDefinition } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+const getCategorizationRules = async (): Promise<CategorizationRule[]> => {
+    return await invoke("get_categorization_rules") as CategorizationRule[];
+};
+
+const createCategorizationRule = async (rule: RuleDefinition): Promise<number> => {
+    return await invoke("create_categorization_rule", { rule }) as number;
+};
+
+const updateCategorizationRule = async (id: number, rule: RuleDefinition): Promise<void> => {
+    await invoke("update_categorization_rule", { id, rule });
+};
+
+const deleteCategorizationRule = async (id: number): Promise<void> => {
+    await invoke("delete_categorization_rule", { id });
+};
+
+// With dryRun nothing is saved, the result lists what would change
+const applyCategorizationRule = async (ruleId: number, onlyUncategorized: boolean, dryRun: boolean): Promise<CategoryChange[]> => {
+    return await invoke("apply_categorization_rule", { ruleId, onlyUncategorized, dryRun }) as CategoryChange[];
+};
+
+export const rulesApi = {
+    getCategorizationRules,
+    createCategorizationRule,
+    updateCategorizationRule,
+    deleteCategorizationRule,
+    applyCategorizationRule,
+};
@@ -0,0 +1,48 @@
+import type { Tag, TagSpending } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+const getTags = async (): Promise<Tag[]> => {
+    return await invoke("get_tags") as Tag[];
+};
+
+// Rejects when another tag already has the name, ignoring case
+const createTag = async (name: string): Promise<number> => {
+    return await invoke("create_tag", { name }) as number;
+};
+
+const renameTag = async (id: number, name: string): Promise<void> => {
+    await invoke("rename_tag", { id, name });
+};
+
+const deleteTag = async (id: number): Promise<void> => {
+    await invoke("delete_tag", { id });
+};
+
+// Moves the source tag's transactions to the target and deletes the source
+const mergeTags = async (sourceId: number, targetId: number): Promise<void> => {
+    await invoke("merge_tags", { sourceId, targetId });
+};
+
+const getTransactionTags = async (transactionId: number): Promise<Tag[]> => {
+    return await invoke("get_transaction_tags", { transactionId }) as Tag[];
+};
+
+const setTransactionTags = async (transactionId: number, tagIds: number[]): Promise<void> => {
+    await invoke("set_transaction_tags", { transactionId, tagIds });
+};
+
+// Dates are YYYY-MM-DD and inclusive
+const getTagSpending = async (dateFrom: string, dateTo: string): Promise<TagSpending[]> => {
+    return await invoke("get_tag_spending", { dateFrom, dateTo }) as TagSpending[];
+};
+
+export const tagsApi = {
+    getTags,
+    createTag,
+    renameTag,
+    deleteTag,
+    mergeTags,
+    getTransactionTags,
+    setTransactionTags,
+    getTagSpending,
+};
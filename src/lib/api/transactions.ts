@@ -1,4 +1,4 @@
-import type { PaginedSortedTransactionsResponse, TransactionWithAccount } from "$lib/types";
+import type { CategorySuggestion, PaginedSortedTransactionsResponse, PageDirection, SplitLine, TransactionFilter, TransactionInput, TransactionPage, TransactionSplit, TransactionWithAccount } from "$lib/types";
 import { invoke } from "@tauri-apps/api/core";
 
 const syncTransactions = async (): Promise<void> => {
@@ -9,22 +9,83 @@ const getPaginatedSortedTransactions = async (
   page: number,
   page_size: number,
   sort_col: string | null,
-  sort_dir: string | null
+  sort_dir: string | null,
+  filter: TransactionFilter | null = null
 ): Promise<PaginedSortedTransactionsResponse> => {
   return (await invoke("get_paginated_sorted_transactions", {
     page: page,
     pageSize: page_size,
     sortCol: sort_col,
-    sortDir: sort_dir
+    sortDir: sort_dir,
+    filter: filter
   })) as PaginedSortedTransactionsResponse;
 }
 
+// Pass a page's next_cursor or prev_cursor to move from it, or null for the first page
+const getTransactionsPage = async (
+  cursor: string | null,
+  direction: PageDirection,
+  pageSize: number,
+  sortCol: string | null,
+  sortDir: string | null,
+  filter: TransactionFilter | null = null
+): Promise<TransactionPage> => {
+  return (await invoke("get_transactions_page", {
+    cursor,
+    direction,
+    pageSize,
+    sortCol,
+    sortDir,
+    filter
+  })) as TransactionPage;
+}
+
 const updateTransactionCategory = async (transactionId: number, categoryId: number): Promise<void> => {
   await invoke("update_transaction_category", { transactionId, categoryId });
 }
 
+// Resolves to null when no past correction resembles the transaction
+const suggestTransactionCategory = async (transactionId: number): Promise<CategorySuggestion | null> => {
+  return await invoke("suggest_transaction_category", { transactionId }) as CategorySuggestion | null;
+}
+
+const getTransactionSplits = async (transactionId: number): Promise<TransactionSplit[]> => {
+  return await invoke("get_transaction_splits", { transactionId }) as TransactionSplit[];
+}
+
+// Lines must add up to the transaction's amount, an empty list removes the split
+const setTransactionSplits = async (transactionId: number, splits: SplitLine[]): Promise<void> => {
+  await invoke("set_transaction_splits", { transactionId, splits });
+}
+
+// Without a category the transaction is filed by rules and past corrections. Resolves to the new id
+const createTransaction = async (transaction: TransactionInput, categoryId: number | null = null): Promise<number> => {
+  return await invoke("create_transaction", { transaction, categoryId }) as number;
+}
+
+// Edits to synced transactions are kept as overrides that later syncs leave alone
+const updateTransaction = async (transactionId: number, transaction: TransactionInput): Promise<void> => {
+  await invoke("update_transaction", { transactionId, transaction });
+}
+
+const deleteTransaction = async (transactionId: number): Promise<void> => {
+  await invoke("delete_transaction", { transactionId });
+}
+
+const restoreTransaction = async (transactionId: number): Promise<void> => {
+  await invoke("restore_transaction", { transactionId });
+}
+
 export const transactionsApi = {
   syncTransactions,
   getPaginatedSortedTransactions,
-  updateTransactionCategory
+  getTransactionsPage,
+  updateTransactionCategory,
+  suggestTransactionCategory,
+  getTransactionSplits,
+  setTransactionSplits,
+  createTransaction,
+  updateTransaction,
+  deleteTransaction,
+  restoreTransaction
 }
@@ -0,0 +1,42 @@
+import type { Transfer, TransferStatus } from "$lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+const getTransfers = async (status: TransferStatus | null = null): Promise<Transfer[]> => {
+    return await invoke("get_transfers", { status }) as Transfer[];
+};
+
+// Syncs and imports are checked as they are saved. Resolves to the number of transfers found
+const detectTransfers = async (): Promise<number> => {
+    return await invoke("detect_transfers") as number;
+};
+
+const getTransferWindowDays = async (): Promise<number> => {
+    return await invoke("get_transfer_window_days") as number;
+};
+
+const setTransferWindowDays = async (windowDays: number): Promise<void> => {
+    await invoke("set_transfer_window_days", { windowDays });
+};
+
+const confirmTransfer = async (id: number): Promise<void> => {
+    await invoke("confirm_transfer", { id });
+};
+
+const rejectTransfer = async (id: number): Promise<void> => {
+    await invoke("reject_transfer", { id });
+};
+
+// Resolves to the transfer's id
+const linkTransfer = async (outflowTransactionId: number, inflowTransactionId: number): Promise<number> => {
+    return await invoke("link_transfer", { outflowTransactionId, inflowTransactionId }) as number;
+};
+
+export const transfersApi = {
+    getTransfers,
+    detectTransfers,
+    getTransferWindowDays,
+    setTransferWindowDays,
+    confirmTransfer,
+    rejectTransfer,
+    linkTransfer,
+};
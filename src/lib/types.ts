@@ -24,6 +24,62 @@ interface Transaction {
     date: Date;
     account_id: number;
     category_id: number;
+    plaid_category_primary?: string | null;
+    plaid_category_detailed?: string | null;
+    // Readable merchant name cleaned up from name
+    payee?: string | null;
+    merchant_id?: number | null;
+}
+
+interface TransactionSplit {
+  id: number;
+  transaction_id: number;
+  category_id: number;
+  amount: number;
+  memo: string | null;
+}
+
+// Amounts are in cents with the parent transaction's sign
+interface SplitLine {
+  category_id: number;
+  amount_cents: number;
+  memo: string | null;
+}
+
+// A transaction's own fields as entered by the user, date as YYYY-MM-DD
+interface TransactionInput {
+  account_id: number;
+  name: string;
+  amount_cents: number;
+  date: string;
+}
+
+// Omitted fields and empty id lists don't filter anything
+interface TransactionFilter {
+  date_from?: string;
+  date_to?: string;
+  account_ids?: number[];
+  category_ids?: number[];
+  // Matches transactions with any of these tags
+  tag_ids?: number[];
+  merchant_ids?: number[];
+  // Signed like transaction amounts, so spending is negative
+  min_amount_cents?: number;
+  max_amount_cents?: number;
+  pending?: boolean;
+  search?: string;
+}
+
+type PageDirection = "Next" | "Prev";
+
+interface TransactionPage {
+  transactions: TransactionWithAccount[];
+  // Null on the last page
+  next_cursor: string | null;
+  // Null on the first page
+  prev_cursor: string | null;
+  // Only counted for the first page
+  num_transactions: number | null;
 }
 
 interface TransactionWithAccount {
@@ -45,6 +101,298 @@ interface PaginedSortedTransactionsResponse {
 
 type TransactionImport = Omit<Transaction, "id">;
 
+interface ImporterInfo {
+  id: string;
+  display_name: string;
+}
+
+interface Detection {
+  importer_id: string;
+  display_name: string;
+  confidence: number;
+}
+
+type DetectionError =
+  | { kind: "unreadable"; message: string }
+  | { kind: "no_match" }
+  | { kind: "ambiguous"; candidates: Detection[] };
+
+type AmountColumns =
+  | { kind: "single"; column: number; sign_convention: "DEBITS_NEGATIVE" | "DEBITS_POSITIVE" }
+  | { kind: "split"; debit_column: number; credit_column: number };
+
+interface ColumnMapping {
+  delimiter: string;
+  skip_rows: number;
+  date_column: number;
+  date_format: string;
+  amount: AmountColumns;
+  description_columns: number[];
+}
+
+interface ImportProfile {
+  id: number;
+  name: string;
+  mapping: ColumnMapping;
+}
+
+interface RejectedRow {
+  line: number;
+  raw: string;
+  reason: string;
+}
+
+// A parsed statement ready for review, with the lines that couldn't be read
+interface ImportedStatement {
+  transactions: TransactionImport[];
+  rejected: RejectedRow[];
+}
+
+interface StatementBalance {
+  amount: number;
+  as_of: string;
+}
+
+interface ImportSplit {
+  category: string | null;
+  memo: string | null;
+  amount: number;
+}
+
+interface ParseReport {
+  transactions: (Omit<TransactionImport, "account_id" | "category_id"> & {
+    external_id: string | null;
+    category: string | null;
+    splits: ImportSplit[];
+  })[];
+  rejected: RejectedRow[];
+  statement_balance: StatementBalance | null;
+}
+
+interface Reconciliation {
+  statement_balance: number;
+  as_of: string;
+  account_balance: number;
+  difference: number;
+}
+
+type RowStatus = "Valid" | "Invalid";
+
+interface StagedRow {
+  row_index: number;
+  date: string | null;
+  name: string;
+  amount: number | null;
+  proposed_category_id: number;
+  duplicate: boolean;
+  status: RowStatus;
+  reason: string | null;
+}
+
+interface ImportSession {
+  id: number;
+  account_id: number;
+  importer_id: string;
+  file_path: string;
+  file_hash: string;
+  statement_balance: number | null;
+  statement_balance_as_of: string | null;
+  created_at: string;
+  expires_at: string;
+}
+
+interface ImportPreview {
+  session: ImportSession;
+  rows: StagedRow[];
+}
+
+interface ImportBatch {
+  id: number;
+  account_id: number;
+  account_name: string;
+  importer_id: string;
+  file_name: string;
+  file_hash: string;
+  row_count: number;
+  imported_at: string;
+  rolled_back_at: string | null;
+}
+
+interface Tag {
+  id: number;
+  name: string;
+}
+
+interface TagSpending {
+  tag_id: number;
+  name: string;
+  spent_cents: number;
+  transaction_count: number;
+}
+
+// The parts of a merchant the user edits
+interface MerchantDetails {
+  name: string;
+  default_category_id: number | null;
+  logo_url: string | null;
+  notes: string | null;
+}
+
+// Spending is positive, net of refunds and leaving out transfers
+interface MerchantSummary extends MerchantDetails {
+  id: number;
+  transaction_count: number;
+  lifetime_spent_cents: number;
+  period_spent_cents: number;
+}
+
+type Cadence = "Weekly" | "Biweekly" | "Monthly" | "Annual";
+type RecurringStatus = "Active" | "Lapsed";
+// Suggested series are kept up to date by detection, reviewed ones keep the user's changes
+type RecurringReview = "Suggested" | "Confirmed" | "Dismissed";
+
+// Amounts are in cents and negative like the charges themselves, dates are YYYY-MM-DD
+interface RecurringSeriesEdit {
+  name: string;
+  cadence: Cadence;
+  expected_amount_cents: number;
+}
+
+interface RecurringSeries extends RecurringSeriesEdit {
+  id: number;
+  payee: string;
+  last_date: string;
+  next_expected_date: string;
+  status: RecurringStatus;
+  review: RecurringReview;
+}
+
+type AlertKind = "PriceChange" | "MissedPayment";
+
+// Amounts are negative like the charges themselves, amount_cents is null for missed payments
+interface RecurringAlert {
+  id: number;
+  series_id: number;
+  series_name: string;
+  kind: AlertKind;
+  transaction_id: number | null;
+  expected_amount_cents: number;
+  amount_cents: number | null;
+  expected_date: string;
+  created_at: string;
+}
+
+type TransferStatus = "Suggested" | "Confirmed" | "Rejected";
+
+// Amounts are signed, so the outflow is negative
+interface Transfer {
+  id: number;
+  status: TransferStatus;
+  outflow_transaction_id: number;
+  outflow_name: string;
+  outflow_account_name: string;
+  outflow_date: string;
+  outflow_amount: number;
+  inflow_transaction_id: number;
+  inflow_name: string;
+  inflow_account_name: string;
+  inflow_date: string;
+  inflow_amount: number;
+}
+
+// For Regex rules $1 in payee is replaced by the first capture group
+interface PayeeRuleDefinition {
+  pattern: string;
+  description_match: DescriptionMatch;
+  payee: string;
+}
+
+interface PayeeRule extends PayeeRuleDefinition {
+  id: number;
+}
+
+type BulkAction = "SetCategory" | "Delete" | "Restore" | "AddTag" | "RemoveTag";
+
+// category_id is required by SetCategory, tag_id by AddTag and RemoveTag
+interface BulkChange {
+  action: BulkAction;
+  category_id: number | null;
+  tag_id: number | null;
+}
+
+type BulkSelection = { Ids: number[] } | { Filter: TransactionFilter };
+
+interface BulkOperation extends BulkChange {
+  id: number;
+  transaction_count: number;
+  // Selected split transactions a recategorize left alone
+  skipped_split_count: number;
+  created_at: string;
+  undone_at: string | null;
+}
+
+type DescriptionMatch = "Contains" | "Regex";
+
+type AmountSign = "Debit" | "Credit";
+
+// Conditions left null match any transaction; amount bounds apply to the absolute amount
+interface RuleDefinition {
+  name: string;
+  priority: number;
+  enabled: boolean;
+  description_pattern: string | null;
+  description_match: DescriptionMatch;
+  merchant_entity_id: string | null;
+  account_id: number | null;
+  min_amount_cents: number | null;
+  max_amount_cents: number | null;
+  amount_sign: AmountSign | null;
+  category_id: number;
+}
+
+interface CategorizationRule extends RuleDefinition {
+  id: number;
+}
+
+interface CategoryChange {
+  transaction_id: number;
+  name: string;
+  date: string;
+  from_category_id: number;
+  from_category_name: string;
+  to_category_id: number;
+  to_category_name: string;
+}
+
+type SuggestionSource = "Merchant" | "Description";
+
+interface CategorySuggestion {
+  category_id: number;
+  // Between 0 and 1
+  confidence: number;
+  source: SuggestionSource;
+}
+
+interface PlaidCategoryMapping {
+  pfc_code: string;
+  category_id: number;
+}
+
+interface AcceptedRow {
+  row_index: number;
+  category_id: number | null;
+}
+
+interface ImportSummary {
+  inserted: number;
+  skipped: number;
+  failed: number;
+  // Rows written without their file's splits, as the splits didn't add up to the amount
+  unbalanced_splits: number;
+  rejected: RejectedRow[];
+  reconciliation: Reconciliation | null;
+}
+
 interface PlaidAccount {
   account_id: string;
   balances: {
@@ -101,4 +449,4 @@ interface DropdownOption {
     content?: Snippet;
 }
 
-export type { Category, CategoryDetails, CategoryOverview, Transaction, TransactionImport, AccountType, Account, DropdownOption, PlaidAccount, PlaidItem, AccountsGetResponse, LinkedInstitution, TransactionWithAccount, PaginedSortedTransactionsResponse };
+export type { Category, CategoryDetails, CategoryOverview, Transaction, TransactionSplit, SplitLine, Tag, TagSpending, MerchantDetails, MerchantSummary, Cadence, RecurringReview, RecurringSeries, RecurringSeriesEdit, RecurringStatus, AlertKind, RecurringAlert, Transfer, TransferStatus, TransactionInput, TransactionFilter, PageDirection, TransactionPage, TransactionImport, AmountColumns, ColumnMapping, ImportProfile, Detection, DetectionError, ImporterInfo, AcceptedRow, AmountSign, BulkAction, BulkChange, BulkOperation, BulkSelection, CategorizationRule, CategoryChange, CategorySuggestion, SuggestionSource, DescriptionMatch, PayeeRule, PayeeRuleDefinition, RuleDefinition, ImportBatch, ImportedStatement, ImportPreview, ImportSession, ImportSplit, ImportSummary, ParseReport, Reconciliation, RejectedRow, RowStatus, StagedRow, StatementBalance, AccountType, Account, DropdownOption, PlaidAccount, PlaidCategoryMapping, PlaidItem, AccountsGetResponse, LinkedInstitution, TransactionWithAccount, PaginedSortedTransactionsResponse };